@@ -0,0 +1,216 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use crate::note::Note;
+use serde::{Deserialize, Serialize};
+
+/// Valor de saida para o sopro confortavel, metade da escala midi
+const COMFORTABLE_OUTPUT: u8 = 64;
+/// Fracao das amostras descartadas no topo, evita que um pico isolado vire o maximo
+const PEAK_DISCARD: f32 = 0.05;
+
+/// Curva de resposta do sopro de um paciente.
+///
+/// O que fica abaixo do `noise_floor` vira 0, o sopro `comfortable` vira o meio
+/// da escala e o `maximum` vira a velocity maxima, assim as metas de intensidade
+/// sao relativas a capacidade de cada paciente.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreathCalibration {
+    pub noise_floor: u8,
+    pub comfortable: u8,
+    pub maximum: u8,
+}
+
+impl Default for BreathCalibration {
+    /// Praticamente linear, usado quando o paciente nao foi calibrado
+    fn default() -> Self {
+        Self {
+            noise_floor: 0,
+            comfortable: COMFORTABLE_OUTPUT,
+            maximum: Note::MAX_VELOCITY,
+        }
+    }
+}
+
+impl BreathCalibration {
+    pub fn validated(self) -> ArduinoCommResult<Self> {
+        if self.noise_floor < self.comfortable
+            && self.comfortable < self.maximum
+            && self.maximum <= Note::MAX_VELOCITY
+        {
+            Ok(self)
+        } else {
+            Err(ArduinoCommunicationError::InvalidCalibration(format!(
+                "expected noise floor {} < comfortable {} < maximum {}",
+                self.noise_floor, self.comfortable, self.maximum
+            )))
+        }
+    }
+
+    /// Aplica a curva na pressao crua recebida do dispositivo
+    pub fn apply(&self, raw: u8) -> u8 {
+        if raw <= self.noise_floor {
+            return 0;
+        }
+        let (from, to, out_from, out_to) = if raw <= self.comfortable {
+            (self.noise_floor, self.comfortable, 0, COMFORTABLE_OUTPUT)
+        } else {
+            (
+                self.comfortable,
+                self.maximum,
+                COMFORTABLE_OUTPUT,
+                Note::MAX_VELOCITY,
+            )
+        };
+        let position = (raw.min(to) - from) as f32 / (to - from) as f32;
+        let out = out_from as f32 + position * (out_to - out_from) as f32;
+        (out.round() as u8).max(1)
+    }
+}
+
+/// Etapas da calibracao, cada uma grava o paciente fazendo uma coisa
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CalibrationStep {
+    /// Sem soprar, mede o ruido do sensor
+    Resting,
+    /// Soprando de forma confortavel, sem esforço
+    Comfortable,
+    /// Soprando o mais forte que conseguir
+    Maximum,
+}
+
+/// Grava as amostras de pressao de cada etapa e calcula a [BreathCalibration]
+#[derive(Debug, Clone, Default)]
+pub struct BreathCalibrator {
+    current: Option<(CalibrationStep, Vec<u8>)>,
+    noise_floor: Option<u8>,
+    comfortable: Option<u8>,
+    maximum: Option<u8>,
+}
+
+impl BreathCalibrator {
+    /// Começa a gravar uma etapa, descartando a etapa que estava sendo gravada
+    pub fn start_step(&mut self, step: CalibrationStep) {
+        self.current = Some((step, vec![]));
+    }
+
+    pub fn receive_sample(&mut self, sample: u8) {
+        if let Some((_, samples)) = self.current.as_mut() {
+            samples.push(sample);
+        }
+    }
+
+    /// Termina a etapa atual e retorna o valor medido nela
+    pub fn finish_step(&mut self) -> ArduinoCommResult<u8> {
+        let Some((step, mut samples)) = self.current.take() else {
+            return Err(ArduinoCommunicationError::InvalidCalibration(
+                "no calibration step is being recorded".to_string(),
+            ));
+        };
+        samples.sort_unstable();
+        let floor = self.noise_floor.unwrap_or_default();
+        let value = match step {
+            CalibrationStep::Resting => Self::peak(&samples),
+            CalibrationStep::Comfortable => {
+                let blowing: Vec<u8> = samples.into_iter().filter(|s| *s > floor).collect();
+                blowing.get(blowing.len() / 2).copied()
+            }
+            CalibrationStep::Maximum => Self::peak(&samples),
+        };
+        let Some(value) = value else {
+            return Err(ArduinoCommunicationError::InvalidCalibration(format!(
+                "no breath samples were recorded during {:?}",
+                step
+            )));
+        };
+        match step {
+            CalibrationStep::Resting => self.noise_floor = Some(value),
+            CalibrationStep::Comfortable => self.comfortable = Some(value),
+            CalibrationStep::Maximum => self.maximum = Some(value),
+        }
+        Ok(value)
+    }
+
+    /// Curva com as etapas gravadas, todas precisam ter sido feitas
+    pub fn calibration(&self) -> ArduinoCommResult<BreathCalibration> {
+        let (Some(noise_floor), Some(comfortable), Some(maximum)) =
+            (self.noise_floor, self.comfortable, self.maximum)
+        else {
+            return Err(ArduinoCommunicationError::InvalidCalibration(
+                "all calibration steps must be recorded".to_string(),
+            ));
+        };
+        BreathCalibration {
+            noise_floor,
+            comfortable,
+            maximum,
+        }
+        .validated()
+    }
+
+    /// Maior valor sem os picos isolados, `samples` precisa estar ordenado
+    fn peak(samples: &[u8]) -> Option<u8> {
+        let discard = (samples.len() as f32 * PEAK_DISCARD) as usize;
+        samples.len().checked_sub(discard + 1).map(|i| samples[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_the_patient_range_to_the_midi_range() {
+        let calibration = BreathCalibration {
+            noise_floor: 10,
+            comfortable: 40,
+            maximum: 80,
+        }
+        .validated()
+        .unwrap();
+        assert_eq!(calibration.apply(0), 0);
+        assert_eq!(calibration.apply(10), 0);
+        assert_eq!(calibration.apply(11), 2);
+        assert_eq!(calibration.apply(40), 64);
+        assert_eq!(calibration.apply(80), 127);
+        assert_eq!(calibration.apply(120), 127);
+        assert_eq!(BreathCalibration::default().apply(100), 100);
+        assert!(BreathCalibration {
+            noise_floor: 50,
+            comfortable: 40,
+            maximum: 80,
+        }
+        .validated()
+        .is_err());
+    }
+
+    #[test]
+    fn calibrates_from_recorded_steps() {
+        let mut calibrator = BreathCalibrator::default();
+        calibrator.start_step(CalibrationStep::Resting);
+        for s in [0, 2, 3, 1, 4] {
+            calibrator.receive_sample(s);
+        }
+        assert_eq!(calibrator.finish_step().unwrap(), 4);
+        assert!(calibrator.calibration().is_err());
+
+        calibrator.start_step(CalibrationStep::Comfortable);
+        for s in [0, 30, 35, 40, 0] {
+            calibrator.receive_sample(s);
+        }
+        assert_eq!(calibrator.finish_step().unwrap(), 35);
+
+        calibrator.start_step(CalibrationStep::Maximum);
+        for s in (50..=90).chain([127]) {
+            calibrator.receive_sample(s);
+        }
+        assert_eq!(calibrator.finish_step().unwrap(), 89);
+        assert!(calibrator.finish_step().is_err());
+        assert_eq!(
+            calibrator.calibration().unwrap(),
+            BreathCalibration {
+                noise_floor: 4,
+                comfortable: 35,
+                maximum: 89,
+            }
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use crate::midi_message::{MidiMessage, MidiMessageKind};
+use crate::midi_subscriptions::MidiSubscriptions;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_PRESSURE_SAMPLE_RATE: u32 = 50; // Hz
+const MIN_SAMPLE_RATE: u32 = 1;
+const MAX_SAMPLE_RATE: u32 = 1_000;
+
+/// De onde vem a pressao do sopro enviada pelo dispositivo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PressureSource {
+    /// Usa a velocity das notas, a pressao so muda no inicio e fim de cada nota
+    #[default]
+    NoteVelocity,
+    /// Um control change continuo, normalmente o 2 (breath controller)
+    ControlChange(u8),
+    /// Channel pressure (aftertouch de canal)
+    ChannelPressure,
+}
+
+impl PressureSource {
+    pub fn kinds(&self) -> Vec<MidiMessageKind> {
+        match self {
+            PressureSource::NoteVelocity => MidiMessageKind::NOTES.to_vec(),
+            PressureSource::ControlChange(_) => vec![MidiMessageKind::ControlChange],
+            PressureSource::ChannelPressure => vec![MidiMessageKind::ChannelPressure],
+        }
+    }
+
+    /// Le a pressao da mensagem, [None] se ela nao for desta fonte
+    pub fn read(&self, message: &MidiMessage) -> Option<u8> {
+        match (self, message) {
+            (PressureSource::NoteVelocity, MidiMessage::NoteOn { velocity, .. }) => Some(*velocity),
+            (PressureSource::NoteVelocity, MidiMessage::NoteOff { .. }) => Some(0),
+            (
+                PressureSource::ControlChange(number),
+                MidiMessage::ControlChange {
+                    controller, value, ..
+                },
+            ) if controller == number => Some(*value),
+            (PressureSource::ChannelPressure, MidiMessage::ChannelPressure { pressure, .. }) => {
+                Some(*pressure)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Ultimo valor de pressao recebido, compartilhado entre a leitura do dispositivo
+/// e o [PressureSampler]
+#[derive(Debug, Clone, Default)]
+pub struct PressureInput {
+    value: Arc<AtomicU8>,
+}
+
+impl PressureInput {
+    pub fn set(&self, value: u8) {
+        self.value.store(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u8 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+impl MidiSubscriptions {
+    /// Atualiza o `input` com a pressao lida da `source`
+    pub fn on_pressure(self, source: PressureSource, input: PressureInput) -> Self {
+        self.subscribe(&source.kinds(), move |m| {
+            if let Some(v) = source.read(m) {
+                input.set(v);
+            }
+        })
+    }
+}
+
+/// Le o [PressureInput] em uma taxa fixa, independente de quantas mensagens
+/// o dispositivo envia, e entrega cada amostra para o callback.
+///
+/// A thread para quando o sampler é dropado.
+pub struct PressureSampler {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+    interval: Duration,
+}
+
+impl PressureSampler {
+    pub fn start<F: Fn(u8) + Send + 'static>(
+        input: PressureInput,
+        sample_rate: u32,
+        callback: F,
+    ) -> Self {
+        let rate = sample_rate.clamp(MIN_SAMPLE_RATE, MAX_SAMPLE_RATE);
+        let interval = Duration::from_secs(1) / rate;
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let handle = std::thread::spawn(move || {
+            let mut next = Instant::now() + interval;
+            while thread_running.load(Ordering::Relaxed) {
+                callback(input.get());
+                let now = Instant::now();
+                if next > now {
+                    std::thread::sleep(next - now);
+                    next += interval;
+                } else {
+                    // atrasou, recomeça a contagem ao inves de tentar compensar
+                    next = now + interval;
+                }
+            }
+        });
+        Self {
+            running,
+            handle: Some(handle),
+            interval,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(h) = self.handle.take() {
+            let _ = h.join();
+        }
+    }
+}
+
+impl Drop for PressureSampler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn reads_only_the_configured_source() {
+        let cc = MidiMessage::ControlChange {
+            channel: 0,
+            controller: 2,
+            value: 90,
+        };
+        let pressure = MidiMessage::ChannelPressure {
+            channel: 0,
+            pressure: 30,
+        };
+        assert_eq!(PressureSource::ControlChange(2).read(&cc), Some(90));
+        assert_eq!(PressureSource::ControlChange(7).read(&cc), None);
+        assert_eq!(PressureSource::ChannelPressure.read(&pressure), Some(30));
+        assert_eq!(PressureSource::NoteVelocity.read(&pressure), None);
+    }
+
+    #[test]
+    fn samples_at_a_fixed_rate() {
+        let input = PressureInput::default();
+        input.set(64);
+        let samples = Arc::new(Mutex::new(vec![]));
+        let s = samples.clone();
+        let sampler = PressureSampler::start(input.clone(), 100, move |v| {
+            s.lock().unwrap().push(v);
+        });
+        std::thread::sleep(Duration::from_millis(205));
+        drop(sampler);
+        let samples = samples.lock().unwrap();
+        assert!((15..=25).contains(&samples.len()), "{}", samples.len());
+        assert!(samples.iter().all(|v| *v == 64));
+    }
+}
@@ -0,0 +1,530 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use crate::midi_message::{MidiMessage, MidiMessageKind};
+use crate::midi_subscriptions::MidiSubscriptions;
+use crate::note::Note;
+#[cfg(feature = "verbose")]
+use crate::LOG_TAG;
+use midir::{MidiOutput, MidiOutputConnection};
+#[cfg(feature = "verbose")]
+use paris::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Display;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+const CONFIG_CLIENT_NAME: &str = "InspiraSomDeviceConfig";
+const CONFIG_PORT_NAME: &str = "InspiraSomConfigPort";
+const SYSEX_START: u8 = 0xF0;
+const SYSEX_END: u8 = 0xF7;
+/// Id de fabricante reservado para uso nao comercial
+const MANUFACTURER_ID: u8 = 0x7D;
+/// Identifica as mensagens do firmware da ocarina entre outras com o mesmo fabricante
+const DEVICE_FAMILY: u8 = 0x49;
+
+const INFO_REQUEST: u8 = 0x01;
+const CONFIG_REQUEST: u8 = 0x02;
+const CONFIG_WRITE: u8 = 0x03;
+const INFO_RESPONSE: u8 = 0x41;
+const CONFIG_RESPONSE: u8 = 0x42;
+const ACK_RESPONSE: u8 = 0x43;
+const ACK_OK: u8 = 0;
+
+const DATA_MASK: u8 = 0x7F;
+/// Maior valor de 14 bits, enviado em dois bytes de 7 bits
+const MAX_14_BITS: u16 = 0x3FFF;
+pub const MAX_NOTE_MAPPINGS: usize = 32;
+pub const DEFAULT_RESPONSE_TIMEOUT: Duration = Duration::from_millis(1_000);
+/// Respostas guardadas sem ninguem esperando por elas
+const MAX_QUEUED_RESPONSES: usize = 16;
+
+/// Versao do firmware gravado na placa
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FirmwareVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+impl Display for FirmwareVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Identificacao da placa respondida pelo firmware
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    /// Numero de serie de 14 bits gravado na placa
+    pub device_id: u16,
+    pub firmware_version: FirmwareVersion,
+}
+
+/// Sensibilidade dos sensores, de 0 a 127
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SensorThresholds {
+    /// Pressao para começar a tocar
+    pub breath_on: u8,
+    /// Pressao abaixo da qual a nota para, nao pode ser maior que `breath_on`
+    pub breath_off: u8,
+    /// Sensibilidade dos furos ao toque
+    pub key_touch: u8,
+}
+
+/// Uma posicao dos dedos e a nota tocada com ela
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteMapping {
+    /// Um bit por furo, 1 é o furo tapado
+    pub fingering: u16,
+    pub note: u8,
+}
+
+/// Configuracao gravada no firmware, antes so mudava regravando a placa
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceConfig {
+    pub thresholds: SensorThresholds,
+    pub note_map: Vec<NoteMapping>,
+}
+
+impl DeviceConfig {
+    pub fn validated(self) -> ArduinoCommResult<Self> {
+        let t = self.thresholds;
+        let invalid = |reason: String| Err(ArduinoCommunicationError::InvalidDeviceConfig(reason));
+        if t.breath_on > Note::MAX_VELOCITY || t.key_touch > Note::MAX_VELOCITY {
+            return invalid(format!("thresholds must be at most {}", Note::MAX_VELOCITY));
+        }
+        if t.breath_off > t.breath_on {
+            return invalid(format!(
+                "breath off {} must not be higher than breath on {}",
+                t.breath_off, t.breath_on
+            ));
+        }
+        if self.note_map.len() > MAX_NOTE_MAPPINGS {
+            return invalid(format!(
+                "{} note mappings, the device supports {}",
+                self.note_map.len(),
+                MAX_NOTE_MAPPINGS
+            ));
+        }
+        let mut fingerings = HashSet::new();
+        for m in &self.note_map {
+            if m.fingering > MAX_14_BITS || m.note > DATA_MASK {
+                return invalid(format!("note mapping {:?} is out of range", m));
+            }
+            if !fingerings.insert(m.fingering) {
+                return invalid(format!("fingering {:#b} is mapped twice", m.fingering));
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Mensagens enviadas pelo app para a placa
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SysExRequest {
+    QueryInfo,
+    QueryConfig,
+    WriteConfig(DeviceConfig),
+}
+
+/// Mensagens enviadas pela placa para o app
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SysExResponse {
+    Info(DeviceInfo),
+    Config(DeviceConfig),
+    /// Resposta de [SysExRequest::WriteConfig], qualquer valor diferente de 0 é um erro do firmware
+    Ack(u8),
+}
+
+impl SysExRequest {
+    /// Mensagem completa, com 0xF0 e 0xF7
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            SysExRequest::QueryInfo => frame(INFO_REQUEST, &[]),
+            SysExRequest::QueryConfig => frame(CONFIG_REQUEST, &[]),
+            SysExRequest::WriteConfig(config) => frame(CONFIG_WRITE, &encode_config(config)),
+        }
+    }
+
+    /// Le o conteudo de uma SysEx, sem 0xF0 e 0xF7. Usado pelo lado do firmware
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        match unframe(data)? {
+            (INFO_REQUEST, []) => Some(SysExRequest::QueryInfo),
+            (CONFIG_REQUEST, []) => Some(SysExRequest::QueryConfig),
+            (CONFIG_WRITE, payload) => decode_config(payload).map(SysExRequest::WriteConfig),
+            _ => None,
+        }
+    }
+}
+
+impl SysExResponse {
+    /// Mensagem completa, com 0xF0 e 0xF7. Usado pelo lado do firmware
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            SysExResponse::Info(info) => {
+                let (hi, lo) = split_14_bits(info.device_id);
+                let v = info.firmware_version;
+                frame(INFO_RESPONSE, &[hi, lo, v.major, v.minor, v.patch])
+            }
+            SysExResponse::Config(config) => frame(CONFIG_RESPONSE, &encode_config(config)),
+            SysExResponse::Ack(status) => frame(ACK_RESPONSE, &[*status]),
+        }
+    }
+
+    /// Le o conteudo de uma SysEx, sem 0xF0 e 0xF7. [None] se ela nao for do firmware
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        match unframe(data)? {
+            (INFO_RESPONSE, [hi, lo, major, minor, patch]) => {
+                Some(SysExResponse::Info(DeviceInfo {
+                    device_id: join_14_bits(*hi, *lo),
+                    firmware_version: FirmwareVersion {
+                        major: *major,
+                        minor: *minor,
+                        patch: *patch,
+                    },
+                }))
+            }
+            (CONFIG_RESPONSE, payload) => decode_config(payload).map(SysExResponse::Config),
+            (ACK_RESPONSE, [status]) => Some(SysExResponse::Ack(*status)),
+            _ => None,
+        }
+    }
+}
+
+fn frame(command: u8, payload: &[u8]) -> Vec<u8> {
+    let mut message = vec![SYSEX_START, MANUFACTURER_ID, DEVICE_FAMILY, command];
+    message.extend(payload.iter().map(|b| b & DATA_MASK));
+    message.push(SYSEX_END);
+    message
+}
+
+fn unframe(data: &[u8]) -> Option<(u8, &[u8])> {
+    match data {
+        [MANUFACTURER_ID, DEVICE_FAMILY, command, payload @ ..] => Some((*command, payload)),
+        _ => None,
+    }
+}
+
+/// `[breath_on, breath_off, key_touch, quantidade, (furos alto, furos baixo, nota)...]`
+fn encode_config(config: &DeviceConfig) -> Vec<u8> {
+    let t = config.thresholds;
+    let mut payload = vec![
+        t.breath_on,
+        t.breath_off,
+        t.key_touch,
+        config.note_map.len() as u8,
+    ];
+    for m in &config.note_map {
+        let (hi, lo) = split_14_bits(m.fingering);
+        payload.extend([hi, lo, m.note]);
+    }
+    payload
+}
+
+fn decode_config(payload: &[u8]) -> Option<DeviceConfig> {
+    let [breath_on, breath_off, key_touch, count, mappings @ ..] = payload else {
+        return None;
+    };
+    if mappings.len() != *count as usize * 3 {
+        return None;
+    }
+    Some(DeviceConfig {
+        thresholds: SensorThresholds {
+            breath_on: *breath_on,
+            breath_off: *breath_off,
+            key_touch: *key_touch,
+        },
+        note_map: mappings
+            .chunks_exact(3)
+            .map(|m| NoteMapping {
+                fingering: join_14_bits(m[0], m[1]),
+                note: m[2],
+            })
+            .collect(),
+    })
+}
+
+fn split_14_bits(value: u16) -> (u8, u8) {
+    (((value >> 7) as u8) & DATA_MASK, (value as u8) & DATA_MASK)
+}
+
+fn join_14_bits(hi: u8, lo: u8) -> u16 {
+    ((hi as u16 & DATA_MASK as u16) << 7) | (lo as u16 & DATA_MASK as u16)
+}
+
+/// Respostas do firmware recebidas na conexao de entrada, esperadas pelo [DeviceConfigClient]
+#[derive(Debug, Clone, Default)]
+pub struct DeviceResponses {
+    queue: Arc<(Mutex<VecDeque<SysExResponse>>, Condvar)>,
+}
+
+impl DeviceResponses {
+    pub fn push(&self, response: SysExResponse) {
+        let (queue, condvar) = &*self.queue;
+        if let Ok(mut q) = queue.lock() {
+            if q.len() >= MAX_QUEUED_RESPONSES {
+                q.pop_front();
+            }
+            q.push_back(response);
+            condvar.notify_all();
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut q) = self.queue.0.lock() {
+            q.clear();
+        }
+    }
+
+    /// Espera a primeira resposta que passa no `filter`, as outras continuam na fila
+    pub fn wait_for(
+        &self,
+        timeout: Duration,
+        filter: impl Fn(&SysExResponse) -> bool,
+    ) -> Option<SysExResponse> {
+        let (queue, condvar) = &*self.queue;
+        let deadline = Instant::now() + timeout;
+        let mut q = queue.lock().ok()?;
+        loop {
+            if let Some(i) = q.iter().position(&filter) {
+                return q.remove(i);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return None;
+            }
+            q = condvar.wait_timeout(q, remaining).ok()?.0;
+        }
+    }
+}
+
+impl MidiSubscriptions {
+    /// Guarda as SysEx do firmware em `responses`
+    pub fn on_device_responses(self, responses: DeviceResponses) -> Self {
+        self.subscribe(&[MidiMessageKind::SysEx], move |m| {
+            if let MidiMessage::SysEx(data) = m {
+                if let Some(response) = SysExResponse::decode(data) {
+                    responses.push(response);
+                }
+            }
+        })
+    }
+}
+
+/// Para onde as SysEx do app sao enviadas
+pub trait SysExOutput: Send {
+    fn send(&mut self, message: &[u8]) -> ArduinoCommResult<()>;
+}
+
+impl SysExOutput for MidiOutputConnection {
+    fn send(&mut self, message: &[u8]) -> ArduinoCommResult<()> {
+        MidiOutputConnection::send(self, message)
+            .map_err(|e| ArduinoCommunicationError::DeviceConfigError(e.to_string()))
+    }
+}
+
+/// Abre a porta de saida midi do dispositivo, que tem o mesmo nome da porta de entrada
+pub fn connect_sysex_output(name: &str) -> ArduinoCommResult<MidiOutputConnection> {
+    let midi_out = MidiOutput::new(CONFIG_CLIENT_NAME)?;
+    for p in midi_out.ports() {
+        let Ok(n) = midi_out.port_name(&p) else {
+            continue;
+        };
+        if n.contains(name) {
+            #[cfg(feature = "verbose")]
+            {
+                info!("[{}] Sending device configuration to port {}", LOG_TAG, n);
+            }
+            return midi_out
+                .connect(&p, CONFIG_PORT_NAME)
+                .map_err(|e| ArduinoCommunicationError::DeviceConfigError(e.to_string()));
+        }
+    }
+    Err(ArduinoCommunicationError::DeviceConfigError(format!(
+        "device `{}` has no midi output",
+        name
+    )))
+}
+
+/// Le e grava a identificacao e a configuracao da placa
+pub struct DeviceConfigClient {
+    output: Box<dyn SysExOutput>,
+    responses: DeviceResponses,
+    timeout: Duration,
+}
+
+impl DeviceConfigClient {
+    pub fn new(output: Box<dyn SysExOutput>, responses: DeviceResponses) -> Self {
+        Self {
+            output,
+            responses,
+            timeout: DEFAULT_RESPONSE_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn device_info(&mut self) -> ArduinoCommResult<DeviceInfo> {
+        match self.request(SysExRequest::QueryInfo)? {
+            SysExResponse::Info(info) => Ok(info),
+            other => Err(Self::unexpected(other)),
+        }
+    }
+
+    pub fn config(&mut self) -> ArduinoCommResult<DeviceConfig> {
+        match self.request(SysExRequest::QueryConfig)? {
+            SysExResponse::Config(config) => Ok(config),
+            other => Err(Self::unexpected(other)),
+        }
+    }
+
+    /// Grava a configuracao e le de volta o que a placa esta usando
+    pub fn write_config(&mut self, config: DeviceConfig) -> ArduinoCommResult<DeviceConfig> {
+        let config = config.validated()?;
+        match self.request(SysExRequest::WriteConfig(config))? {
+            SysExResponse::Ack(ACK_OK) => self.config(),
+            SysExResponse::Ack(status) => Err(ArduinoCommunicationError::DeviceConfigError(
+                format!("device rejected the configuration with status {}", status),
+            )),
+            other => Err(Self::unexpected(other)),
+        }
+    }
+
+    /// A placa respondeu uma mensagem que nao corresponde ao pedido, o firmware
+    /// fala outra versao do protocolo
+    fn unexpected(response: SysExResponse) -> ArduinoCommunicationError {
+        ArduinoCommunicationError::DeviceConfigError(format!(
+            "protocol mismatch, unexpected response {:?}",
+            response
+        ))
+    }
+
+    fn request(&mut self, request: SysExRequest) -> ArduinoCommResult<SysExResponse> {
+        self.responses.clear();
+        self.output.send(&request.encode())?;
+        let expected = |r: &SysExResponse| {
+            matches!(
+                (&request, r),
+                (SysExRequest::QueryInfo, SysExResponse::Info(_))
+                    | (SysExRequest::QueryConfig, SysExResponse::Config(_))
+                    | (SysExRequest::WriteConfig(_), SysExResponse::Ack(_))
+            )
+        };
+        self.responses
+            .wait_for(self.timeout, expected)
+            .ok_or(ArduinoCommunicationError::DeviceConfigTimeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi_parser::MidiParser;
+
+    fn config() -> DeviceConfig {
+        DeviceConfig {
+            thresholds: SensorThresholds {
+                breath_on: 20,
+                breath_off: 12,
+                key_touch: 64,
+            },
+            note_map: vec![
+                NoteMapping {
+                    fingering: 0b1111_1111_1111,
+                    note: 60,
+                },
+                NoteMapping {
+                    fingering: 0,
+                    note: 77,
+                },
+            ],
+        }
+    }
+
+    /// Firmware de mentira, responde pela entrada o que recebe na saida
+    struct SimulatedFirmware {
+        parser: MidiParser,
+        responses: DeviceResponses,
+        config: DeviceConfig,
+    }
+
+    impl SysExOutput for SimulatedFirmware {
+        fn send(&mut self, message: &[u8]) -> ArduinoCommResult<()> {
+            for m in self.parser.feed(message) {
+                let MidiMessage::SysEx(data) = m else {
+                    continue;
+                };
+                let response = match SysExRequest::decode(&data) {
+                    Some(SysExRequest::QueryInfo) => SysExResponse::Info(DeviceInfo {
+                        device_id: 1234,
+                        firmware_version: FirmwareVersion {
+                            major: 1,
+                            minor: 2,
+                            patch: 0,
+                        },
+                    }),
+                    Some(SysExRequest::QueryConfig) => SysExResponse::Config(self.config.clone()),
+                    Some(SysExRequest::WriteConfig(c)) => {
+                        self.config = c;
+                        SysExResponse::Ack(ACK_OK)
+                    }
+                    None => continue,
+                };
+                let mut parser = MidiParser::new();
+                let subscriptions =
+                    MidiSubscriptions::new().on_device_responses(self.responses.clone());
+                for m in parser.feed(&response.encode()) {
+                    subscriptions.dispatch(0, &m);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn messages_survive_encoding() {
+        let request = SysExRequest::WriteConfig(config());
+        let encoded = request.encode();
+        assert!(encoded[1..encoded.len() - 1]
+            .iter()
+            .all(|b| *b <= DATA_MASK));
+        assert_eq!(
+            SysExRequest::decode(&encoded[1..encoded.len() - 1]),
+            Some(request)
+        );
+        let response = SysExResponse::Config(config());
+        let encoded = response.encode();
+        assert_eq!(
+            SysExResponse::decode(&encoded[1..encoded.len() - 1]),
+            Some(response)
+        );
+        assert_eq!(SysExResponse::decode(&[0x7E, 0x01, 0x06, 0x02]), None);
+    }
+
+    #[test]
+    fn reads_and_writes_the_simulated_firmware() {
+        let responses = DeviceResponses::default();
+        let firmware = SimulatedFirmware {
+            parser: MidiParser::new(),
+            responses: responses.clone(),
+            config: config(),
+        };
+        let mut client = DeviceConfigClient::new(Box::new(firmware), responses)
+            .with_timeout(Duration::from_millis(100));
+        let info = client.device_info().unwrap();
+        assert_eq!(info.device_id, 1234);
+        assert_eq!(info.firmware_version.to_string(), "1.2.0");
+        assert_eq!(client.config().unwrap(), config());
+
+        let mut changed = config();
+        changed.thresholds.breath_on = 30;
+        assert_eq!(client.write_config(changed.clone()).unwrap(), changed);
+
+        let mut invalid = config();
+        invalid.thresholds.breath_off = 40;
+        assert!(client.write_config(invalid).is_err());
+    }
+}
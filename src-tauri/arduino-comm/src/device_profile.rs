@@ -0,0 +1,80 @@
+use crate::breath_pressure::{PressureSource, DEFAULT_PRESSURE_SAMPLE_RATE};
+use crate::input_filter::InputFilterConfig;
+use crate::instrument_profile::DEFAULT_INSTRUMENT_ID;
+use crate::osc::OSC_DEVICE_NAME;
+use crate::serial_connection::SERIAL_DEVICE_NAME;
+use serde::{Deserialize, Serialize};
+
+const OCARINA_PORT_NAME: &str = "USB MidiKliK";
+const BREATH_CONTROLLER_PORT_NAME: &str = "Breath";
+const BREATH_CONTROLLER_CC: u8 = 2;
+
+/// Como ler os dados de um dispositivo, escolhido pelo nome da porta
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceProfile {
+    pub name: String,
+    /// Parte do nome da porta midi que identifica o dispositivo
+    pub port_pattern: String,
+    pub pressure_source: PressureSource,
+    /// Amostras de pressao por segundo
+    pub pressure_sample_rate: u32,
+    /// Id do [crate::instrument_profile::InstrumentProfile] do dispositivo,
+    /// [None] deixa o instrumento escolhido pelo usuario
+    #[serde(default)]
+    pub instrument: Option<String>,
+    /// Filtro aplicado nas notas antes delas chegarem no app
+    #[serde(default)]
+    pub input_filter: InputFilterConfig,
+}
+
+impl Default for DeviceProfile {
+    fn default() -> Self {
+        Self {
+            name: "Generic".to_string(),
+            port_pattern: String::new(),
+            pressure_source: PressureSource::NoteVelocity,
+            pressure_sample_rate: DEFAULT_PRESSURE_SAMPLE_RATE,
+            instrument: None,
+            input_filter: InputFilterConfig::default(),
+        }
+    }
+}
+
+impl DeviceProfile {
+    pub fn builtin() -> Vec<DeviceProfile> {
+        vec![
+            DeviceProfile {
+                name: "Inspira Som Ocarina".to_string(),
+                port_pattern: OCARINA_PORT_NAME.to_string(),
+                instrument: Some(DEFAULT_INSTRUMENT_ID.to_string()),
+                ..Default::default()
+            },
+            DeviceProfile {
+                name: "Breath Controller".to_string(),
+                port_pattern: BREATH_CONTROLLER_PORT_NAME.to_string(),
+                pressure_source: PressureSource::ControlChange(BREATH_CONTROLLER_CC),
+                ..Default::default()
+            },
+            DeviceProfile {
+                name: "Inspira Som Serial".to_string(),
+                port_pattern: SERIAL_DEVICE_NAME.to_string(),
+                pressure_source: PressureSource::ChannelPressure,
+                ..Default::default()
+            },
+            DeviceProfile {
+                name: "OSC".to_string(),
+                port_pattern: OSC_DEVICE_NAME.to_string(),
+                pressure_source: PressureSource::ChannelPressure,
+                ..Default::default()
+            },
+        ]
+    }
+
+    /// Procura o perfil da porta, se nenhum servir usa o perfil padrao
+    pub fn for_port(port_name: &str) -> DeviceProfile {
+        Self::builtin()
+            .into_iter()
+            .find(|p| port_name.contains(&p.port_pattern))
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,176 @@
+use crate::errors::ArduinoCommResult;
+use crate::input_source::InputSource;
+use crate::midi_connection::connect_to_port_with_name;
+use crate::serial_connection::{list_serial_ports, serial_device_name, SerialConnection};
+#[cfg(feature = "verbose")]
+use crate::LOG_TAG;
+use midir::MidiInput;
+#[cfg(feature = "verbose")]
+use paris::info;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const WATCHER_CLIENT_NAME: &str = "InspiraSomDeviceWatcher";
+
+/// Dispositivo que apareceu ou sumiu desde a ultima verificacao
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceEvent {
+    Attached(String),
+    Detached(String),
+}
+
+/// Porta que pode ser reaberta, usada para reconectar sozinho quando o dispositivo volta
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DevicePort {
+    Midi(String),
+    Serial { port_name: String, baud_rate: u32 },
+}
+
+impl DevicePort {
+    /// Mesmo nome do [InputSource::name] da fonte aberta e dos eventos do [DeviceWatcher]
+    pub fn name(&self) -> String {
+        match self {
+            DevicePort::Midi(name) => name.to_owned(),
+            DevicePort::Serial { port_name, .. } => serial_device_name(port_name),
+        }
+    }
+
+    pub fn open(&self) -> ArduinoCommResult<Box<dyn InputSource>> {
+        Ok(match self {
+            DevicePort::Midi(name) => Box::new(connect_to_port_with_name(name)?),
+            DevicePort::Serial {
+                port_name,
+                baud_rate,
+            } => Box::new(SerialConnection::open(port_name, *baud_rate)?),
+        })
+    }
+}
+
+/// Compara duas listas de dispositivos e retorna o que mudou
+pub fn diff_devices(previous: &HashSet<String>, current: &HashSet<String>) -> Vec<DeviceEvent> {
+    let mut events: Vec<DeviceEvent> = previous
+        .difference(current)
+        .map(|d| DeviceEvent::Detached(d.to_owned()))
+        .collect();
+    events.extend(
+        current
+            .difference(previous)
+            .map(|d| DeviceEvent::Attached(d.to_owned())),
+    );
+    events.sort_by_key(|e| match e {
+        DeviceEvent::Detached(d) => (0, d.to_owned()),
+        DeviceEvent::Attached(d) => (1, d.to_owned()),
+    });
+    events
+}
+
+/// Lista as portas midi e seriais pelo nome da fonte, usando um unico cliente midi
+pub fn device_lister() -> ArduinoCommResult<impl FnMut() -> Option<Vec<String>> + Send + 'static> {
+    let midi_in = MidiInput::new(WATCHER_CLIENT_NAME)?;
+    Ok(move || {
+        let mut devices: Vec<String> = midi_in
+            .ports()
+            .iter()
+            .filter_map(|p| midi_in.port_name(p).ok())
+            .collect();
+        devices.extend(
+            list_serial_ports()
+                .unwrap_or_default()
+                .iter()
+                .map(|p| serial_device_name(p)),
+        );
+        Some(devices)
+    })
+}
+
+/// Verifica periodicamente os dispositivos conectados no computador.
+///
+/// A verificacao roda em uma thread propria e para quando o watcher é dropado.
+pub struct DeviceWatcher {
+    stop: Arc<AtomicBool>,
+}
+
+impl DeviceWatcher {
+    /// Observa as portas midi e seriais, ver [device_lister]
+    pub fn start(
+        interval: Duration,
+        on_event: impl FnMut(DeviceEvent) + Send + 'static,
+    ) -> ArduinoCommResult<Self> {
+        Ok(Self::with_lister(interval, device_lister()?, on_event))
+    }
+
+    /// Observa os dispositivos retornados por `lister`, [None] pula a verificacao.
+    ///
+    /// Os dispositivos ja presentes na primeira verificacao geram [DeviceEvent::Attached].
+    pub fn with_lister(
+        interval: Duration,
+        mut lister: impl FnMut() -> Option<Vec<String>> + Send + 'static,
+        mut on_event: impl FnMut(DeviceEvent) + Send + 'static,
+    ) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        std::thread::spawn(move || {
+            let mut known: HashSet<String> = HashSet::new();
+            while !stopped.load(Ordering::Relaxed) {
+                if let Some(devices) = lister() {
+                    let current: HashSet<String> = devices.into_iter().collect();
+                    for event in diff_devices(&known, &current) {
+                        #[cfg(feature = "verbose")]
+                        {
+                            info!("[{}] Device event: {:?}", LOG_TAG, event);
+                        }
+                        on_event(event);
+                    }
+                    known = current;
+                }
+                std::thread::sleep(interval);
+            }
+        });
+        Self { stop }
+    }
+}
+
+impl Drop for DeviceWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+    use std::sync::Mutex;
+
+    #[test]
+    fn emits_attach_and_detach_events() {
+        let devices = Arc::new(Mutex::new(vec!["USB MidiKliK".to_string()]));
+        let d = devices.clone();
+        let (sender, receiver) = channel();
+        let watcher = DeviceWatcher::with_lister(
+            Duration::from_millis(1),
+            move || Some(d.lock().unwrap().clone()),
+            move |e| {
+                let _ = sender.send(e);
+            },
+        );
+        let next = || receiver.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(next(), DeviceEvent::Attached("USB MidiKliK".to_string()));
+
+        *devices.lock().unwrap() = vec!["Breath".to_string()];
+        assert_eq!(next(), DeviceEvent::Detached("USB MidiKliK".to_string()));
+        assert_eq!(next(), DeviceEvent::Attached("Breath".to_string()));
+        drop(watcher);
+    }
+
+    #[test]
+    fn serial_ports_use_the_connection_name() {
+        let port = DevicePort::Serial {
+            port_name: "/dev/ttyACM0".to_string(),
+            baud_rate: 115_200,
+        };
+        assert_eq!(port.name(), "InspiraSom Serial (/dev/ttyACM0)");
+    }
+}
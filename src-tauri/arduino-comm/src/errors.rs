@@ -22,6 +22,44 @@ pub enum ArduinoCommunicationError {
     NoDevicesConnected,
     #[error("Error while listening to port `{0}`")]
     PortListenError(String),
+    #[error("Output port with name `{0}` was not found")]
+    ThruPortNotFound(String),
+    #[error("Error while connecting to thru output: {0}")]
+    ThruConnectionError(String),
+    #[error("Invalid thru configuration: {0}")]
+    InvalidThruConfig(String),
+    #[error("Error while recording device input: {0}")]
+    RecordingError(String),
+    #[error("Invalid input recording: {0}")]
+    InvalidRecording(String),
+    #[error("Replay speed `{0}` must be at least 1.0")]
+    InvalidReplaySpeed(f32),
+    #[error("Serial port error: {0}")]
+    SerialError(String),
+    #[error("Invalid instrument profile {0}")]
+    InvalidInstrumentProfile(String),
+    #[error("Invalid breath calibration: {0}")]
+    InvalidCalibration(String),
+    #[error("Invalid latency calibration: {0}")]
+    InvalidLatencyCalibration(String),
+    #[error("Invalid input filter: {0}")]
+    InvalidInputFilter(String),
+    #[error("Could not configure the device: {0}")]
+    DeviceConfigError(String),
+    #[error("The device did not answer the configuration request")]
+    DeviceConfigTimeout,
+    #[error("Invalid device configuration: {0}")]
+    InvalidDeviceConfig(String),
+    #[error("Invalid firmware: {0}")]
+    InvalidFirmware(String),
+    #[error("Firmware upload failed: {0}")]
+    FirmwareUploadError(String),
+    #[error("Firmware upload failed and the previous firmware was restored: {0}")]
+    FirmwareRolledBack(String),
+    #[error("OSC error: {0}")]
+    OscError(String),
+    #[error("Invalid OSC packet: {0}")]
+    InvalidOscPacket(String),
 }
 
 impl From<InitError> for ArduinoCommunicationError {
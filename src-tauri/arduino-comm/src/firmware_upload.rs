@@ -0,0 +1,560 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use crate::serial_connection::serial_error;
+#[cfg(feature = "verbose")]
+use crate::LOG_TAG;
+#[cfg(feature = "verbose")]
+use paris::{info, warn};
+use serde::Serialize;
+use serialport::ClearBuffer;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// Velocidade do optiboot, o bootloader do Arduino Uno
+pub const BOOTLOADER_BAUD_RATE: u32 = 115_200;
+/// Tamanho da pagina de flash do ATmega328P
+pub const FLASH_PAGE_SIZE: usize = 128;
+/// Flash do ATmega328P sem o espaco do bootloader
+pub const MAX_FIRMWARE_SIZE: usize = 32_256;
+/// Assinatura lida do ATmega328P, o unico chip para o qual o firmware é compilado
+pub const ATMEGA328P_SIGNATURE: [u8; 3] = [0x1E, 0x95, 0x0F];
+const BOOTLOADER_TIMEOUT: Duration = Duration::from_millis(500);
+/// Tempo para a placa reiniciar e o bootloader começar a escutar
+const RESET_DELAY: Duration = Duration::from_millis(250);
+const SYNC_ATTEMPTS: usize = 10;
+const ERASED_BYTE: u8 = 0xFF;
+
+const STK_OK: u8 = 0x10;
+const STK_INSYNC: u8 = 0x14;
+const CRC_EOP: u8 = 0x20;
+const STK_GET_SYNC: u8 = 0x30;
+const STK_ENTER_PROGMODE: u8 = 0x50;
+const STK_LEAVE_PROGMODE: u8 = 0x51;
+const STK_LOAD_ADDRESS: u8 = 0x55;
+const STK_PROG_PAGE: u8 = 0x64;
+const STK_READ_PAGE: u8 = 0x74;
+const STK_READ_SIGN: u8 = 0x75;
+const MEMORY_FLASH: u8 = b'F';
+
+const HEX_DATA: u8 = 0x00;
+const HEX_EOF: u8 = 0x01;
+const HEX_EXTENDED_SEGMENT: u8 = 0x02;
+const HEX_EXTENDED_LINEAR: u8 = 0x04;
+
+/// Firmware pronto para ser gravado, começando no endereço 0 da flash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirmwareImage {
+    data: Vec<u8>,
+}
+
+impl FirmwareImage {
+    pub fn from_bytes(data: Vec<u8>) -> ArduinoCommResult<Self> {
+        if data.is_empty() || data.len() > MAX_FIRMWARE_SIZE {
+            return Err(ArduinoCommunicationError::InvalidFirmware(format!(
+                "firmware has {} bytes, expected 1 to {}",
+                data.len(),
+                MAX_FIRMWARE_SIZE
+            )));
+        }
+        Ok(Self { data })
+    }
+
+    /// Le um `.hex` ou um `.bin` com a imagem crua da flash
+    pub fn load(path: impl AsRef<Path>) -> ArduinoCommResult<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| ArduinoCommunicationError::InvalidFirmware(e.to_string()))?;
+        if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("hex"))
+        {
+            Self::from_intel_hex(&String::from_utf8_lossy(&bytes))
+        } else {
+            Self::from_bytes(bytes)
+        }
+    }
+
+    /// Le o `.hex` gerado pela IDE do Arduino
+    pub fn from_intel_hex(text: &str) -> ArduinoCommResult<Self> {
+        let invalid = |line: usize, reason: &str| {
+            ArduinoCommunicationError::InvalidFirmware(format!("line {}: {}", line + 1, reason))
+        };
+        let mut data: Vec<u8> = vec![];
+        let mut base = 0usize;
+        for (i, line) in text.lines().map(str::trim).enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let Some(hex) = line.strip_prefix(':') else {
+                return Err(invalid(i, "record must start with `:`"));
+            };
+            let bytes = (0..hex.len())
+                .step_by(2)
+                .map(|j| {
+                    hex.get(j..j + 2)
+                        .and_then(|b| u8::from_str_radix(b, 16).ok())
+                })
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(|| invalid(i, "record is not hexadecimal"))?;
+            let [length, address_hi, address_lo, kind, rest @ ..] = bytes.as_slice() else {
+                return Err(invalid(i, "record is too short"));
+            };
+            if rest.len() != *length as usize + 1 {
+                return Err(invalid(i, "record length does not match"));
+            }
+            if bytes.iter().fold(0u8, |acc, b| acc.wrapping_add(*b)) != 0 {
+                return Err(invalid(i, "wrong checksum"));
+            }
+            let payload = &rest[..*length as usize];
+            let extended = || {
+                payload
+                    .iter()
+                    .fold(0usize, |acc, b| (acc << 8) | *b as usize)
+            };
+            match *kind {
+                HEX_DATA => {
+                    let start = base + ((*address_hi as usize) << 8 | *address_lo as usize);
+                    let end = start + payload.len();
+                    if end > MAX_FIRMWARE_SIZE {
+                        return Err(invalid(i, "data is outside of the flash"));
+                    }
+                    if data.len() < end {
+                        data.resize(end, ERASED_BYTE);
+                    }
+                    data[start..end].copy_from_slice(payload);
+                }
+                HEX_EOF => break,
+                HEX_EXTENDED_SEGMENT => base = extended() << 4,
+                HEX_EXTENDED_LINEAR => base = extended() << 16,
+                // enderecos de inicio nao importam para o bootloader
+                _ => {}
+            }
+        }
+        Self::from_bytes(data)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Endereço e conteudo de cada pagina, a ultima completada com flash apagada
+    fn pages(&self) -> Vec<(usize, Vec<u8>)> {
+        self.data
+            .chunks(FLASH_PAGE_SIZE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut page = chunk.to_vec();
+                page.resize(FLASH_PAGE_SIZE, ERASED_BYTE);
+                (i * FLASH_PAGE_SIZE, page)
+            })
+            .collect()
+    }
+}
+
+/// Etapas da gravacao enviadas no progresso
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum UploadStage {
+    /// Lendo o firmware atual para poder voltar para ele
+    Backup,
+    Write,
+    Verify,
+    /// A gravacao falhou e o firmware antigo esta sendo regravado
+    Rollback,
+}
+
+/// Paginas ja processadas da etapa atual
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct UploadProgress {
+    pub stage: UploadStage,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Conversa com o bootloader do Arduino usando o protocolo STK500 v1
+pub struct Stk500<P: Read + Write> {
+    port: P,
+}
+
+impl<P: Read + Write> Stk500<P> {
+    pub fn new(port: P) -> Self {
+        Self { port }
+    }
+
+    /// Tenta sincronizar algumas vezes, a placa pode demorar a sair do reset
+    pub fn sync(&mut self) -> ArduinoCommResult<()> {
+        let mut last_error = None;
+        for _ in 0..SYNC_ATTEMPTS {
+            match self.command(&[STK_GET_SYNC], 0) {
+                Ok(_) => return Ok(()),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| upload_error("bootloader did not answer")))
+    }
+
+    pub fn signature(&mut self) -> ArduinoCommResult<[u8; 3]> {
+        let s = self.command(&[STK_READ_SIGN], 3)?;
+        Ok([s[0], s[1], s[2]])
+    }
+
+    pub fn enter_programming(&mut self) -> ArduinoCommResult<()> {
+        self.command(&[STK_ENTER_PROGMODE], 0).map(|_| ())
+    }
+
+    /// Sai da gravacao, o bootloader começa a rodar o firmware
+    pub fn leave_programming(&mut self) -> ArduinoCommResult<()> {
+        self.command(&[STK_LEAVE_PROGMODE], 0).map(|_| ())
+    }
+
+    pub fn write_page(&mut self, address: usize, page: &[u8]) -> ArduinoCommResult<()> {
+        self.load_address(address)?;
+        let size = page.len() as u16;
+        let mut message = vec![STK_PROG_PAGE, (size >> 8) as u8, size as u8, MEMORY_FLASH];
+        message.extend_from_slice(page);
+        self.command(&message, 0).map(|_| ())
+    }
+
+    pub fn read_page(&mut self, address: usize, size: usize) -> ArduinoCommResult<Vec<u8>> {
+        self.load_address(address)?;
+        let length = size as u16;
+        self.command(
+            &[
+                STK_READ_PAGE,
+                (length >> 8) as u8,
+                length as u8,
+                MEMORY_FLASH,
+            ],
+            size,
+        )
+    }
+
+    /// O bootloader recebe o endereço em palavras de 2 bytes
+    fn load_address(&mut self, address: usize) -> ArduinoCommResult<()> {
+        let word = (address / 2) as u16;
+        self.command(&[STK_LOAD_ADDRESS, word as u8, (word >> 8) as u8], 0)
+            .map(|_| ())
+    }
+
+    /// Envia o comando e le `[STK_INSYNC, resposta..., STK_OK]`
+    fn command(&mut self, message: &[u8], response_size: usize) -> ArduinoCommResult<Vec<u8>> {
+        let mut bytes = message.to_vec();
+        bytes.push(CRC_EOP);
+        self.port
+            .write_all(&bytes)
+            .and_then(|_| self.port.flush())
+            .map_err(|e| upload_error(&e.to_string()))?;
+        let mut response = vec![0u8; response_size + 2];
+        self.port
+            .read_exact(&mut response)
+            .map_err(|e| upload_error(&format!("bootloader did not answer: {}", e)))?;
+        if response[0] != STK_INSYNC || response[response_size + 1] != STK_OK {
+            return Err(upload_error(&format!(
+                "bootloader is out of sync after command {:#04x}",
+                message[0]
+            )));
+        }
+        Ok(response[1..=response_size].to_vec())
+    }
+}
+
+/// Grava o firmware e confere o que foi gravado.
+///
+/// Se a gravacao ou a conferencia falharem o firmware antigo é regravado, retornando
+/// [ArduinoCommunicationError::FirmwareRolledBack] se isso deu certo. O modo de
+/// programacao sempre é deixado, mesmo quando algo falha.
+pub fn upload<P: Read + Write>(
+    stk: &mut Stk500<P>,
+    image: &FirmwareImage,
+    on_progress: impl FnMut(UploadProgress),
+) -> ArduinoCommResult<()> {
+    stk.sync()?;
+    stk.enter_programming()?;
+    let result = program(stk, image, on_progress);
+    let left = stk.leave_programming();
+    result.and(left)
+}
+
+fn program<P: Read + Write>(
+    stk: &mut Stk500<P>,
+    image: &FirmwareImage,
+    mut on_progress: impl FnMut(UploadProgress),
+) -> ArduinoCommResult<()> {
+    let signature = stk.signature()?;
+    if signature != ATMEGA328P_SIGNATURE {
+        return Err(ArduinoCommunicationError::InvalidFirmware(format!(
+            "the board signature {:02X?} is not an ATmega328P",
+            signature
+        )));
+    }
+    let pages = image.pages();
+    let total = pages.len();
+    let mut backup = Vec::with_capacity(total);
+    for (i, (address, _)) in pages.iter().enumerate() {
+        backup.push((*address, stk.read_page(*address, FLASH_PAGE_SIZE)?));
+        on_progress(progress(UploadStage::Backup, i + 1, total));
+    }
+    if let Err(e) = write_and_verify(stk, &pages, UploadStage::Write, &mut on_progress) {
+        #[cfg(feature = "verbose")]
+        {
+            warn!("[{}] Firmware upload failed, rolling back: {}", LOG_TAG, e);
+        }
+        return match write_and_verify(stk, &backup, UploadStage::Rollback, &mut on_progress) {
+            Ok(_) => Err(ArduinoCommunicationError::FirmwareRolledBack(e.to_string())),
+            Err(rollback) => Err(upload_error(&format!(
+                "{}, the previous firmware could not be restored: {}",
+                e, rollback
+            ))),
+        };
+    }
+    Ok(())
+}
+
+/// Grava todas as paginas e depois le cada uma de volta
+fn write_and_verify<P: Read + Write>(
+    stk: &mut Stk500<P>,
+    pages: &[(usize, Vec<u8>)],
+    stage: UploadStage,
+    on_progress: &mut impl FnMut(UploadProgress),
+) -> ArduinoCommResult<()> {
+    let total = pages.len();
+    for (i, (address, page)) in pages.iter().enumerate() {
+        stk.write_page(*address, page)?;
+        on_progress(progress(stage, i + 1, total));
+    }
+    for (i, (address, page)) in pages.iter().enumerate() {
+        if &stk.read_page(*address, page.len())? != page {
+            return Err(upload_error(&format!(
+                "flash at {:#06x} does not match what was written",
+                address
+            )));
+        }
+        if stage != UploadStage::Rollback {
+            on_progress(progress(UploadStage::Verify, i + 1, total));
+        }
+    }
+    Ok(())
+}
+
+fn progress(stage: UploadStage, done: usize, total: usize) -> UploadProgress {
+    UploadProgress { stage, done, total }
+}
+
+fn upload_error(reason: &str) -> ArduinoCommunicationError {
+    ArduinoCommunicationError::FirmwareUploadError(reason.to_string())
+}
+
+/// Reinicia a placa na porta serial e grava o firmware pelo bootloader
+pub fn upload_firmware(
+    port_name: &str,
+    image: &FirmwareImage,
+    on_progress: impl FnMut(UploadProgress),
+) -> ArduinoCommResult<()> {
+    let mut port = serialport::new(port_name, BOOTLOADER_BAUD_RATE)
+        .timeout(BOOTLOADER_TIMEOUT)
+        .open()
+        .map_err(serial_error)?;
+    #[cfg(feature = "verbose")]
+    {
+        info!(
+            "[{}] Uploading {} bytes of firmware to {}",
+            LOG_TAG,
+            image.len(),
+            port_name
+        );
+    }
+    // o Arduino reinicia quando o DTR cai, entrando no bootloader
+    port.write_data_terminal_ready(false)
+        .map_err(serial_error)?;
+    std::thread::sleep(Duration::from_millis(50));
+    port.write_data_terminal_ready(true).map_err(serial_error)?;
+    std::thread::sleep(RESET_DELAY);
+    port.clear(ClearBuffer::All).map_err(serial_error)?;
+    upload(&mut Stk500::new(port), image, on_progress)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serialport::{SerialPort, TTYPort};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Bootloader de mentira rodando do outro lado de um pseudo terminal.
+    /// As primeiras `corrupted_writes` paginas sao gravadas com defeito
+    struct SimulatedBoard {
+        flash: Arc<Mutex<Vec<u8>>>,
+        corrupted_writes: usize,
+        signature: [u8; 3],
+        /// Se o bootloader saiu do modo de programacao
+        left_programming: Arc<AtomicBool>,
+    }
+
+    impl SimulatedBoard {
+        fn new(flash: Arc<Mutex<Vec<u8>>>, corrupted_writes: usize) -> Self {
+            Self {
+                flash,
+                corrupted_writes,
+                signature: ATMEGA328P_SIGNATURE,
+                left_programming: Arc::new(AtomicBool::new(false)),
+            }
+        }
+    }
+
+    fn simulated_bootloader(board: &SimulatedBoard) -> TTYPort {
+        let flash = board.flash.clone();
+        let mut corrupted_writes = board.corrupted_writes;
+        let signature = board.signature;
+        let left_programming = board.left_programming.clone();
+        let (mut board, mut computer) = TTYPort::pair().unwrap();
+        board.set_timeout(Duration::from_secs(5)).unwrap();
+        computer.set_timeout(BOOTLOADER_TIMEOUT).unwrap();
+        std::thread::spawn(move || {
+            let mut address = 0usize;
+            let read = |board: &mut TTYPort, n: usize| -> Option<Vec<u8>> {
+                let mut bytes = vec![0u8; n];
+                board.read_exact(&mut bytes).ok().map(|_| bytes)
+            };
+            while let Some(command) = read(&mut board, 1) {
+                let reply = match command[0] {
+                    STK_GET_SYNC | STK_ENTER_PROGMODE => vec![],
+                    STK_LEAVE_PROGMODE => {
+                        left_programming.store(true, Ordering::Relaxed);
+                        vec![]
+                    }
+                    STK_READ_SIGN => signature.to_vec(),
+                    STK_LOAD_ADDRESS => {
+                        let Some(a) = read(&mut board, 2) else { break };
+                        address = (a[0] as usize | (a[1] as usize) << 8) * 2;
+                        vec![]
+                    }
+                    STK_PROG_PAGE => {
+                        let Some(h) = read(&mut board, 3) else { break };
+                        let size = (h[0] as usize) << 8 | h[1] as usize;
+                        let Some(mut page) = read(&mut board, size) else {
+                            break;
+                        };
+                        if corrupted_writes > 0 {
+                            corrupted_writes -= 1;
+                            page[0] ^= 0xFF;
+                        }
+                        flash.lock().unwrap()[address..address + size].copy_from_slice(&page);
+                        vec![]
+                    }
+                    STK_READ_PAGE => {
+                        let Some(h) = read(&mut board, 3) else { break };
+                        let size = (h[0] as usize) << 8 | h[1] as usize;
+                        flash.lock().unwrap()[address..address + size].to_vec()
+                    }
+                    _ => continue,
+                };
+                if read(&mut board, 1) != Some(vec![CRC_EOP]) {
+                    continue;
+                }
+                let mut response = vec![STK_INSYNC];
+                response.extend(reply);
+                response.push(STK_OK);
+                if board.write_all(&response).is_err() {
+                    break;
+                }
+            }
+        });
+        computer
+    }
+
+    fn to_intel_hex(data: &[u8]) -> String {
+        let mut hex = String::new();
+        for (i, chunk) in data.chunks(16).enumerate() {
+            let address = (i * 16) as u16;
+            let mut record = vec![
+                chunk.len() as u8,
+                (address >> 8) as u8,
+                address as u8,
+                HEX_DATA,
+            ];
+            record.extend_from_slice(chunk);
+            let checksum = record
+                .iter()
+                .fold(0u8, |acc, b| acc.wrapping_add(*b))
+                .wrapping_neg();
+            record.push(checksum);
+            hex.push(':');
+            hex.extend(record.iter().map(|b| format!("{:02X}", b)));
+            hex.push('\n');
+        }
+        hex.push_str(":00000001FF\n");
+        hex
+    }
+
+    #[test]
+    fn uploads_and_verifies_the_firmware() {
+        let data: Vec<u8> = (0..300).map(|i| (i % 251) as u8).collect();
+        let image = FirmwareImage::from_intel_hex(&to_intel_hex(&data)).unwrap();
+        assert_eq!(image.len(), 300);
+        let mut corrupted = to_intel_hex(&data);
+        corrupted.replace_range(9..10, "F");
+        assert!(FirmwareImage::from_intel_hex(&corrupted).is_err());
+
+        let flash = Arc::new(Mutex::new(vec![ERASED_BYTE; MAX_FIRMWARE_SIZE]));
+        let board = SimulatedBoard::new(flash.clone(), 0);
+        let mut stk = Stk500::new(simulated_bootloader(&board));
+        assert_eq!(stk.signature().unwrap(), ATMEGA328P_SIGNATURE);
+        let mut stages = vec![];
+        upload(&mut stk, &image, |p| {
+            if stages.last() != Some(&p.stage) {
+                stages.push(p.stage);
+            }
+        })
+        .unwrap();
+        assert_eq!(
+            stages,
+            vec![UploadStage::Backup, UploadStage::Write, UploadStage::Verify]
+        );
+        let flash = flash.lock().unwrap();
+        assert_eq!(&flash[..300], data.as_slice());
+        assert!(flash[300..384].iter().all(|b| *b == ERASED_BYTE));
+        assert!(board.left_programming.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn rolls_back_when_verification_fails() {
+        let previous: Vec<u8> = (0..MAX_FIRMWARE_SIZE).map(|i| (i % 7) as u8).collect();
+        let flash = Arc::new(Mutex::new(previous.clone()));
+        let board = SimulatedBoard::new(flash.clone(), 1);
+        let mut stk = Stk500::new(simulated_bootloader(&board));
+        let image = FirmwareImage::from_bytes(vec![0xAB; 200]).unwrap();
+        let mut rolled_back = false;
+        let result = upload(&mut stk, &image, |p| {
+            rolled_back |= p.stage == UploadStage::Rollback;
+        });
+        assert!(matches!(
+            result,
+            Err(ArduinoCommunicationError::FirmwareRolledBack(_))
+        ));
+        assert!(rolled_back);
+        assert_eq!(*flash.lock().unwrap(), previous);
+        assert!(board.left_programming.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn rejects_boards_that_are_not_an_atmega328p() {
+        let previous = vec![ERASED_BYTE; MAX_FIRMWARE_SIZE];
+        let flash = Arc::new(Mutex::new(previous.clone()));
+        let board = SimulatedBoard {
+            signature: [0x1E, 0x98, 0x01],
+            ..SimulatedBoard::new(flash.clone(), 0)
+        };
+        let mut stk = Stk500::new(simulated_bootloader(&board));
+        let image = FirmwareImage::from_bytes(vec![0xAB; 200]).unwrap();
+        let result = upload(&mut stk, &image, |_| {});
+        assert!(matches!(
+            result,
+            Err(ArduinoCommunicationError::InvalidFirmware(_))
+        ));
+        assert_eq!(*flash.lock().unwrap(), previous);
+        assert!(board.left_programming.load(Ordering::Relaxed));
+    }
+}
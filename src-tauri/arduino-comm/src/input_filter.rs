@@ -0,0 +1,377 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use crate::midi_message::MidiMessageKind;
+use crate::midi_subscriptions::MidiSubscriptions;
+use crate::midi_wrapper::MidiWrapper;
+use crate::note::Note;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_MIN_NOTE_DURATION_MS: u64 = 30;
+/// Intervalo em que os note off atrasados sao verificados
+const FLUSH_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Limites de velocity para ligar e desligar uma nota, o de desligar é menor
+/// para que a nota nao fique ligando e desligando perto do limite
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hysteresis {
+    /// Menor velocity de um note on para ligar a nota
+    pub on_velocity: u8,
+    /// Um note on repetido abaixo desta velocity desliga a nota
+    pub off_velocity: u8,
+}
+
+/// Partes do filtro de entrada, cada uma pode ser desligada
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputFilterConfig {
+    /// Note off que chega antes desse tempo é atrasado, se a nota voltar nesse meio tempo
+    /// as duas mensagens sao descartadas. [None] desliga
+    pub min_note_duration_ms: Option<u64>,
+    /// [None] desliga
+    pub hysteresis: Option<Hysteresis>,
+    /// Descarta note on de uma nota que ja esta ligada
+    pub suppress_duplicate_note_ons: bool,
+}
+
+impl Default for InputFilterConfig {
+    fn default() -> Self {
+        Self {
+            min_note_duration_ms: Some(DEFAULT_MIN_NOTE_DURATION_MS),
+            hysteresis: None,
+            suppress_duplicate_note_ons: true,
+        }
+    }
+}
+
+impl InputFilterConfig {
+    /// Nao filtra nada
+    pub const DISABLED: Self = Self {
+        min_note_duration_ms: None,
+        hysteresis: None,
+        suppress_duplicate_note_ons: false,
+    };
+
+    pub fn is_disabled(&self) -> bool {
+        *self == Self::DISABLED
+    }
+
+    pub fn validated(self) -> ArduinoCommResult<Self> {
+        match self.hysteresis {
+            Some(h) if h.off_velocity > h.on_velocity || h.on_velocity > Note::MAX_VELOCITY => {
+                Err(ArduinoCommunicationError::InvalidInputFilter(format!(
+                    "expected off velocity {} <= on velocity {} <= {}",
+                    h.off_velocity,
+                    h.on_velocity,
+                    Note::MAX_VELOCITY
+                )))
+            }
+            _ => Ok(self),
+        }
+    }
+}
+
+/// Quantas mensagens cada parte do filtro segurou, para diagnostico
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterStats {
+    /// Notas que voltaram antes da duracao minima do note off atrasado,
+    /// o note off e o note on seguinte foram descartados
+    pub short_notes: u64,
+    /// Note on abaixo da [Hysteresis::on_velocity]
+    pub below_threshold: u64,
+    pub duplicate_note_ons: u64,
+}
+
+impl FilterStats {
+    pub fn total(&self) -> u64 {
+        self.short_notes + self.below_threshold + self.duplicate_note_ons
+    }
+}
+
+#[derive(Debug)]
+struct ActiveNote {
+    started_at: Instant,
+    /// Note off segurado e quando ele pode ser enviado
+    pending_off: Option<(MidiWrapper, Instant)>,
+}
+
+/// Filtra os picos curtos de note on e note off que os sensores de sopro geram
+/// perto dos limites, antes das notas chegarem no callback
+#[derive(Debug)]
+pub struct InputFilter {
+    config: InputFilterConfig,
+    active: HashMap<(u8, u8), ActiveNote>,
+    stats: FilterStats,
+}
+
+impl InputFilter {
+    pub fn new(config: InputFilterConfig) -> ArduinoCommResult<Self> {
+        Ok(Self {
+            config: config.validated()?,
+            active: HashMap::new(),
+            stats: FilterStats::default(),
+        })
+    }
+
+    pub fn config(&self) -> InputFilterConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: InputFilterConfig) -> ArduinoCommResult<()> {
+        self.config = config.validated()?;
+        Ok(())
+    }
+
+    pub fn stats(&self) -> FilterStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = FilterStats::default();
+    }
+
+    /// Recebe uma nota que chegou em `now`, retorna a nota que deve seguir ou [None] se foi filtrada
+    pub fn process(&mut self, wrapper: MidiWrapper, now: Instant) -> Option<MidiWrapper> {
+        let key = (wrapper.channel, wrapper.note.byte);
+        let mut wrapper = wrapper;
+        if wrapper.state == Note::STATE_ON {
+            let Some(active) = self.active.get_mut(&key) else {
+                return match self.config.hysteresis {
+                    Some(h) if wrapper.air_strength < h.on_velocity => {
+                        self.stats.below_threshold += 1;
+                        None
+                    }
+                    _ => {
+                        self.active.insert(
+                            key,
+                            ActiveNote {
+                                started_at: now,
+                                pending_off: None,
+                            },
+                        );
+                        Some(wrapper)
+                    }
+                };
+            };
+            match self.config.hysteresis {
+                Some(h) if wrapper.air_strength < h.off_velocity => {
+                    wrapper.state = Note::STATE_OFF;
+                    wrapper.air_strength = 0;
+                }
+                _ => {
+                    // a nota voltou antes do note off atrasado sair, ela nunca parou
+                    if active.pending_off.take().is_some() {
+                        self.stats.short_notes += 1;
+                        return None;
+                    }
+                    if self.config.suppress_duplicate_note_ons {
+                        self.stats.duplicate_note_ons += 1;
+                        return None;
+                    }
+                    return Some(wrapper);
+                }
+            }
+        }
+        // note off de uma nota que foi filtrada ou que ja foi desligada,
+        // com o filtro desligado ele segue como chegou
+        let Some(active) = self.active.get_mut(&key) else {
+            return self.config.is_disabled().then_some(wrapper);
+        };
+        if active.pending_off.is_some() {
+            return None;
+        }
+        if let Some(min) = self.config.min_note_duration_ms.map(Duration::from_millis) {
+            let release_at = active.started_at + min;
+            if release_at > now {
+                active.pending_off = Some((wrapper, release_at));
+                return None;
+            }
+        }
+        self.active.remove(&key);
+        Some(wrapper)
+    }
+
+    /// Retorna os note off atrasados que ja podem ser enviados
+    pub fn flush(&mut self, now: Instant) -> Vec<MidiWrapper> {
+        let released: Vec<(u8, u8)> = self
+            .active
+            .iter()
+            .filter(|(_, a)| matches!(a.pending_off, Some((_, at)) if at <= now))
+            .map(|(k, _)| *k)
+            .collect();
+        released
+            .into_iter()
+            .filter_map(|k| self.active.remove(&k)?.pending_off)
+            .map(|(wrapper, _)| wrapper)
+            .collect()
+    }
+}
+
+/// [InputFilter] compartilhado entre a leitura do dispositivo e quem configura o filtro
+#[derive(Debug, Clone)]
+pub struct InputFilterHandle {
+    filter: Arc<Mutex<InputFilter>>,
+}
+
+impl InputFilterHandle {
+    pub fn new(config: InputFilterConfig) -> ArduinoCommResult<Self> {
+        Ok(Self {
+            filter: Arc::new(Mutex::new(InputFilter::new(config)?)),
+        })
+    }
+
+    pub fn config(&self) -> InputFilterConfig {
+        self.lock().config()
+    }
+
+    pub fn set_config(&self, config: InputFilterConfig) -> ArduinoCommResult<()> {
+        self.lock().set_config(config)
+    }
+
+    pub fn stats(&self) -> FilterStats {
+        self.lock().stats()
+    }
+
+    pub fn reset_stats(&self) {
+        self.lock().reset_stats()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InputFilter> {
+        self.filter.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Para a thread que envia os note off atrasados quando a inscricao é dropada
+struct FlushGuard(Arc<AtomicBool>);
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+impl MidiSubscriptions {
+    /// Igual ao [MidiSubscriptions::on_notes], mas as notas passam antes pelo `filter`.
+    ///
+    /// Os note off atrasados pelo filtro sao enviados por uma thread propria.
+    pub fn on_filtered_notes<F: Fn(MidiWrapper) + Send + Sync + 'static>(
+        self,
+        filter: InputFilterHandle,
+        callback: F,
+    ) -> Self {
+        let callback = Arc::new(callback);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let guard = FlushGuard(stopped.clone());
+        let flush_filter = filter.clone();
+        let flush_callback = callback.clone();
+        std::thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                std::thread::sleep(FLUSH_INTERVAL);
+                let released = flush_filter.lock().flush(Instant::now());
+                for wrapper in released {
+                    flush_callback(wrapper);
+                }
+            }
+        });
+        self.subscribe_timed(&MidiMessageKind::NOTES, move |timestamp, m| {
+            let _ = &guard;
+            let Some(wrapper) = MidiWrapper::from_message(m) else {
+                return;
+            };
+            let filtered = filter
+                .lock()
+                .process(wrapper.with_timestamp(timestamp), Instant::now());
+            if let Some(wrapper) = filtered {
+                callback(wrapper);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi_message::MidiMessage;
+
+    fn note(on: bool, velocity: u8) -> MidiWrapper {
+        let message = if on {
+            MidiMessage::NoteOn {
+                channel: 0,
+                key: 60,
+                velocity,
+            }
+        } else {
+            MidiMessage::NoteOff {
+                channel: 0,
+                key: 60,
+                velocity,
+            }
+        };
+        MidiWrapper::from_message(&message).unwrap()
+    }
+
+    #[test]
+    fn holds_short_note_offs_until_the_minimum_duration() {
+        let mut filter = InputFilter::new(InputFilterConfig::default()).unwrap();
+        let start = Instant::now();
+        let ms = |m: u64| start + Duration::from_millis(m);
+        assert!(filter.process(note(true, 80), ms(0)).is_some());
+        assert!(filter.process(note(false, 0), ms(5)).is_none());
+        // a nota voltou antes do note off sair, nada é enviado
+        assert!(filter.process(note(true, 80), ms(10)).is_none());
+        assert!(filter.flush(ms(40)).is_empty());
+
+        assert!(filter.process(note(false, 0), ms(20)).is_none());
+        assert!(filter.flush(ms(25)).is_empty());
+        assert_eq!(filter.flush(ms(30)).len(), 1);
+        assert!(filter.process(note(true, 80), ms(50)).is_some());
+        assert!(filter.process(note(false, 0), ms(100)).is_some());
+        assert_eq!(filter.stats().short_notes, 1);
+    }
+
+    #[test]
+    fn applies_hysteresis_and_drops_duplicates() {
+        let mut filter = InputFilter::new(InputFilterConfig {
+            min_note_duration_ms: None,
+            hysteresis: Some(Hysteresis {
+                on_velocity: 40,
+                off_velocity: 20,
+            }),
+            suppress_duplicate_note_ons: true,
+        })
+        .unwrap();
+        let now = Instant::now();
+        assert!(filter.process(note(true, 30), now).is_none());
+        assert!(filter.process(note(false, 0), now).is_none());
+        assert!(filter.process(note(true, 50), now).is_some());
+        assert!(filter.process(note(true, 30), now).is_none());
+        let off = filter.process(note(true, 10), now).unwrap();
+        assert_eq!(off.state, Note::STATE_OFF);
+        assert_eq!(
+            filter.stats(),
+            FilterStats {
+                short_notes: 0,
+                below_threshold: 1,
+                duplicate_note_ons: 1,
+            }
+        );
+
+        let mut disabled = InputFilter::new(InputFilterConfig::DISABLED).unwrap();
+        assert!(disabled.process(note(true, 1), now).is_some());
+        assert!(disabled.process(note(true, 1), now).is_some());
+        assert!(disabled.process(note(false, 0), now).is_some());
+        // note off de uma nota que o filtro nunca viu
+        assert!(disabled.process(note(false, 0), now).is_some());
+        assert_eq!(disabled.stats().total(), 0);
+        assert!(InputFilterConfig {
+            hysteresis: Some(Hysteresis {
+                on_velocity: 10,
+                off_velocity: 20,
+            }),
+            ..InputFilterConfig::DISABLED
+        }
+        .validated()
+        .is_err());
+    }
+}
@@ -0,0 +1,400 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use crate::input_source::{ConnectionHolder, InputSource, InputSourceKind, RawInputCallback};
+#[cfg(feature = "verbose")]
+use crate::LOG_TAG;
+#[cfg(feature = "verbose")]
+use paris::{info, warn};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const RECORDING_MAGIC: &[u8; 5] = b"ISREC";
+const RECORDING_VERSION: u8 = 1;
+/// Intervalo maximo dormindo no replay, para que ele pare logo ao ser dropado
+const REPLAY_MAX_SLEEP: Duration = Duration::from_millis(50);
+
+/// Bytes recebidos de uma fonte, `timestamp` em microssegundos como a fonte enviou
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedInput {
+    pub timestamp: u64,
+    pub bytes: Vec<u8>,
+}
+
+/// Sessao de entrada gravada, na ordem em que os bytes chegaram.
+///
+/// O arquivo é binario: `ISREC`, a versao e depois cada entrada como
+/// timestamp (u64), tamanho (u16) e os bytes, tudo little endian.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputRecording {
+    pub inputs: Vec<RecordedInput>,
+}
+
+impl InputRecording {
+    pub fn load(path: impl AsRef<Path>) -> ArduinoCommResult<Self> {
+        let file = File::open(path).map_err(recording_error)?;
+        Self::read_from(BufReader::new(file))
+    }
+
+    pub fn read_from<R: Read>(mut reader: R) -> ArduinoCommResult<Self> {
+        let mut header = [0u8; 6];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| invalid_recording("missing header"))?;
+        if &header[..5] != RECORDING_MAGIC {
+            return Err(invalid_recording("not an input recording"));
+        }
+        if header[5] != RECORDING_VERSION {
+            return Err(invalid_recording(&format!(
+                "unsupported version {}",
+                header[5]
+            )));
+        }
+        let mut inputs = vec![];
+        loop {
+            let mut timestamp = [0u8; 8];
+            // o arquivo so pode terminar entre duas entradas
+            match reader.read(&mut timestamp[..1]) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(recording_error(e)),
+            }
+            reader
+                .read_exact(&mut timestamp[1..])
+                .map_err(|_| invalid_recording("truncated input"))?;
+            let mut len = [0u8; 2];
+            reader
+                .read_exact(&mut len)
+                .map_err(|_| invalid_recording("truncated input"))?;
+            let mut bytes = vec![0u8; u16::from_le_bytes(len) as usize];
+            reader
+                .read_exact(&mut bytes)
+                .map_err(|_| invalid_recording("truncated input"))?;
+            inputs.push(RecordedInput {
+                timestamp: u64::from_le_bytes(timestamp),
+                bytes,
+            });
+        }
+        Ok(Self { inputs })
+    }
+
+    /// Tempo entre a primeira e a ultima entrada
+    pub fn duration(&self) -> Duration {
+        match (self.inputs.first(), self.inputs.last()) {
+            (Some(first), Some(last)) => {
+                Duration::from_micros(last.timestamp.saturating_sub(first.timestamp))
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Envia todas as entradas sem esperar, util para reproduzir uma sessao em testes
+    pub fn replay_instantly(&self, mut on_bytes: impl FnMut(u64, &[u8])) {
+        for input in &self.inputs {
+            on_bytes(input.timestamp, &input.bytes);
+        }
+    }
+}
+
+struct RecordingWriter {
+    writer: Box<dyn Write + Send>,
+    count: u64,
+}
+
+impl RecordingWriter {
+    fn new(mut writer: Box<dyn Write + Send>) -> ArduinoCommResult<Self> {
+        writer.write_all(RECORDING_MAGIC).map_err(recording_error)?;
+        writer
+            .write_all(&[RECORDING_VERSION])
+            .map_err(recording_error)?;
+        Ok(Self { writer, count: 0 })
+    }
+
+    fn write(&mut self, timestamp: u64, bytes: &[u8]) -> std::io::Result<()> {
+        // mensagens maiores que isso so acontecem com SysEx absurdas, corta o resto
+        let len = bytes.len().min(u16::MAX as usize);
+        self.writer.write_all(&timestamp.to_le_bytes())?;
+        self.writer.write_all(&(len as u16).to_le_bytes())?;
+        self.writer.write_all(&bytes[..len])?;
+        self.count += 1;
+        Ok(())
+    }
+}
+
+/// Grava os bytes crus de uma fonte de entrada.
+///
+/// A fonte precisa ser envolvida com [InputRecorder::wrap] antes de conectar,
+/// depois disso a gravacao pode ser ligada e desligada a qualquer momento.
+#[derive(Clone, Default)]
+pub struct InputRecorder {
+    writer: Arc<Mutex<Option<RecordingWriter>>>,
+}
+
+impl InputRecorder {
+    /// Começa a gravar no arquivo, substituindo ele caso exista
+    pub fn start(&self, path: impl AsRef<Path>) -> ArduinoCommResult<()> {
+        let file = File::create(path).map_err(recording_error)?;
+        self.start_with_writer(BufWriter::new(file))
+    }
+
+    pub fn start_with_writer<W: Write + Send + 'static>(&self, writer: W) -> ArduinoCommResult<()> {
+        let recording = RecordingWriter::new(Box::new(writer))?;
+        let Ok(mut w) = self.writer.lock() else {
+            return Err(ArduinoCommunicationError::PortError);
+        };
+        if let Some(mut previous) = w.replace(recording) {
+            let _ = previous.writer.flush();
+        }
+        Ok(())
+    }
+
+    /// Termina a gravacao, retorna quantas entradas foram gravadas
+    pub fn stop(&self) -> ArduinoCommResult<u64> {
+        let Ok(mut w) = self.writer.lock() else {
+            return Err(ArduinoCommunicationError::PortError);
+        };
+        let Some(mut recording) = w.take() else {
+            return Err(ArduinoCommunicationError::RecordingError(
+                "there is no recording in progress".to_string(),
+            ));
+        };
+        recording.writer.flush().map_err(recording_error)?;
+        Ok(recording.count)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.writer.lock().is_ok_and(|w| w.is_some())
+    }
+
+    /// Envolve a fonte para que tudo que ela enviar passe pelo gravador
+    pub fn wrap(&self, source: Box<dyn InputSource>) -> Box<dyn InputSource> {
+        Box::new(RecordingInput {
+            source,
+            recorder: self.clone(),
+        })
+    }
+
+    fn record(&self, timestamp: u64, bytes: &[u8]) {
+        let Ok(mut w) = self.writer.lock() else {
+            return;
+        };
+        let Some(recording) = w.as_mut() else {
+            return;
+        };
+        if let Err(_e) = recording.write(timestamp, bytes) {
+            #[cfg(feature = "verbose")]
+            {
+                warn!("[{}] Input recording stopped: {}", LOG_TAG, _e);
+            }
+            w.take();
+        }
+    }
+}
+
+struct RecordingInput {
+    source: Box<dyn InputSource>,
+    recorder: InputRecorder,
+}
+
+impl InputSource for RecordingInput {
+    fn name(&self) -> String {
+        self.source.name()
+    }
+
+    fn kind(&self) -> InputSourceKind {
+        self.source.kind()
+    }
+
+    fn start(
+        self: Box<Self>,
+        mut on_bytes: RawInputCallback,
+    ) -> ArduinoCommResult<ConnectionHolder> {
+        let recorder = self.recorder;
+        self.source.start(Box::new(move |timestamp, bytes| {
+            recorder.record(timestamp, bytes);
+            on_bytes(timestamp, bytes);
+        }))
+    }
+}
+
+/// Permite saber se o replay ja enviou todas as entradas
+#[derive(Debug, Clone)]
+pub struct ReplayProgress(Arc<AtomicBool>);
+
+impl ReplayProgress {
+    pub fn is_finished(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Fonte que envia uma sessao gravada, respeitando o tempo entre as entradas.
+///
+/// `speed` 1.0 é a velocidade original, valores maiores reproduzem mais rapido.
+pub struct ReplayInput {
+    name: String,
+    recording: InputRecording,
+    speed: f32,
+    finished: Arc<AtomicBool>,
+}
+
+impl ReplayInput {
+    pub fn new(name: &str, recording: InputRecording, speed: f32) -> ArduinoCommResult<Self> {
+        if !speed.is_finite() || speed < 1.0 {
+            return Err(ArduinoCommunicationError::InvalidReplaySpeed(speed));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            recording,
+            speed,
+            finished: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub fn progress(&self) -> ReplayProgress {
+        ReplayProgress(self.finished.clone())
+    }
+}
+
+/// Para o replay quando é dropada
+struct ReplayConnection {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for ReplayConnection {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl InputSource for ReplayInput {
+    fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    fn kind(&self) -> InputSourceKind {
+        InputSourceKind::Replay
+    }
+
+    fn start(
+        self: Box<Self>,
+        mut on_bytes: RawInputCallback,
+    ) -> ArduinoCommResult<ConnectionHolder> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        #[cfg(feature = "verbose")]
+        {
+            info!(
+                "[{}] Replaying {} inputs at {}x",
+                LOG_TAG,
+                self.recording.inputs.len(),
+                self.speed
+            );
+        }
+        std::thread::spawn(move || {
+            let origin = self.recording.inputs.first().map_or(0, |i| i.timestamp);
+            let started_at = Instant::now();
+            for input in &self.recording.inputs {
+                let offset = input.timestamp.saturating_sub(origin) as f64 / self.speed as f64;
+                let target = started_at + Duration::from_micros(offset as u64);
+                loop {
+                    if stopped.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let now = Instant::now();
+                    if now >= target {
+                        break;
+                    }
+                    std::thread::sleep((target - now).min(REPLAY_MAX_SLEEP));
+                }
+                on_bytes(input.timestamp, &input.bytes);
+            }
+            self.finished.store(true, Ordering::Relaxed);
+        });
+        Ok(ConnectionHolder::new(ReplayConnection { stop }))
+    }
+}
+
+fn recording_error(e: std::io::Error) -> ArduinoCommunicationError {
+    ArduinoCommunicationError::RecordingError(e.to_string())
+}
+
+fn invalid_recording(reason: &str) -> ArduinoCommunicationError {
+    ArduinoCommunicationError::InvalidRecording(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_source::start_listening;
+    use crate::midi_subscriptions::MidiSubscriptions;
+    use crate::midi_wrapper::MidiWrapper;
+    use crate::virtual_input::{virtual_input, VIRTUAL_DEVICE_NAME};
+
+    /// Writer que guarda os bytes em memoria para o teste ler depois
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recorded_session_replays_the_same_notes() {
+        let recorder = InputRecorder::default();
+        let buffer = SharedBuffer::default();
+        let (input, sender) = virtual_input(VIRTUAL_DEVICE_NAME);
+        let holder =
+            start_listening(recorder.wrap(Box::new(input)), MidiSubscriptions::new()).unwrap();
+
+        sender.note_on(60, 10).unwrap();
+        recorder.start_with_writer(buffer.clone()).unwrap();
+        sender.note_on(62, 100).unwrap();
+        sender.control_change(2, 50).unwrap();
+        sender.note_off(62).unwrap();
+        assert_eq!(recorder.stop().unwrap(), 3);
+        assert!(recorder.stop().is_err());
+        drop(holder);
+
+        let bytes = buffer.0.lock().unwrap().clone();
+        let recording = InputRecording::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(recording.inputs.len(), 3);
+        assert_eq!(recording.inputs[1].bytes, vec![0xB0, 2, 50]);
+
+        let notes: Arc<Mutex<Vec<MidiWrapper>>> = Arc::new(Mutex::new(vec![]));
+        let n = notes.clone();
+        let replay = ReplayInput::new("replay", recording, 1000.0).unwrap();
+        let progress = replay.progress();
+        let _holder = start_listening(
+            Box::new(replay),
+            MidiSubscriptions::new().on_notes(move |w| n.lock().unwrap().push(w)),
+        )
+        .unwrap();
+        while !progress.is_finished() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let notes = notes.lock().unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].note.byte, 62);
+        assert_eq!(notes[0].air_strength, 100);
+    }
+
+    #[test]
+    fn rejects_invalid_files_and_speeds() {
+        assert!(InputRecording::read_from(&b"MThd\x01"[..]).is_err());
+        assert!(InputRecording::read_from(&b"ISREC\x01\x00\x00"[..]).is_err());
+        let empty = InputRecording::read_from(&b"ISREC\x01"[..]).unwrap();
+        assert_eq!(empty.duration(), Duration::ZERO);
+        assert!(ReplayInput::new("replay", empty.clone(), 0.5).is_err());
+        assert!(ReplayInput::new("replay", empty, f32::NAN).is_err());
+    }
+}
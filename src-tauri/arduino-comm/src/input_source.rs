@@ -0,0 +1,78 @@
+use crate::errors::ArduinoCommResult;
+use crate::midi_parser::MidiParser;
+use crate::midi_subscriptions::MidiSubscriptions;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Recebe os bytes midi crus de uma fonte, junto com o tempo em microssegundos
+/// em que eles chegaram (contado a partir de um ponto qualquer definido pela fonte)
+pub type RawInputCallback = Box<dyn FnMut(u64, &[u8]) + Send + 'static>;
+
+/// Tipo da fonte de entrada, usado para saber como monitorar a conexao
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputSourceKind {
+    /// Porta midi real do sistema, via midir
+    Midi,
+    /// Dispositivo de software, alimentado pelo proprio programa
+    Virtual,
+    /// Sessao gravada anteriormente sendo reproduzida
+    Replay,
+    /// Placa conectada direto pela serial, com o protocolo de frames
+    Serial,
+    /// Outro programa enviando mensagens OSC por UDP
+    Osc,
+}
+
+/// Algo que produz bytes midi, seja um dispositivo real ou nao.
+///
+/// A conexao fica ativa enquanto o [ConnectionHolder] retornado por
+/// [InputSource::start] existir.
+pub trait InputSource: Send {
+    fn name(&self) -> String;
+    fn kind(&self) -> InputSourceKind;
+    fn start(self: Box<Self>, on_bytes: RawInputCallback) -> ArduinoCommResult<ConnectionHolder>;
+}
+
+/// Mantem uma conexao aberta, a conexao é fechada quando ele é dropado
+pub struct ConnectionHolder {
+    _con: Box<dyn Send>,
+    alive: Option<Arc<AtomicBool>>,
+}
+
+impl ConnectionHolder {
+    pub fn new<T: Send + 'static>(connection: T) -> Self {
+        Self {
+            _con: Box::new(connection),
+            alive: None,
+        }
+    }
+
+    /// Para fontes que sabem quando a conexao caiu, `alive` vira false quando isso acontece
+    pub fn with_liveness<T: Send + 'static>(connection: T, alive: Arc<AtomicBool>) -> Self {
+        Self {
+            _con: Box::new(connection),
+            alive: Some(alive),
+        }
+    }
+
+    /// Se a fonte ainda esta enviando dados, fontes sem essa informacao sempre estao vivas
+    pub fn is_alive(&self) -> bool {
+        match self.alive.as_ref() {
+            Some(a) => a.load(Ordering::Relaxed),
+            None => true,
+        }
+    }
+}
+
+/// Começa a ler a fonte, interpretando os bytes e enviando as mensagens para os inscritos
+pub fn start_listening(
+    source: Box<dyn InputSource>,
+    subscriptions: MidiSubscriptions,
+) -> ArduinoCommResult<ConnectionHolder> {
+    let mut parser = MidiParser::new();
+    source.start(Box::new(move |timestamp, bytes| {
+        for message in parser.feed(bytes) {
+            subscriptions.dispatch(timestamp, &message);
+        }
+    }))
+}
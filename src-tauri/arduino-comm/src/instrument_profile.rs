@@ -0,0 +1,189 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use crate::note::Note;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use strum::IntoEnumIterator;
+
+/// Instrumento usado quando nenhum outro é escolhido, é a ocarina original do projeto
+pub const DEFAULT_INSTRUMENT_ID: &str = "ocarina-12";
+const DEFAULT_INSTRUMENT_NAME: &str = "Ocarina 12 furos";
+/// Byte midi da primeira nota do [Note]
+const NOTE_ENUM_FIRST_KEY: u8 = 55;
+const MAX_KEY: u8 = 127;
+
+/// Uma nota que o instrumento consegue tocar
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstrumentNote {
+    /// Byte midi que o instrumento envia para essa nota
+    pub key: u8,
+    /// Nome mostrado para o paciente, bemois usam `b` depois da letra (`Ab3`)
+    pub name: String,
+    /// Linha da nota na partitura, 0 é a natural mais aguda.
+    /// Quando omitido é calculado, o bemol fica na mesma linha da natural acima dele.
+    #[serde(default)]
+    pub ordinal: Option<u8>,
+}
+
+impl InstrumentNote {
+    pub fn is_bmol(&self) -> bool {
+        self.name.chars().skip(1).any(|c| c == 'b')
+    }
+
+    /// Linha na partitura, sempre existe depois de [InstrumentProfile::validated]
+    pub fn ordinal(&self) -> u8 {
+        self.ordinal.unwrap_or_default()
+    }
+}
+
+/// Extensao e nomes das notas de um instrumento, carregado dos arquivos de dados
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstrumentProfile {
+    pub id: String,
+    pub name: String,
+    pub notes: Vec<InstrumentNote>,
+}
+
+impl Default for InstrumentProfile {
+    fn default() -> Self {
+        let notes = Note::iter()
+            .enumerate()
+            .map(|(i, note)| {
+                let name: &str = note.into();
+                InstrumentNote {
+                    key: NOTE_ENUM_FIRST_KEY + i as u8,
+                    name: name.to_string(),
+                    ordinal: Some(note.ordinal()),
+                }
+            })
+            .collect();
+        Self {
+            id: DEFAULT_INSTRUMENT_ID.to_string(),
+            name: DEFAULT_INSTRUMENT_NAME.to_string(),
+            notes,
+        }
+    }
+}
+
+impl InstrumentProfile {
+    /// Confere o perfil, ordena as notas e calcula os ordinais que faltam
+    pub fn validated(mut self) -> ArduinoCommResult<Self> {
+        if self.id.is_empty() || self.notes.is_empty() {
+            return Err(self.invalid("an instrument needs an id and at least one note"));
+        }
+        let mut keys = HashSet::new();
+        for n in &self.notes {
+            if n.key > MAX_KEY || !keys.insert(n.key) {
+                return Err(self.invalid(&format!("key {} is invalid or repeated", n.key)));
+            }
+        }
+        self.notes.sort_by_key(|n| n.key);
+        let mut ordinal = 0u8;
+        for i in (0..self.notes.len()).rev() {
+            if self.notes[i].is_bmol() || self.notes[i].ordinal.is_some() {
+                continue;
+            }
+            self.notes[i].ordinal = Some(ordinal);
+            ordinal += 1;
+        }
+        for i in 0..self.notes.len() {
+            if self.notes[i].ordinal.is_some() {
+                continue;
+            }
+            let key = self.notes[i].key;
+            let Some(natural) = self
+                .notes
+                .iter()
+                .find(|n| Some(n.key) == key.checked_add(1) && !n.is_bmol())
+            else {
+                return Err(self.invalid(&format!(
+                    "flat note {} needs an ordinal because the note above it is missing",
+                    self.notes[i].name
+                )));
+            };
+            self.notes[i].ordinal = natural.ordinal;
+        }
+        Ok(self)
+    }
+
+    pub fn note(&self, key: u8) -> ArduinoCommResult<&InstrumentNote> {
+        self.notes
+            .iter()
+            .find(|n| n.key == key)
+            .ok_or(ArduinoCommunicationError::ByteNotSupported(key))
+    }
+
+    pub fn contains(&self, key: u8) -> bool {
+        self.notes.iter().any(|n| n.key == key)
+    }
+
+    pub fn lowest_key(&self) -> u8 {
+        self.notes.first().map_or(0, |n| n.key)
+    }
+
+    pub fn highest_key(&self) -> u8 {
+        self.notes.last().map_or(0, |n| n.key)
+    }
+
+    /// Notas da lista que o instrumento nao consegue tocar, sem repeticoes
+    pub fn unsupported_keys(&self, keys: impl IntoIterator<Item = u8>) -> Vec<u8> {
+        let mut unsupported: Vec<u8> = keys
+            .into_iter()
+            .filter(|k| !self.contains(*k))
+            .collect::<HashSet<u8>>()
+            .into_iter()
+            .collect();
+        unsupported.sort_unstable();
+        unsupported
+    }
+
+    fn invalid(&self, reason: &str) -> ArduinoCommunicationError {
+        ArduinoCommunicationError::InvalidInstrumentProfile(format!("{}: {}", self.id, reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_profile_matches_the_note_enum() {
+        let profile = InstrumentProfile::default().validated().unwrap();
+        for (i, note) in Note::iter().enumerate() {
+            let key = NOTE_ENUM_FIRST_KEY + i as u8;
+            let mapped = profile.note(key).unwrap();
+            let name: &str = note.into();
+            assert_eq!(mapped.name, name);
+            assert_eq!(mapped.ordinal(), note.ordinal());
+            assert_eq!(mapped.is_bmol(), note.is_bmol());
+        }
+        assert!(profile.note(54).is_err());
+        assert_eq!(profile.unsupported_keys([54, 60, 54, 90]), vec![54, 90]);
+    }
+
+    #[test]
+    fn ordinals_are_calculated_from_the_names() {
+        let note = |key: u8, name: &str| InstrumentNote {
+            key,
+            name: name.to_string(),
+            ordinal: None,
+        };
+        let profile = InstrumentProfile {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            notes: vec![note(62, "D4"), note(60, "C4"), note(61, "Db4")],
+        }
+        .validated()
+        .unwrap();
+        assert_eq!(profile.lowest_key(), 60);
+        assert_eq!(profile.note(62).unwrap().ordinal(), 0);
+        assert_eq!(profile.note(61).unwrap().ordinal(), 0);
+        assert_eq!(profile.note(60).unwrap().ordinal(), 1);
+
+        let broken = InstrumentProfile {
+            id: "broken".to_string(),
+            name: "Broken".to_string(),
+            notes: vec![note(60, "C4"), note(61, "Db4")],
+        };
+        assert!(broken.validated().is_err());
+    }
+}
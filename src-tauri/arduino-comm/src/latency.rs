@@ -0,0 +1,160 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_CLICK_INTERVAL: Duration = Duration::from_millis(750);
+pub const DEFAULT_CLICK_COUNT: u32 = 16;
+/// Cliques tocados antes de começar a medir, para o paciente pegar o ritmo
+const LEAD_IN_CLICKS: u32 = 4;
+const MIN_CLICK_INTERVAL: Duration = Duration::from_millis(250);
+/// Menor fracao dos cliques medidos que precisa ter uma nota tocada junto
+const MIN_MATCHED_FRACTION: f64 = 0.5;
+
+/// Mede a latencia de entrada de um dispositivo.
+///
+/// O paciente toca junto com cliques em intervalo fixo e a latencia é a media
+/// da diferenca entre cada nota e o clique mais proximo. Os tempos sao contados
+/// a partir do inicio da calibracao, com o mesmo relogio usado para comparar as
+/// notas com a musica.
+#[derive(Debug, Clone)]
+pub struct LatencyCalibration {
+    interval: Duration,
+    clicks: u32,
+    taps: Vec<Duration>,
+}
+
+impl LatencyCalibration {
+    pub fn new(interval: Duration, clicks: u32) -> ArduinoCommResult<Self> {
+        if interval < MIN_CLICK_INTERVAL || clicks == 0 {
+            return Err(ArduinoCommunicationError::InvalidLatencyCalibration(
+                format!(
+                    "{} clicks every {:?} is not a valid calibration",
+                    clicks, interval
+                ),
+            ));
+        }
+        Ok(Self {
+            interval,
+            clicks,
+            taps: vec![],
+        })
+    }
+
+    /// Tempo de cada clique desde o inicio, contando os cliques de preparacao
+    pub fn click_times(&self) -> Vec<Duration> {
+        (1..=LEAD_IN_CLICKS + self.clicks)
+            .map(|i| self.interval * i)
+            .collect()
+    }
+
+    /// Duracao total, depois dela nao ha mais cliques para acompanhar
+    pub fn duration(&self) -> Duration {
+        self.interval * (LEAD_IN_CLICKS + self.clicks + 1)
+    }
+
+    pub fn record_tap(&mut self, at: Duration) {
+        self.taps.push(at);
+    }
+
+    /// Latencia media, nunca negativa. Erro se o paciente nao acompanhou os cliques
+    pub fn offset(&self) -> ArduinoCommResult<Duration> {
+        let measured = &self.click_times()[LEAD_IN_CLICKS as usize..];
+        let max_distance = self.interval.as_micros() as i64 / 2;
+        let offsets: Vec<i64> = measured
+            .iter()
+            .filter_map(|click| {
+                let click = click.as_micros() as i64;
+                self.taps
+                    .iter()
+                    .map(|tap| tap.as_micros() as i64 - click)
+                    .filter(|offset| offset.abs() < max_distance)
+                    .min_by_key(|offset| offset.abs())
+            })
+            .collect();
+        if (offsets.len() as f64) < measured.len() as f64 * MIN_MATCHED_FRACTION {
+            return Err(ArduinoCommunicationError::InvalidLatencyCalibration(
+                format!(
+                    "only {} of {} clicks were played",
+                    offsets.len(),
+                    measured.len()
+                ),
+            ));
+        }
+        let mean = offsets.iter().sum::<i64>() / offsets.len() as i64;
+        Ok(Duration::from_micros(mean.max(0) as u64))
+    }
+}
+
+/// Converte o tempo dado pela fonte de uma conexao em [Instant].
+///
+/// A origem é o menor `chegada - tempo da fonte` visto ate agora, entao uma mensagem
+/// que demorou para ser entregue, ou que ficou segurada pelo filtro de entrada,
+/// continua com o tempo em que a fonte recebeu ela.
+#[derive(Debug, Default)]
+pub struct DeviceClock {
+    origin: Mutex<Option<Instant>>,
+}
+
+impl DeviceClock {
+    pub fn instant(&self, time: Duration, received_at: Instant) -> Instant {
+        let Some(candidate) = received_at.checked_sub(time) else {
+            return received_at;
+        };
+        let Ok(mut origin) = self.origin.lock() else {
+            return received_at;
+        };
+        let origin = match *origin {
+            Some(o) if o <= candidate => o,
+            _ => *origin.insert(candidate),
+        };
+        origin + time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_the_mean_offset_of_the_taps() {
+        let interval = Duration::from_millis(500);
+        let mut calibration = LatencyCalibration::new(interval, 4).unwrap();
+        let clicks = calibration.click_times();
+        assert_eq!(clicks.len(), 8);
+        // os cliques de preparacao e as notas longe de qualquer clique sao ignorados
+        calibration.record_tap(clicks[0] + Duration::from_millis(200));
+        calibration.record_tap(clicks[4] + Duration::from_millis(30));
+        calibration.record_tap(clicks[5] + Duration::from_millis(50));
+        calibration.record_tap(clicks[6] + Duration::from_millis(300));
+        calibration.record_tap(clicks[7] + Duration::from_millis(40));
+        assert_eq!(calibration.offset().unwrap(), Duration::from_millis(40));
+
+        let mut early = LatencyCalibration::new(interval, 2).unwrap();
+        for click in &early.click_times()[4..] {
+            early.record_tap(*click - Duration::from_millis(10));
+        }
+        assert_eq!(early.offset().unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn fails_when_the_clicks_were_not_followed() {
+        let mut calibration = LatencyCalibration::new(Duration::from_millis(500), 4).unwrap();
+        calibration.record_tap(Duration::from_millis(2_510));
+        assert!(calibration.offset().is_err());
+        assert!(LatencyCalibration::new(Duration::from_millis(10), 4).is_err());
+    }
+
+    #[test]
+    fn device_clock_keeps_the_source_spacing() {
+        let clock = DeviceClock::default();
+        let start = Instant::now();
+        let first = clock.instant(Duration::from_millis(100), start + Duration::from_millis(105));
+        assert_eq!(first, start + Duration::from_millis(5) + Duration::from_millis(100));
+        // chegou atrasada, mas a fonte recebeu ela 50ms depois da primeira
+        let late = clock.instant(Duration::from_millis(150), start + Duration::from_millis(400));
+        assert_eq!(late - first, Duration::from_millis(50));
+        // chegou mais rapido que a primeira, a origem anda para tras
+        let fast = clock.instant(Duration::from_millis(200), start + Duration::from_millis(201));
+        assert_eq!(fast, start + Duration::from_millis(201));
+    }
+}
@@ -1,7 +1,25 @@
+pub mod breath_calibration;
+pub mod breath_pressure;
+pub mod device_config;
+pub mod device_profile;
+pub mod device_watcher;
 pub mod errors;
+pub mod firmware_upload;
+pub mod input_filter;
+pub mod input_recording;
+pub mod input_source;
+pub mod instrument_profile;
+pub mod latency;
 pub mod midi_connection;
+pub mod midi_message;
+pub mod midi_parser;
+pub mod midi_subscriptions;
+pub mod midi_thru;
 pub mod midi_wrapper;
 pub mod note;
+pub mod osc;
+pub mod serial_connection;
+pub mod virtual_input;
 
 #[cfg(feature = "verbose")]
 pub(crate) const LOG_TAG: &str = "ARD";
\ No newline at end of file
@@ -1,5 +1,8 @@
 use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
-use crate::midi_wrapper::MidiWrapper;
+use crate::input_source::{
+    start_listening, ConnectionHolder, InputSource, InputSourceKind, RawInputCallback,
+};
+use crate::midi_subscriptions::MidiSubscriptions;
 #[cfg(feature = "verbose")]
 use crate::LOG_TAG;
 use midir::{MidiInput, MidiInputConnection, MidiInputPort};
@@ -16,32 +19,34 @@ pub struct MidiConnection {
     conn: MidiInput
 }
 
-pub struct ConnectionHolder {
-    _con: MidiInputConnection<()>,
-}
-
 impl MidiConnection {
-    pub fn start_connection<C: Fn(MidiWrapper) + Send + 'static>(
+    pub fn start_connection(
         self,
-        callback: C
+        subscriptions: MidiSubscriptions
     ) -> ArduinoCommResult<ConnectionHolder> {
-        let con = self.conn.connect(
+        start_listening(Box::new(self), subscriptions)
+    }
+}
+
+impl InputSource for MidiConnection {
+    fn name(&self) -> String {
+        self.port_name.to_owned()
+    }
+
+    fn kind(&self) -> InputSourceKind {
+        InputSourceKind::Midi
+    }
+
+    fn start(self: Box<Self>, mut on_bytes: RawInputCallback) -> ArduinoCommResult<ConnectionHolder> {
+        let con: MidiInputConnection<()> = self.conn.connect(
             &self.port,
             INSPIRE_PORT_NAME,
-            move |_, x, _| {
-                if x.len() < 3 { return }
-                let wrapper = MidiWrapper::new_from_bytes(
-                    x[0],
-                    x[1],
-                    x[2]
-                );
-                callback(wrapper);
-            },
+            move |stamp, x, _| on_bytes(stamp, x),
             ()
         ).map_err(move |e| {
             ArduinoCommunicationError::PortListenError(e.to_string())
         })?;
-        Ok(ConnectionHolder { _con: con })
+        Ok(ConnectionHolder::new(con))
     }
 }
 
@@ -0,0 +1,125 @@
+use serde::Serialize;
+use std::fmt::Display;
+use strum_macros::{EnumIter, IntoStaticStr};
+
+/// Uma mensagem midi ja interpretada, `channel` vai de 0 a 15
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum MidiMessage {
+    NoteOff {
+        channel: u8,
+        key: u8,
+        velocity: u8,
+    },
+    NoteOn {
+        channel: u8,
+        key: u8,
+        velocity: u8,
+    },
+    PolyAftertouch {
+        channel: u8,
+        key: u8,
+        pressure: u8,
+    },
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    ProgramChange {
+        channel: u8,
+        program: u8,
+    },
+    ChannelPressure {
+        channel: u8,
+        pressure: u8,
+    },
+    /// Valor de -8192 a 8191, 0 é o centro
+    PitchBend {
+        channel: u8,
+        value: i16,
+    },
+    /// Conteudo da mensagem sem os bytes 0xF0 e 0xF7
+    SysEx(Vec<u8>),
+    TimeCodeQuarterFrame(u8),
+    SongPosition(u16),
+    SongSelect(u8),
+    TuneRequest,
+    Clock,
+    Start,
+    Continue,
+    Stop,
+    ActiveSensing,
+    Reset,
+}
+
+/// Tipos de mensagem, usados para escolher quais mensagens cada inscrito recebe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, IntoStaticStr, Serialize)]
+pub enum MidiMessageKind {
+    NoteOff,
+    NoteOn,
+    PolyAftertouch,
+    ControlChange,
+    ProgramChange,
+    ChannelPressure,
+    PitchBend,
+    SysEx,
+    SystemCommon,
+    SystemRealTime,
+}
+
+impl MidiMessageKind {
+    /// As duas mensagens de nota
+    pub const NOTES: [MidiMessageKind; 2] = [MidiMessageKind::NoteOn, MidiMessageKind::NoteOff];
+}
+
+impl MidiMessage {
+    pub fn kind(&self) -> MidiMessageKind {
+        match self {
+            MidiMessage::NoteOff { .. } => MidiMessageKind::NoteOff,
+            MidiMessage::NoteOn { .. } => MidiMessageKind::NoteOn,
+            MidiMessage::PolyAftertouch { .. } => MidiMessageKind::PolyAftertouch,
+            MidiMessage::ControlChange { .. } => MidiMessageKind::ControlChange,
+            MidiMessage::ProgramChange { .. } => MidiMessageKind::ProgramChange,
+            MidiMessage::ChannelPressure { .. } => MidiMessageKind::ChannelPressure,
+            MidiMessage::PitchBend { .. } => MidiMessageKind::PitchBend,
+            MidiMessage::SysEx(_) => MidiMessageKind::SysEx,
+            MidiMessage::TimeCodeQuarterFrame(_)
+            | MidiMessage::SongPosition(_)
+            | MidiMessage::SongSelect(_)
+            | MidiMessage::TuneRequest => MidiMessageKind::SystemCommon,
+            MidiMessage::Clock
+            | MidiMessage::Start
+            | MidiMessage::Continue
+            | MidiMessage::Stop
+            | MidiMessage::ActiveSensing
+            | MidiMessage::Reset => MidiMessageKind::SystemRealTime,
+        }
+    }
+
+    pub fn channel(&self) -> Option<u8> {
+        match self {
+            MidiMessage::NoteOff { channel, .. }
+            | MidiMessage::NoteOn { channel, .. }
+            | MidiMessage::PolyAftertouch { channel, .. }
+            | MidiMessage::ControlChange { channel, .. }
+            | MidiMessage::ProgramChange { channel, .. }
+            | MidiMessage::ChannelPressure { channel, .. }
+            | MidiMessage::PitchBend { channel, .. } => Some(*channel),
+            _ => None,
+        }
+    }
+
+    /// Se a mensagem liga uma nota, note on com velocity 0 conta como note off
+    pub fn is_note_on(&self) -> bool {
+        matches!(self, MidiMessage::NoteOn { velocity, .. } if *velocity > 0)
+    }
+}
+
+impl Display for MidiMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MidiMessage::SysEx(data) => write!(f, "SysEx({} bytes)", data.len()),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use crate::midi_message::MidiMessage;
+
+const STATUS_BIT: u8 = 0x80;
+const STATUS_MASK: u8 = 0xF0;
+const CHANNEL_MASK: u8 = 0x0F;
+const SYSEX_START: u8 = 0xF0;
+const SYSEX_END: u8 = 0xF7;
+const REAL_TIME_START: u8 = 0xF8;
+const PITCH_BEND_CENTER: i16 = 8192;
+/// Limite para uma SysEx que nunca termina nao crescer para sempre
+const MAX_SYSEX_LENGTH: usize = 4096;
+
+/// Le uma sequencia de bytes midi e monta as mensagens.
+///
+/// Suporta running status, mensagens de 1 e 2 bytes de dados, SysEx quebrada em
+/// varios pedacos e mensagens de tempo real no meio de outras mensagens.
+/// O estado é mantido entre as chamadas de [MidiParser::feed].
+#[derive(Debug, Default, Clone)]
+pub struct MidiParser {
+    status: Option<u8>,
+    data: Vec<u8>,
+    sysex: Option<Vec<u8>>,
+}
+
+impl MidiParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<MidiMessage> {
+        bytes.iter().filter_map(|b| self.push(*b)).collect()
+    }
+
+    /// Le um byte, retorna a mensagem caso ela tenha sido completada por ele
+    pub fn push(&mut self, byte: u8) -> Option<MidiMessage> {
+        if byte >= REAL_TIME_START {
+            return Self::real_time(byte);
+        }
+        if let Some(sysex) = self.sysex.as_mut() {
+            if byte == SYSEX_END {
+                return self.sysex.take().map(MidiMessage::SysEx);
+            }
+            if byte & STATUS_BIT == 0 {
+                if sysex.len() < MAX_SYSEX_LENGTH {
+                    sysex.push(byte);
+                }
+                return None;
+            }
+            // Um novo status sem o 0xF7 descarta a SysEx incompleta
+            self.sysex = None;
+        }
+        if byte & STATUS_BIT != 0 {
+            self.data.clear();
+            return match byte {
+                SYSEX_START => {
+                    self.status = None;
+                    self.sysex = Some(vec![]);
+                    None
+                }
+                0xF6 => {
+                    self.status = None;
+                    Some(MidiMessage::TuneRequest)
+                }
+                0xF1..=0xF3 => {
+                    self.status = Some(byte);
+                    None
+                }
+                0xF4 | 0xF5 | SYSEX_END => {
+                    self.status = None;
+                    None
+                }
+                _ => {
+                    self.status = Some(byte);
+                    None
+                }
+            };
+        }
+        let status = self.status?;
+        self.data.push(byte);
+        if self.data.len() < Self::data_length(status) {
+            return None;
+        }
+        let message = Self::build(status, &self.data);
+        self.data.clear();
+        if status >= SYSEX_START {
+            // mensagens de sistema nao usam running status
+            self.status = None;
+        }
+        Some(message)
+    }
+
+    fn data_length(status: u8) -> usize {
+        match status & STATUS_MASK {
+            0xC0 | 0xD0 => 1,
+            0xF0 => match status {
+                0xF1 | 0xF3 => 1,
+                _ => 2,
+            },
+            _ => 2,
+        }
+    }
+
+    fn build(status: u8, data: &[u8]) -> MidiMessage {
+        let channel = status & CHANNEL_MASK;
+        match status & STATUS_MASK {
+            0x80 => MidiMessage::NoteOff {
+                channel,
+                key: data[0],
+                velocity: data[1],
+            },
+            0x90 => MidiMessage::NoteOn {
+                channel,
+                key: data[0],
+                velocity: data[1],
+            },
+            0xA0 => MidiMessage::PolyAftertouch {
+                channel,
+                key: data[0],
+                pressure: data[1],
+            },
+            0xB0 => MidiMessage::ControlChange {
+                channel,
+                controller: data[0],
+                value: data[1],
+            },
+            0xC0 => MidiMessage::ProgramChange {
+                channel,
+                program: data[0],
+            },
+            0xD0 => MidiMessage::ChannelPressure {
+                channel,
+                pressure: data[0],
+            },
+            0xE0 => MidiMessage::PitchBend {
+                channel,
+                value: Self::fourteen_bits(data) as i16 - PITCH_BEND_CENTER,
+            },
+            _ => match status {
+                0xF1 => MidiMessage::TimeCodeQuarterFrame(data[0]),
+                0xF3 => MidiMessage::SongSelect(data[0]),
+                _ => MidiMessage::SongPosition(Self::fourteen_bits(data)),
+            },
+        }
+    }
+
+    #[inline]
+    fn fourteen_bits(data: &[u8]) -> u16 {
+        (data[1] as u16) << 7 | data[0] as u16
+    }
+
+    fn real_time(byte: u8) -> Option<MidiMessage> {
+        match byte {
+            0xF8 => Some(MidiMessage::Clock),
+            0xFA => Some(MidiMessage::Start),
+            0xFB => Some(MidiMessage::Continue),
+            0xFC => Some(MidiMessage::Stop),
+            0xFE => Some(MidiMessage::ActiveSensing),
+            0xFF => Some(MidiMessage::Reset),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_status_repeats_last_status() {
+        let mut parser = MidiParser::new();
+        let messages = parser.feed(&[0x91, 60, 100, 62, 90, 60, 0]);
+        assert_eq!(
+            messages,
+            vec![
+                MidiMessage::NoteOn {
+                    channel: 1,
+                    key: 60,
+                    velocity: 100
+                },
+                MidiMessage::NoteOn {
+                    channel: 1,
+                    key: 62,
+                    velocity: 90
+                },
+                MidiMessage::NoteOn {
+                    channel: 1,
+                    key: 60,
+                    velocity: 0
+                },
+            ]
+        );
+        assert!(!messages[2].is_note_on());
+    }
+
+    #[test]
+    fn short_and_split_messages() {
+        let mut parser = MidiParser::new();
+        assert_eq!(
+            parser.feed(&[0xD0, 42]),
+            vec![MidiMessage::ChannelPressure {
+                channel: 0,
+                pressure: 42
+            }]
+        );
+        assert!(parser.feed(&[0xB2, 2]).is_empty());
+        assert_eq!(
+            parser.feed(&[64, 0xE0, 0, 64]),
+            vec![
+                MidiMessage::ControlChange {
+                    channel: 2,
+                    controller: 2,
+                    value: 64
+                },
+                MidiMessage::PitchBend {
+                    channel: 0,
+                    value: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sysex_with_real_time_in_the_middle() {
+        let mut parser = MidiParser::new();
+        assert_eq!(parser.feed(&[0xF0, 0x7D, 0x01]), vec![]);
+        assert_eq!(
+            parser.feed(&[0xF8, 0x02, 0xF7, 0x80, 60, 0]),
+            vec![
+                MidiMessage::Clock,
+                MidiMessage::SysEx(vec![0x7D, 0x01, 0x02]),
+                MidiMessage::NoteOff {
+                    channel: 0,
+                    key: 60,
+                    velocity: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn data_without_status_is_ignored() {
+        let mut parser = MidiParser::new();
+        assert!(parser.feed(&[60, 100]).is_empty());
+        assert!(parser.feed(&[0xF2, 0, 1, 5]).len() == 1);
+    }
+}
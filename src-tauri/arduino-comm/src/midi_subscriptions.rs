@@ -0,0 +1,58 @@
+use crate::midi_message::{MidiMessage, MidiMessageKind};
+use crate::midi_wrapper::MidiWrapper;
+
+/// Recebe a mensagem junto com o tempo em microssegundos dado pela fonte, ver [crate::input_source::RawInputCallback]
+type Subscriber = Box<dyn Fn(u64, &MidiMessage) + Send + 'static>;
+
+/// Lista de funcoes que recebem as mensagens lidas do dispositivo,
+/// cada uma recebe apenas os tipos de mensagem em que se inscreveu.
+#[derive(Default)]
+pub struct MidiSubscriptions {
+    subscribers: Vec<(Vec<MidiMessageKind>, Subscriber)>,
+}
+
+impl MidiSubscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe<F: Fn(&MidiMessage) + Send + 'static>(
+        self,
+        kinds: &[MidiMessageKind],
+        callback: F,
+    ) -> Self {
+        self.subscribe_timed(kinds, move |_, m| callback(m))
+    }
+
+    /// Igual ao [MidiSubscriptions::subscribe], mas recebe tambem o tempo da mensagem
+    pub fn subscribe_timed<F: Fn(u64, &MidiMessage) + Send + 'static>(
+        mut self,
+        kinds: &[MidiMessageKind],
+        callback: F,
+    ) -> Self {
+        self.subscribers.push((kinds.to_vec(), Box::new(callback)));
+        self
+    }
+
+    /// Recebe apenas note on e note off, ja convertidos para [MidiWrapper]
+    pub fn on_notes<F: Fn(MidiWrapper) + Send + 'static>(self, callback: F) -> Self {
+        self.subscribe_timed(&MidiMessageKind::NOTES, move |timestamp, m| {
+            if let Some(wrapper) = MidiWrapper::from_message(m) {
+                callback(wrapper.with_timestamp(timestamp))
+            }
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+
+    pub fn dispatch(&self, timestamp: u64, message: &MidiMessage) {
+        let kind = message.kind();
+        for (kinds, callback) in &self.subscribers {
+            if kinds.contains(&kind) {
+                callback(timestamp, message);
+            }
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use crate::midi_wrapper::MidiWrapper;
+use crate::note::Note;
+#[cfg(feature = "verbose")]
+use crate::LOG_TAG;
+use midir::{MidiOutput, MidiOutputConnection};
+#[cfg(feature = "verbose")]
+use paris::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const THRU_CLIENT_NAME: &str = "InspiraSomMidiThru";
+const THRU_PORT_NAME: &str = "InspireThruPort";
+const MAX_VELOCITY: u8 = 127;
+const MAX_KEY: i16 = 127;
+const STATUS_MASK: u8 = 0xF0;
+const CHANNEL_MASK: u8 = 0x0F;
+
+/// Curva aplicada na velocity (forca do sopro) antes de enviar a nota para a saida
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum VelocityCurve {
+    /// Envia a velocity como ela chegou
+    #[default]
+    Linear,
+    /// Sopros fracos ficam mais altos
+    Soft,
+    /// Precisa soprar mais forte para soar alto
+    Hard,
+    /// Ignora o sopro e sempre usa o mesmo valor
+    Fixed(u8),
+}
+
+impl VelocityCurve {
+    pub fn apply(&self, velocity: u8) -> u8 {
+        let v = velocity.min(MAX_VELOCITY);
+        let normalized = v as f32 / MAX_VELOCITY as f32;
+        let curved = match self {
+            VelocityCurve::Linear => return v,
+            VelocityCurve::Fixed(f) => return (*f).min(MAX_VELOCITY),
+            VelocityCurve::Soft => normalized.sqrt(),
+            VelocityCurve::Hard => normalized * normalized,
+        };
+        let out = (curved * MAX_VELOCITY as f32).round() as u8;
+        if v > 0 {
+            out.max(1)
+        } else {
+            0
+        }
+    }
+}
+
+/// Configuracao do midi thru, `channel` é o canal de saida (0 a 15)
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct ThruConfig {
+    pub transpose: i8,
+    pub velocity_curve: VelocityCurve,
+    pub channel: u8,
+}
+
+impl ThruConfig {
+    /// Retorna a nota transposta ou [None] caso ela saia do intervalo midi
+    pub fn transpose_key(&self, key: u8) -> Option<u8> {
+        let k = key as i16 + self.transpose as i16;
+        if (0..=MAX_KEY).contains(&k) {
+            Some(k as u8)
+        } else {
+            None
+        }
+    }
+}
+
+/// Repassa as notas tocadas no dispositivo para uma porta de saida midi,
+/// para que o paciente consiga ouvir o que esta tocando.
+pub struct MidiThru {
+    pub port_name: String,
+    connection: MidiOutputConnection,
+    config: ThruConfig,
+    /// nota recebida -> (canal, nota enviada), para desligar a nota certa mesmo
+    /// que a configuracao mude com a nota pressionada
+    on_notes: HashMap<u8, (u8, u8)>,
+}
+
+impl MidiThru {
+    pub fn config(&self) -> ThruConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: ThruConfig) -> ArduinoCommResult<()> {
+        if config.channel > CHANNEL_MASK {
+            return Err(ArduinoCommunicationError::InvalidThruConfig(format!(
+                "channel {} must be between 0 and 15",
+                config.channel
+            )));
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    pub fn forward(&mut self, wrapper: &MidiWrapper) {
+        let key = wrapper.note.byte;
+        let status = wrapper.state & STATUS_MASK;
+        if status == Note::STATE_ON && wrapper.air_strength > 0 {
+            if let Some(previous) = self.on_notes.remove(&key) {
+                self.note_off(previous);
+            }
+            let Some(out_key) = self.config.transpose_key(key) else {
+                return;
+            };
+            let channel = self.config.channel;
+            let velocity = self.config.velocity_curve.apply(wrapper.air_strength);
+            self.send(&[Note::STATE_ON | channel, out_key, velocity]);
+            self.on_notes.insert(key, (channel, out_key));
+        } else if status == Note::STATE_ON || status == Note::STATE_OFF {
+            if let Some(sent) = self.on_notes.remove(&key) {
+                self.note_off(sent);
+            }
+        }
+    }
+
+    /// Desliga todas as notas que ainda estao soando
+    pub fn all_notes_off(&mut self) {
+        let notes: Vec<(u8, u8)> = self.on_notes.drain().map(|(_, n)| n).collect();
+        for n in notes {
+            self.note_off(n);
+        }
+    }
+
+    fn note_off(&mut self, (channel, key): (u8, u8)) {
+        self.send(&[Note::STATE_OFF | channel, key, 0]);
+    }
+
+    fn send(&mut self, message: &[u8]) {
+        if let Err(_e) = self.connection.send(message) {
+            #[cfg(feature = "verbose")]
+            {
+                warn!(
+                    "[{}] Could not forward message to thru port: {}",
+                    LOG_TAG, _e
+                );
+            }
+        }
+    }
+}
+
+impl Drop for MidiThru {
+    fn drop(&mut self) {
+        self.all_notes_off();
+    }
+}
+
+pub fn connect_thru_to_port_with_name(
+    name: &str,
+    config: ThruConfig,
+) -> ArduinoCommResult<MidiThru> {
+    let midi_out = MidiOutput::new(THRU_CLIENT_NAME)?;
+    for p in midi_out.ports() {
+        let Ok(n) = midi_out.port_name(&p) else {
+            continue;
+        };
+        if n.contains(name) {
+            #[cfg(feature = "verbose")]
+            {
+                info!("[{}] Forwarding input to port {}", LOG_TAG, n);
+            }
+            let connection = midi_out
+                .connect(&p, THRU_PORT_NAME)
+                .map_err(move |e| ArduinoCommunicationError::ThruConnectionError(e.to_string()))?;
+            let mut thru = MidiThru {
+                port_name: n,
+                connection,
+                config: ThruConfig::default(),
+                on_notes: HashMap::new(),
+            };
+            thru.set_config(config)?;
+            return Ok(thru);
+        }
+    }
+    Err(ArduinoCommunicationError::ThruPortNotFound(
+        name.to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn velocity_curves() {
+        assert_eq!(VelocityCurve::Linear.apply(64), 64);
+        assert_eq!(VelocityCurve::Fixed(100).apply(3), 100);
+        assert!(VelocityCurve::Soft.apply(32) > 32);
+        assert!(VelocityCurve::Hard.apply(32) < 32);
+        assert_eq!(VelocityCurve::Hard.apply(1), 1);
+        assert_eq!(VelocityCurve::Soft.apply(0), 0);
+        assert_eq!(VelocityCurve::Soft.apply(127), 127);
+    }
+
+    #[test]
+    fn transposition_stays_in_range() {
+        let config = ThruConfig {
+            transpose: 12,
+            ..Default::default()
+        };
+        assert_eq!(config.transpose_key(60), Some(72));
+        assert_eq!(config.transpose_key(120), None);
+    }
+}
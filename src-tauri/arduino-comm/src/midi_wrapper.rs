@@ -1,20 +1,58 @@
-use crate::note::NoteWrapper;
+use crate::midi_message::MidiMessage;
+use crate::note::{Note, NoteWrapper};
 use std::fmt::Display;
+use std::time::Duration;
 
 #[derive(Clone, Debug)]
 pub struct MidiWrapper {
     pub air_strength: u8,
     pub note: NoteWrapper,
+    /// [Note::STATE_ON] ou [Note::STATE_OFF], note on com velocity 0 vira note off
     pub state: u8,
+    pub channel: u8,
+    /// Tempo em microssegundos dado pela fonte quando a mensagem chegou,
+    /// a origem depende da fonte, entao so serve para comparar mensagens da mesma conexao
+    pub timestamp: u64,
 }
 
 impl MidiWrapper {
-    pub fn new_from_bytes(state: u8, byte: u8, velocity: u8) -> Self {
-        MidiWrapper {
+    /// Converte uma mensagem de nota, outras mensagens retornam [None]
+    pub fn from_message(message: &MidiMessage) -> Option<Self> {
+        let (channel, key, velocity) = match message {
+            MidiMessage::NoteOn {
+                channel,
+                key,
+                velocity,
+            }
+            | MidiMessage::NoteOff {
+                channel,
+                key,
+                velocity,
+            } => (*channel, *key, *velocity),
+            _ => return None,
+        };
+        let state = if message.is_note_on() {
+            Note::STATE_ON
+        } else {
+            Note::STATE_OFF
+        };
+        Some(MidiWrapper {
             air_strength: velocity,
-            note: NoteWrapper::new(byte).unwrap_or_else(move |_| Default::default()),
+            note: NoteWrapper::new(key),
             state,
-        }
+            channel,
+            timestamp: 0,
+        })
+    }
+
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// [MidiWrapper::timestamp] como [Duration], monotonico dentro da mesma conexao
+    pub fn time(&self) -> Duration {
+        Duration::from_micros(self.timestamp)
     }
 }
 
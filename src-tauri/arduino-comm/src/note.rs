@@ -35,7 +35,7 @@ pub enum Note {
 impl Note {
     pub const STATE_OFF: u8 = 128;
     pub const STATE_ON: u8 = 144;
-    const MAX_VELOCITY: u8 = 127;
+    pub const MAX_VELOCITY: u8 = 127;
 
     pub fn ordinal(&self) -> u8 {
         let mut iter = Note::iter().filter(move |x1| !x1.is_bmol()).rev();
@@ -64,12 +64,10 @@ impl Note {
             Ok(note_iter.get(i).unwrap())
         }
     }
+    /// Velocity de 0 a 100, para a intensidade relativa ao paciente aplique antes a
+    /// [crate::breath_calibration::BreathCalibration]
     pub fn velocity_percentage(velocity: u8) -> f32 {
-        match velocity {
-            0 => 0f32,
-            Self::MAX_VELOCITY => 100f32,
-            _ => velocity as f32 / Self::MAX_VELOCITY as f32,
-        }
+        velocity.min(Self::MAX_VELOCITY) as f32 * 100f32 / Self::MAX_VELOCITY as f32
     }
     pub fn is_bmol(&self) -> bool {
         let s: &str = self.into();
@@ -77,27 +75,21 @@ impl Note {
     }
 }
 
+/// Nota recebida do dispositivo, `note` é [None] quando o byte esta fora do [Note].
+///
+/// O nome e a posicao da nota dependem do instrumento, ver
+/// [crate::instrument_profile::InstrumentProfile].
 #[derive(Clone, Copy, Debug)]
 pub struct NoteWrapper {
-    pub note: Note,
+    pub note: Option<Note>,
     pub byte: u8,
 }
 
 impl NoteWrapper {
-    pub fn new(note: u8) -> ArduinoCommResult<Self> {
-        let n = Note::from_byte(note)?;
-        Ok(NoteWrapper {
-            note: n,
-            byte: note,
-        })
-    }
-}
-
-impl Default for NoteWrapper {
-    fn default() -> Self {
+    pub fn new(note: u8) -> Self {
         NoteWrapper {
-            note: Note::A3,
-            byte: 57,
+            note: Note::from_byte(note).ok(),
+            byte: note,
         }
     }
 }
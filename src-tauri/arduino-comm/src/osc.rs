@@ -0,0 +1,461 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use crate::input_source::{ConnectionHolder, InputSource, InputSourceKind, RawInputCallback};
+use crate::midi_wrapper::MidiWrapper;
+use crate::note::Note;
+#[cfg(feature = "verbose")]
+use crate::LOG_TAG;
+#[cfg(feature = "verbose")]
+use paris::{info, warn};
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Nome usado nas fontes OSC, tambem usado para escolher o perfil do dispositivo
+pub const OSC_DEVICE_NAME: &str = "InspiraSom OSC";
+pub const DEFAULT_OSC_INPUT_PORT: u16 = 9000;
+/// So aceita mensagens de programas do mesmo computador
+pub const DEFAULT_OSC_INPUT_ADDRESS: &str = "127.0.0.1";
+/// `(nota, velocity)`, velocity 0 desliga a nota
+pub const NOTE_ADDRESS: &str = "/inspirasom/note";
+/// `(pressao)`
+pub const PRESSURE_ADDRESS: &str = "/inspirasom/pressure";
+const PLAYER_ADDRESS: &str = "/inspirasom/player";
+const BUNDLE_TAG: &[u8] = b"#bundle\0";
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+const MAX_PACKET_SIZE: usize = 1536;
+const MAX_MIDI_VALUE: u8 = 127;
+const CHANNEL_PRESSURE: u8 = 0xD0;
+
+/// Argumento de uma mensagem OSC, so os tipos basicos sao suportados
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+}
+
+impl OscArg {
+    /// Valor midi (0 a 127) do argumento, inteiros ja sao midi e floats vao de 0.0 a 1.0
+    pub fn midi_value(&self) -> Option<u8> {
+        match self {
+            OscArg::Int(i) => Some((*i).clamp(0, MAX_MIDI_VALUE as i32) as u8),
+            OscArg::Float(f) => Some((f.clamp(0.0, 1.0) * MAX_MIDI_VALUE as f32).round() as u8),
+            OscArg::Str(_) => None,
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            OscArg::Int(_) => b'i',
+            OscArg::Float(_) => b'f',
+            OscArg::Str(_) => b's',
+        }
+    }
+}
+
+/// Mensagem OSC 1.0, `address` comeca com `/`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+impl OscMessage {
+    pub fn new(address: &str, args: Vec<OscArg>) -> Self {
+        Self {
+            address: address.to_string(),
+            args,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        write_string(&mut bytes, &self.address);
+        let mut tags = String::from(",");
+        tags.extend(self.args.iter().map(|a| a.tag() as char));
+        write_string(&mut bytes, &tags);
+        for arg in self.args.iter() {
+            match arg {
+                OscArg::Int(i) => bytes.extend_from_slice(&i.to_be_bytes()),
+                OscArg::Float(f) => bytes.extend_from_slice(&f.to_be_bytes()),
+                OscArg::Str(s) => write_string(&mut bytes, s),
+            }
+        }
+        bytes
+    }
+
+    /// Le um pacote UDP, bundles sao abertos e retornam todas as mensagens dentro deles
+    pub fn decode_packet(bytes: &[u8]) -> ArduinoCommResult<Vec<OscMessage>> {
+        if !bytes.starts_with(BUNDLE_TAG) {
+            return Ok(vec![Self::decode(bytes)?]);
+        }
+        // o time tag é ignorado, as mensagens sao entregues quando chegam
+        let mut reader = OscReader::new(&bytes[BUNDLE_TAG.len()..]);
+        reader.take(8)?;
+        let mut messages = vec![];
+        while !reader.is_empty() {
+            let size = reader.read_i32()?;
+            if size < 0 {
+                return Err(osc_packet_error("negative bundle element size"));
+            }
+            messages.extend(Self::decode_packet(reader.take(size as usize)?)?);
+        }
+        Ok(messages)
+    }
+
+    fn decode(bytes: &[u8]) -> ArduinoCommResult<Self> {
+        let mut reader = OscReader::new(bytes);
+        let address = reader.read_string()?;
+        if !address.starts_with('/') {
+            return Err(osc_packet_error(&format!("invalid address {}", address)));
+        }
+        let tags = reader.read_string()?;
+        let Some(tags) = tags.strip_prefix(',') else {
+            return Err(osc_packet_error("missing type tags"));
+        };
+        let mut args = vec![];
+        for tag in tags.chars() {
+            args.push(match tag {
+                'i' => OscArg::Int(reader.read_i32()?),
+                'f' => OscArg::Float(f32::from_bits(reader.read_i32()? as u32)),
+                's' => OscArg::Str(reader.read_string()?),
+                t => return Err(osc_packet_error(&format!("unsupported type tag {}", t))),
+            });
+        }
+        Ok(Self { address, args })
+    }
+
+    /// Bytes midi equivalentes das mensagens de entrada, outras mensagens sao ignoradas
+    pub fn to_midi(&self) -> Option<Vec<u8>> {
+        match (self.address.as_str(), self.args.as_slice()) {
+            (NOTE_ADDRESS, [key, velocity]) => {
+                let key = key.midi_value()?;
+                match velocity.midi_value()? {
+                    0 => Some(vec![Note::STATE_OFF, key, 0]),
+                    v => Some(vec![Note::STATE_ON, key, v]),
+                }
+            }
+            (PRESSURE_ADDRESS, [pressure]) => Some(vec![CHANNEL_PRESSURE, pressure.midi_value()?]),
+            _ => None,
+        }
+    }
+}
+
+fn write_string(bytes: &mut Vec<u8>, s: &str) {
+    bytes.extend_from_slice(s.as_bytes());
+    // sempre tem pelo menos um \0 e o tamanho fica multiplo de 4
+    let padding = 4 - s.len() % 4;
+    bytes.resize(bytes.len() + padding, 0);
+}
+
+struct OscReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> OscReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> ArduinoCommResult<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(osc_packet_error("packet is too short"));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn read_i32(&mut self) -> ArduinoCommResult<i32> {
+        let bytes = self.take(4)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_string(&mut self) -> ArduinoCommResult<String> {
+        let Some(end) = self.bytes.iter().position(|b| *b == 0) else {
+            return Err(osc_packet_error("unterminated string"));
+        };
+        let padded = (end / 4 + 1) * 4;
+        let bytes = self.take(padded)?;
+        String::from_utf8(bytes[..end].to_vec()).map_err(|e| osc_packet_error(&e.to_string()))
+    }
+}
+
+fn osc_packet_error(reason: &str) -> ArduinoCommunicationError {
+    ArduinoCommunicationError::InvalidOscPacket(reason.to_string())
+}
+
+fn osc_error(e: std::io::Error) -> ArduinoCommunicationError {
+    ArduinoCommunicationError::OscError(e.to_string())
+}
+
+/// Nome da fonte OSC que escuta na porta UDP
+pub fn osc_device_name(port: u16) -> String {
+    format!("{} (:{})", OSC_DEVICE_NAME, port)
+}
+
+/// Recebe notas e pressao de outros programas por OSC, como um app de celular ou
+/// um sensor do laboratorio, como se fosse um dispositivo midi
+pub struct OscInput {
+    socket: UdpSocket,
+}
+
+impl OscInput {
+    pub fn bind<A: ToSocketAddrs>(address: A) -> ArduinoCommResult<Self> {
+        let socket = UdpSocket::bind(address).map_err(osc_error)?;
+        socket
+            .set_read_timeout(Some(READ_TIMEOUT))
+            .map_err(osc_error)?;
+        #[cfg(feature = "verbose")]
+        {
+            info!(
+                "[{}] Listening for OSC on {:?}",
+                LOG_TAG,
+                socket.local_addr()
+            );
+        }
+        Ok(Self { socket })
+    }
+
+    pub fn local_addr(&self) -> ArduinoCommResult<SocketAddr> {
+        self.socket.local_addr().map_err(osc_error)
+    }
+}
+
+/// Para a leitura do socket quando é dropada
+struct OscListener {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for OscListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl InputSource for OscInput {
+    fn name(&self) -> String {
+        osc_device_name(self.local_addr().map(|a| a.port()).unwrap_or_default())
+    }
+
+    fn kind(&self) -> InputSourceKind {
+        InputSourceKind::Osc
+    }
+
+    fn start(
+        self: Box<Self>,
+        mut on_bytes: RawInputCallback,
+    ) -> ArduinoCommResult<ConnectionHolder> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let socket = self.socket;
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; MAX_PACKET_SIZE];
+            let started_at = Instant::now();
+            while !stopped.load(Ordering::Relaxed) {
+                let read = match socket.recv(&mut buffer) {
+                    Ok(n) => n,
+                    Err(e)
+                        if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut =>
+                    {
+                        continue;
+                    }
+                    Err(_e) => {
+                        #[cfg(feature = "verbose")]
+                        {
+                            warn!("[{}] OSC socket closed: {}", LOG_TAG, _e);
+                        }
+                        break;
+                    }
+                };
+                let timestamp = started_at.elapsed().as_micros() as u64;
+                let messages = match OscMessage::decode_packet(&buffer[..read]) {
+                    Ok(m) => m,
+                    Err(_e) => {
+                        #[cfg(feature = "verbose")]
+                        {
+                            warn!("[{}] Ignoring OSC packet: {}", LOG_TAG, _e);
+                        }
+                        continue;
+                    }
+                };
+                for midi in messages.iter().filter_map(OscMessage::to_midi) {
+                    on_bytes(timestamp, &midi);
+                }
+            }
+        });
+        Ok(ConnectionHolder::new(OscListener { stop }))
+    }
+}
+
+/// Envia as notas e a pressao de cada jogador por OSC, para visualizadores ou
+/// programas de pesquisa que gravam a sessao.
+///
+/// Os enderecos sao `/inspirasom/player/{jogador}/note` com `(nota, velocity)` e
+/// `/inspirasom/player/{jogador}/pressure` com `(pressao)`.
+pub struct OscOutput {
+    socket: UdpSocket,
+    target: SocketAddr,
+}
+
+impl OscOutput {
+    /// `target` pode ser um endereco de broadcast da rede local
+    pub fn connect(target: &str) -> ArduinoCommResult<Self> {
+        let Some(target) = target.to_socket_addrs().map_err(osc_error)?.next() else {
+            return Err(ArduinoCommunicationError::OscError(format!(
+                "could not resolve {}",
+                target
+            )));
+        };
+        let local: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(local).map_err(osc_error)?;
+        socket.set_broadcast(true).map_err(osc_error)?;
+        #[cfg(feature = "verbose")]
+        {
+            info!("[{}] Sending OSC to {}", LOG_TAG, target);
+        }
+        Ok(Self { socket, target })
+    }
+
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    pub fn send_note(&self, player: u8, wrapper: &MidiWrapper) {
+        let velocity = if wrapper.state == Note::STATE_ON {
+            wrapper.air_strength
+        } else {
+            0
+        };
+        self.send(&OscMessage::new(
+            &player_address(player, "note"),
+            vec![
+                OscArg::Int(wrapper.note.byte as i32),
+                OscArg::Int(velocity as i32),
+            ],
+        ));
+    }
+
+    pub fn send_pressure(&self, player: u8, pressure: u8) {
+        self.send(&OscMessage::new(
+            &player_address(player, "pressure"),
+            vec![OscArg::Int(pressure as i32)],
+        ));
+    }
+
+    fn send(&self, message: &OscMessage) {
+        if let Err(_e) = self.socket.send_to(&message.encode(), self.target) {
+            #[cfg(feature = "verbose")]
+            {
+                warn!("[{}] Could not send OSC message: {}", LOG_TAG, _e);
+            }
+        }
+    }
+}
+
+fn player_address(player: u8, kind: &str) -> String {
+    format!("{}/{}/{}", PLAYER_ADDRESS, player, kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_source::start_listening;
+    use crate::midi_message::{MidiMessage, MidiMessageKind};
+    use crate::midi_subscriptions::MidiSubscriptions;
+    use std::sync::mpsc;
+
+    #[test]
+    fn encodes_and_decodes_messages_and_bundles() {
+        let message = OscMessage::new(
+            NOTE_ADDRESS,
+            vec![
+                OscArg::Int(60),
+                OscArg::Float(0.5),
+                OscArg::Str("ab".into()),
+            ],
+        );
+        let bytes = message.encode();
+        assert_eq!(bytes.len() % 4, 0);
+        assert_eq!(
+            OscMessage::decode_packet(&bytes).unwrap(),
+            vec![message.clone()]
+        );
+        assert_eq!(message.to_midi(), None);
+
+        let pressure = OscMessage::new(PRESSURE_ADDRESS, vec![OscArg::Float(1.0)]);
+        let mut bundle = BUNDLE_TAG.to_vec();
+        bundle.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+        for m in [&message, &pressure] {
+            let encoded = m.encode();
+            bundle.extend_from_slice(&(encoded.len() as i32).to_be_bytes());
+            bundle.extend_from_slice(&encoded);
+        }
+        let decoded = OscMessage::decode_packet(&bundle).unwrap();
+        assert_eq!(decoded, vec![message, pressure]);
+        assert_eq!(decoded[1].to_midi(), Some(vec![CHANNEL_PRESSURE, 127]));
+        assert_eq!(
+            OscMessage::new(NOTE_ADDRESS, vec![OscArg::Int(62), OscArg::Int(0)]).to_midi(),
+            Some(vec![Note::STATE_OFF, 62, 0])
+        );
+        assert!(OscMessage::decode_packet(&bytes[..bytes.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn udp_messages_reach_the_subscribers_and_output_is_sent() {
+        let input = OscInput::bind("127.0.0.1:0").unwrap();
+        let address = input.local_addr().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let subscriptions = MidiSubscriptions::new().subscribe(
+            &[MidiMessageKind::NoteOn, MidiMessageKind::ChannelPressure],
+            move |m| {
+                let _ = tx.send(m.clone());
+            },
+        );
+        let holder = start_listening(Box::new(input), subscriptions).unwrap();
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let note = OscMessage::new(NOTE_ADDRESS, vec![OscArg::Int(60), OscArg::Int(90)]);
+        let pressure = OscMessage::new(PRESSURE_ADDRESS, vec![OscArg::Int(40)]);
+        sender.send_to(&note.encode(), address).unwrap();
+        sender.send_to(&pressure.encode(), address).unwrap();
+        let timeout = Duration::from_secs(2);
+        assert!(matches!(
+            rx.recv_timeout(timeout).unwrap(),
+            MidiMessage::NoteOn {
+                key: 60,
+                velocity: 90,
+                ..
+            }
+        ));
+        assert!(matches!(
+            rx.recv_timeout(timeout).unwrap(),
+            MidiMessage::ChannelPressure { pressure: 40, .. }
+        ));
+        drop(holder);
+
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver.set_read_timeout(Some(timeout)).unwrap();
+        let output = OscOutput::connect(&receiver.local_addr().unwrap().to_string()).unwrap();
+        output.send_pressure(1, 64);
+        let mut buffer = [0u8; MAX_PACKET_SIZE];
+        let read = receiver.recv(&mut buffer).unwrap();
+        assert_eq!(
+            OscMessage::decode_packet(&buffer[..read]).unwrap(),
+            vec![OscMessage::new(
+                "/inspirasom/player/1/pressure",
+                vec![OscArg::Int(64)]
+            )]
+        );
+    }
+}
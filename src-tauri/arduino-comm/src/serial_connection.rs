@@ -0,0 +1,348 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use crate::input_source::{ConnectionHolder, InputSource, InputSourceKind, RawInputCallback};
+use crate::note::Note;
+#[cfg(feature = "verbose")]
+use crate::LOG_TAG;
+#[cfg(feature = "verbose")]
+use paris::{info, warn};
+use serialport::SerialPort;
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Nome usado nas portas seriais, tambem usado para escolher o perfil do dispositivo
+pub const SERIAL_DEVICE_NAME: &str = "InspiraSom Serial";
+pub const DEFAULT_BAUD_RATE: u32 = 115_200;
+/// Sem heartbeat nesse intervalo a conexao é considerada perdida
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(2);
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+const READ_BUFFER_SIZE: usize = 64;
+const CHANNEL_PRESSURE: u8 = 0xD0;
+
+const FRAME_SYNC: u8 = 0xA5;
+const FRAME_NOTE_ON: u8 = 0x01;
+const FRAME_NOTE_OFF: u8 = 0x02;
+const FRAME_PRESSURE: u8 = 0x03;
+const FRAME_HEARTBEAT: u8 = 0x04;
+const MAX_PAYLOAD_LENGTH: usize = 8;
+
+/// Mensagem do protocolo serial da placa.
+///
+/// Cada frame é `[0xA5, tipo, tamanho, payload..., checksum]`, o checksum é o xor
+/// do tipo, do tamanho e do payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerialFrame {
+    NoteOn {
+        key: u8,
+        velocity: u8,
+    },
+    NoteOff {
+        key: u8,
+    },
+    /// Pressao do sopro de 0 a 127
+    Pressure(u8),
+    Heartbeat,
+}
+
+impl SerialFrame {
+    pub fn encode(&self) -> Vec<u8> {
+        let (kind, payload) = match *self {
+            SerialFrame::NoteOn { key, velocity } => (FRAME_NOTE_ON, vec![key, velocity]),
+            SerialFrame::NoteOff { key } => (FRAME_NOTE_OFF, vec![key]),
+            SerialFrame::Pressure(p) => (FRAME_PRESSURE, vec![p]),
+            SerialFrame::Heartbeat => (FRAME_HEARTBEAT, vec![]),
+        };
+        let mut frame = vec![FRAME_SYNC, kind, payload.len() as u8];
+        frame.extend_from_slice(&payload);
+        frame.push(Self::checksum(kind, &payload));
+        frame
+    }
+
+    /// Converte o frame para os bytes midi equivalentes, heartbeats nao viram midi
+    pub fn to_midi(&self) -> Option<Vec<u8>> {
+        match *self {
+            SerialFrame::NoteOn { key, velocity } => {
+                Some(vec![Note::STATE_ON, key & 0x7F, velocity & 0x7F])
+            }
+            SerialFrame::NoteOff { key } => Some(vec![Note::STATE_OFF, key & 0x7F, 0]),
+            SerialFrame::Pressure(p) => Some(vec![CHANNEL_PRESSURE, p & 0x7F]),
+            SerialFrame::Heartbeat => None,
+        }
+    }
+
+    fn decode(kind: u8, payload: &[u8]) -> Option<Self> {
+        match (kind, payload) {
+            (FRAME_NOTE_ON, [key, velocity]) => Some(SerialFrame::NoteOn {
+                key: *key,
+                velocity: *velocity,
+            }),
+            (FRAME_NOTE_OFF, [key]) => Some(SerialFrame::NoteOff { key: *key }),
+            (FRAME_PRESSURE, [p]) => Some(SerialFrame::Pressure(*p)),
+            (FRAME_HEARTBEAT, []) => Some(SerialFrame::Heartbeat),
+            _ => None,
+        }
+    }
+
+    fn checksum(kind: u8, payload: &[u8]) -> u8 {
+        payload
+            .iter()
+            .fold(kind ^ payload.len() as u8, |acc, b| acc ^ b)
+    }
+}
+
+/// Monta os frames a partir dos bytes lidos da serial, descartando frames corrompidos
+#[derive(Debug, Default)]
+pub struct SerialFrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SerialFrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<SerialFrame> {
+        bytes.iter().filter_map(|b| self.push(*b)).collect()
+    }
+
+    pub fn push(&mut self, byte: u8) -> Option<SerialFrame> {
+        if self.buffer.is_empty() {
+            if byte == FRAME_SYNC {
+                self.buffer.push(byte);
+            }
+            return None;
+        }
+        self.buffer.push(byte);
+        if self.buffer.len() == 3 && self.buffer[2] as usize > MAX_PAYLOAD_LENGTH {
+            self.resync();
+            return None;
+        }
+        if self.buffer.len() < 4 || self.buffer.len() < 4 + self.buffer[2] as usize {
+            return None;
+        }
+        let kind = self.buffer[1];
+        let payload = &self.buffer[3..self.buffer.len() - 1];
+        let valid = SerialFrame::checksum(kind, payload) == byte;
+        let frame = if valid {
+            SerialFrame::decode(kind, payload)
+        } else {
+            None
+        };
+        if valid {
+            self.buffer.clear();
+        } else {
+            self.resync();
+        }
+        frame
+    }
+
+    /// Descarta o sync atual e recomeca a partir do proximo sync do buffer
+    fn resync(&mut self) {
+        let rest: Vec<u8> = self.buffer.drain(..).skip(1).collect();
+        for b in rest {
+            // um frame completo no resto so acontece com dados corrompidos, pode ser descartado
+            let _ = self.push(b);
+        }
+    }
+}
+
+/// Nome da fonte de entrada de uma porta serial
+pub fn serial_device_name(port_name: &str) -> String {
+    format!("{} ({})", SERIAL_DEVICE_NAME, port_name)
+}
+
+pub fn list_serial_ports() -> ArduinoCommResult<Vec<String>> {
+    let ports = serialport::available_ports().map_err(serial_error)?;
+    Ok(ports.into_iter().map(|p| p.port_name).collect())
+}
+
+/// Placa conectada direto pela USB serial, sem firmware midi
+pub struct SerialConnection {
+    pub port_name: String,
+    port: Box<dyn SerialPort>,
+}
+
+impl SerialConnection {
+    pub fn open(port_name: &str, baud_rate: u32) -> ArduinoCommResult<Self> {
+        let port = serialport::new(port_name, baud_rate)
+            .timeout(READ_TIMEOUT)
+            .open()
+            .map_err(serial_error)?;
+        #[cfg(feature = "verbose")]
+        {
+            info!("[{}] Opened serial port {}", LOG_TAG, port_name);
+        }
+        Ok(Self::from_port(port_name, port))
+    }
+
+    /// Usa uma porta ja aberta, ela precisa ter um timeout de leitura
+    pub fn from_port(port_name: &str, port: Box<dyn SerialPort>) -> Self {
+        Self {
+            port_name: port_name.to_string(),
+            port,
+        }
+    }
+}
+
+/// Para a leitura da serial quando é dropada
+struct SerialListener {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for SerialListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl InputSource for SerialConnection {
+    fn name(&self) -> String {
+        serial_device_name(&self.port_name)
+    }
+
+    fn kind(&self) -> InputSourceKind {
+        InputSourceKind::Serial
+    }
+
+    fn start(
+        self: Box<Self>,
+        mut on_bytes: RawInputCallback,
+    ) -> ArduinoCommResult<ConnectionHolder> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let alive = Arc::new(AtomicBool::new(true));
+        let stopped = stop.clone();
+        let connected = alive.clone();
+        let mut port = self.port;
+        std::thread::spawn(move || {
+            let mut decoder = SerialFrameDecoder::new();
+            let mut buffer = [0u8; READ_BUFFER_SIZE];
+            let started_at = Instant::now();
+            let mut last_heartbeat = Instant::now();
+            while !stopped.load(Ordering::Relaxed) {
+                let read = match port.read(&mut buffer) {
+                    Ok(n) => n,
+                    Err(e)
+                        if e.kind() == ErrorKind::TimedOut
+                            || e.kind() == ErrorKind::Interrupted =>
+                    {
+                        0
+                    }
+                    Err(_e) => {
+                        #[cfg(feature = "verbose")]
+                        {
+                            warn!("[{}] Serial port closed: {}", LOG_TAG, _e);
+                        }
+                        break;
+                    }
+                };
+                let timestamp = started_at.elapsed().as_micros() as u64;
+                for frame in decoder.feed(&buffer[..read]) {
+                    last_heartbeat = Instant::now();
+                    if let Some(midi) = frame.to_midi() {
+                        on_bytes(timestamp, &midi);
+                    }
+                }
+                if last_heartbeat.elapsed() > HEARTBEAT_TIMEOUT {
+                    #[cfg(feature = "verbose")]
+                    {
+                        warn!("[{}] Serial device stopped sending heartbeats", LOG_TAG);
+                    }
+                    break;
+                }
+            }
+            connected.store(false, Ordering::Relaxed);
+        });
+        Ok(ConnectionHolder::with_liveness(
+            SerialListener { stop },
+            alive,
+        ))
+    }
+}
+
+pub(crate) fn serial_error(e: serialport::Error) -> ArduinoCommunicationError {
+    ArduinoCommunicationError::SerialError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_source::start_listening;
+    use crate::midi_message::{MidiMessage, MidiMessageKind};
+    use crate::midi_subscriptions::MidiSubscriptions;
+    use crate::midi_wrapper::MidiWrapper;
+    use std::io::Write;
+    use std::sync::Mutex;
+
+    #[test]
+    fn decoder_skips_corrupted_frames() {
+        let mut decoder = SerialFrameDecoder::new();
+        let mut bytes = vec![0x00, 0x13];
+        let mut corrupted = SerialFrame::NoteOn {
+            key: 60,
+            velocity: 90,
+        }
+        .encode();
+        corrupted[4] ^= 0xFF;
+        bytes.extend(corrupted);
+        bytes.extend(SerialFrame::Pressure(42).encode());
+        bytes.extend(SerialFrame::Heartbeat.encode());
+        bytes.extend([FRAME_SYNC, FRAME_PRESSURE, 200]);
+        bytes.extend(SerialFrame::NoteOff { key: 60 }.encode());
+        assert_eq!(
+            decoder.feed(&bytes),
+            vec![
+                SerialFrame::Pressure(42),
+                SerialFrame::Heartbeat,
+                SerialFrame::NoteOff { key: 60 },
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_frames_from_a_pseudo_terminal() {
+        let (mut board, computer) = serialport::TTYPort::pair().unwrap();
+        let mut computer = computer;
+        computer.set_timeout(READ_TIMEOUT).unwrap();
+        let connection = SerialConnection::from_port("pty", Box::new(computer));
+        assert!(connection.name().starts_with(SERIAL_DEVICE_NAME));
+
+        let notes: Arc<Mutex<Vec<MidiWrapper>>> = Arc::new(Mutex::new(vec![]));
+        let pressure: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(vec![]));
+        let n = notes.clone();
+        let p = pressure.clone();
+        let subscriptions = MidiSubscriptions::new()
+            .on_notes(move |w| n.lock().unwrap().push(w))
+            .subscribe(&[MidiMessageKind::ChannelPressure], move |m| {
+                if let MidiMessage::ChannelPressure { pressure, .. } = m {
+                    p.lock().unwrap().push(*pressure);
+                }
+            });
+        let holder = start_listening(Box::new(connection), subscriptions).unwrap();
+
+        let mut bytes = SerialFrame::Heartbeat.encode();
+        bytes.extend(
+            SerialFrame::NoteOn {
+                key: 64,
+                velocity: 80,
+            }
+            .encode(),
+        );
+        bytes.extend(SerialFrame::Pressure(33).encode());
+        bytes.extend(SerialFrame::NoteOff { key: 64 }.encode());
+        board.write_all(&bytes).unwrap();
+
+        let started = Instant::now();
+        while notes.lock().unwrap().len() < 2 && started.elapsed() < Duration::from_secs(2) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let notes = notes.lock().unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].note.byte, 64);
+        assert_eq!(notes[0].air_strength, 80);
+        assert_eq!(notes[1].state, Note::STATE_OFF);
+        assert_eq!(*pressure.lock().unwrap(), vec![33]);
+        assert!(holder.is_alive());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::errors::{ArduinoCommResult, ArduinoCommunicationError};
+use crate::input_source::{ConnectionHolder, InputSource, InputSourceKind, RawInputCallback};
+use crate::note::Note;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub const VIRTUAL_DEVICE_NAME: &str = "InspiraSom Virtual Input";
+const CONTROL_CHANGE: u8 = 0xB0;
+const CHANNEL_PRESSURE: u8 = 0xD0;
+/// Bytes de dados midi so usam 7 bits
+const DATA_MASK: u8 = 0x7F;
+
+type SharedCallback = Arc<Mutex<Option<(Instant, RawInputCallback)>>>;
+
+/// Dispositivo de entrada de software, as mensagens sao enviadas pelo
+/// [VirtualInputSender] e entregues na mesma thread de quem enviou.
+pub struct VirtualInput {
+    name: String,
+    callback: SharedCallback,
+}
+
+/// Lado que envia as mensagens para o [VirtualInput], pode ser clonado a vontade
+#[derive(Clone)]
+pub struct VirtualInputSender {
+    callback: SharedCallback,
+}
+
+/// Cria um dispositivo virtual e o seu sender
+pub fn virtual_input(name: &str) -> (VirtualInput, VirtualInputSender) {
+    let callback: SharedCallback = Arc::new(Mutex::new(None));
+    (
+        VirtualInput {
+            name: name.to_string(),
+            callback: callback.clone(),
+        },
+        VirtualInputSender { callback },
+    )
+}
+
+/// Desconecta o sender quando a conexao é dropada
+struct VirtualConnection {
+    callback: SharedCallback,
+}
+
+impl Drop for VirtualConnection {
+    fn drop(&mut self) {
+        if let Ok(mut c) = self.callback.lock() {
+            c.take();
+        }
+    }
+}
+
+impl InputSource for VirtualInput {
+    fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    fn kind(&self) -> InputSourceKind {
+        InputSourceKind::Virtual
+    }
+
+    fn start(self: Box<Self>, on_bytes: RawInputCallback) -> ArduinoCommResult<ConnectionHolder> {
+        if let Ok(mut c) = self.callback.lock() {
+            *c = Some((Instant::now(), on_bytes));
+        } else {
+            return Err(ArduinoCommunicationError::PortError);
+        }
+        Ok(ConnectionHolder::new(VirtualConnection {
+            callback: self.callback,
+        }))
+    }
+}
+
+impl VirtualInputSender {
+    pub fn is_connected(&self) -> bool {
+        self.callback.lock().is_ok_and(|c| c.is_some())
+    }
+
+    /// Envia bytes midi crus, do mesmo jeito que um dispositivo real enviaria
+    pub fn send_bytes(&self, bytes: &[u8]) -> ArduinoCommResult<()> {
+        let Ok(mut c) = self.callback.lock() else {
+            return Err(ArduinoCommunicationError::PortError);
+        };
+        let Some((started_at, callback)) = c.as_mut() else {
+            return Err(ArduinoCommunicationError::NoDevicesConnected);
+        };
+        callback(started_at.elapsed().as_micros() as u64, bytes);
+        Ok(())
+    }
+
+    pub fn note_on(&self, key: u8, velocity: u8) -> ArduinoCommResult<()> {
+        self.send_bytes(&[Note::STATE_ON, key & DATA_MASK, velocity & DATA_MASK])
+    }
+
+    pub fn note_off(&self, key: u8) -> ArduinoCommResult<()> {
+        self.send_bytes(&[Note::STATE_OFF, key & DATA_MASK, 0])
+    }
+
+    pub fn control_change(&self, controller: u8, value: u8) -> ArduinoCommResult<()> {
+        self.send_bytes(&[CONTROL_CHANGE, controller & DATA_MASK, value & DATA_MASK])
+    }
+
+    pub fn channel_pressure(&self, pressure: u8) -> ArduinoCommResult<()> {
+        self.send_bytes(&[CHANNEL_PRESSURE, pressure & DATA_MASK])
+    }
+
+    /// Toca uma sequencia de notas `(nota, velocity, duracao)`, bloqueando a thread
+    /// ate a ultima nota terminar
+    pub fn play_notes(&self, notes: &[(u8, u8, Duration)]) -> ArduinoCommResult<()> {
+        for (key, velocity, duration) in notes {
+            self.note_on(*key, *velocity)?;
+            std::thread::sleep(*duration);
+            self.note_off(*key)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_source::start_listening;
+    use crate::midi_message::{MidiMessage, MidiMessageKind};
+    use crate::midi_subscriptions::MidiSubscriptions;
+    use crate::midi_wrapper::MidiWrapper;
+
+    #[test]
+    fn scripted_notes_reach_the_subscribers() {
+        let (input, sender) = virtual_input(VIRTUAL_DEVICE_NAME);
+        let notes: Arc<Mutex<Vec<MidiWrapper>>> = Arc::new(Mutex::new(vec![]));
+        let pressure: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(vec![]));
+        let n = notes.clone();
+        let p = pressure.clone();
+        let subscriptions = MidiSubscriptions::new()
+            .on_notes(move |w| n.lock().unwrap().push(w))
+            .subscribe(&[MidiMessageKind::ControlChange], move |m| {
+                if let MidiMessage::ControlChange { value, .. } = m {
+                    p.lock().unwrap().push(*value);
+                }
+            });
+        assert!(sender.note_on(60, 100).is_err());
+
+        let holder = start_listening(Box::new(input), subscriptions).unwrap();
+        sender
+            .play_notes(&[(60, 100, Duration::ZERO), (62, 40, Duration::ZERO)])
+            .unwrap();
+        sender.control_change(2, 77).unwrap();
+
+        let notes = notes.lock().unwrap();
+        assert_eq!(notes.len(), 4);
+        assert_eq!(notes[0].note.byte, 60);
+        assert_eq!(notes[0].state, Note::STATE_ON);
+        assert_eq!(notes[1].state, Note::STATE_OFF);
+        assert_eq!(notes[2].air_strength, 40);
+        assert_eq!(*pressure.lock().unwrap(), vec![77]);
+
+        drop(holder);
+        assert!(!sender.is_connected());
+        assert!(sender.note_on(60, 100).is_err());
+    }
+}
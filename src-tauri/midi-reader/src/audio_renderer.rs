@@ -0,0 +1,324 @@
+use crate::errors::{MidiReaderError, MidiReaderResult};
+use crate::midi_file::create_sheet_and_ticker;
+use anyhow::anyhow;
+use midly::MidiMessage;
+use nodi::timers::Ticker;
+use nodi::{Connection, MidiEvent, Player, Sheet, Timer};
+use std::cell::Cell;
+use std::f32::consts::TAU;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Duration;
+
+pub const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+const MAX_VELOCITY: f32 = 127f32;
+const BITS_PER_SAMPLE: u16 = 16;
+const HEADER_SIZE: u32 = 36;
+
+/// Uma nota ja com o tempo resolvido, pronta para ser sintetizada
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderNote {
+    pub start: Duration,
+    pub duration: Duration,
+    pub key: u8,
+    pub velocity: u8,
+}
+
+/// Um evento de entrada gravado, `at` é o tempo desde o inicio da gravacao
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputEvent {
+    pub at: Duration,
+    pub on: bool,
+    pub key: u8,
+    pub velocity: u8,
+}
+
+/// Sintetizador simples que imita uma ocarina: uma senoide com um pouco de
+/// harmonico mais um ruido de sopro, ambos controlados pela velocity da nota.
+#[derive(Debug, Clone, Copy)]
+pub struct OcarinaSynth {
+    pub sample_rate: u32,
+    pub attack: Duration,
+    pub release: Duration,
+    /// Quantidade de ruido de sopro, de 0 a 1
+    pub breath_noise: f32,
+    /// Quantidade do segundo harmonico, de 0 a 1
+    pub harmonic: f32,
+}
+
+impl Default for OcarinaSynth {
+    fn default() -> Self {
+        Self {
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            attack: Duration::from_millis(30),
+            release: Duration::from_millis(80),
+            breath_noise: 0.12,
+            harmonic: 0.08,
+        }
+    }
+}
+
+impl OcarinaSynth {
+    /// Sintetiza todas as notas em um unico canal de audio
+    pub fn render(&self, notes: &[RenderNote]) -> Vec<i16> {
+        let rate = self.sample_rate as f32;
+        let total = notes
+            .iter()
+            .map(|n| n.start + n.duration + self.release)
+            .max()
+            .unwrap_or(Duration::ZERO);
+        let mut buffer = vec![0f32; (total.as_secs_f32() * rate).ceil() as usize];
+        let attack = (self.attack.as_secs_f32() * rate).max(1f32);
+        let release = (self.release.as_secs_f32() * rate).max(1f32);
+        let mut noise = NoiseGenerator::new(0x1234_5678);
+        for note in notes {
+            let first = (note.start.as_secs_f32() * rate) as usize;
+            let held = note.duration.as_secs_f32() * rate;
+            let length = (held + release) as usize;
+            let frequency = Self::key_frequency(note.key);
+            let strength = note.velocity.min(127) as f32 / MAX_VELOCITY;
+            for i in 0..length {
+                let Some(sample) = buffer.get_mut(first + i) else {
+                    break;
+                };
+                let t = i as f32;
+                let envelope = if t < attack {
+                    t / attack
+                } else if t < held {
+                    1f32
+                } else {
+                    (1f32 - (t - held) / release).max(0f32)
+                };
+                let phase = TAU * frequency * t / rate;
+                let tone = phase.sin() + self.harmonic * (2f32 * phase).sin();
+                let breath = self.breath_noise * strength * noise.next();
+                *sample += envelope * strength * (tone + breath);
+            }
+        }
+        let peak = buffer.iter().fold(0f32, |p, s| p.max(s.abs()));
+        let gain = if peak > 1f32 { 1f32 / peak } else { 1f32 };
+        buffer
+            .into_iter()
+            .map(|s| (s * gain * i16::MAX as f32) as i16)
+            .collect()
+    }
+
+    #[inline]
+    fn key_frequency(key: u8) -> f32 {
+        440f32 * 2f32.powf((key as f32 - 69f32) / 12f32)
+    }
+}
+
+/// Ruido branco passado por um filtro passa baixa, para soar como sopro
+struct NoiseGenerator {
+    state: u32,
+    last: f32,
+}
+
+impl NoiseGenerator {
+    fn new(seed: u32) -> Self {
+        Self {
+            state: seed,
+            last: 0f32,
+        }
+    }
+
+    fn next(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        let white = self.state as f32 / u32::MAX as f32 * 2f32 - 1f32;
+        self.last += 0.2 * (white - self.last);
+        self.last
+    }
+}
+
+/// Escreve as amostras em um arquivo wav PCM 16 bits mono
+pub fn write_wav<W: Write>(out: &mut W, samples: &[i16], sample_rate: u32) -> std::io::Result<()> {
+    let block_align = BITS_PER_SAMPLE / 8;
+    let data_size = samples.len() as u32 * block_align as u32;
+    out.write_all(b"RIFF")?;
+    out.write_all(&(HEADER_SIZE + data_size).to_le_bytes())?;
+    out.write_all(b"WAVE")?;
+    out.write_all(b"fmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?;
+    out.write_all(&sample_rate.to_le_bytes())?;
+    out.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+    out.write_all(&block_align.to_le_bytes())?;
+    out.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+    out.write_all(b"data")?;
+    out.write_all(&data_size.to_le_bytes())?;
+    for s in samples {
+        out.write_all(&s.to_le_bytes())?;
+    }
+    out.flush()
+}
+
+/// Transforma os eventos de entrada gravados em notas, notas que nunca foram
+/// desligadas terminam no ultimo evento recebido.
+pub fn notes_from_events(events: &[InputEvent]) -> Vec<RenderNote> {
+    let mut notes: Vec<RenderNote> = vec![];
+    let mut open: Vec<RenderNote> = vec![];
+    for e in events {
+        if let Some(p) = open.iter().position(|n| n.key == e.key) {
+            let mut n = open.remove(p);
+            n.duration = e.at.saturating_sub(n.start);
+            notes.push(n);
+        }
+        if e.on && e.velocity > 0 {
+            open.push(RenderNote {
+                start: e.at,
+                duration: Duration::ZERO,
+                key: e.key,
+                velocity: e.velocity,
+            });
+        }
+    }
+    let end = events.last().map(|e| e.at).unwrap_or(Duration::ZERO);
+    for mut n in open {
+        n.duration = end.saturating_sub(n.start);
+        notes.push(n);
+    }
+    notes
+}
+
+/// Percorre a partitura sem dormir, apenas somando o tempo de cada evento
+pub fn notes_from_sheet(sheet: &Sheet, ticker: Ticker) -> Vec<RenderNote> {
+    let clock = Cell::new(Duration::ZERO);
+    let mut events: Vec<InputEvent> = vec![];
+    let timer = RenderTimer {
+        clock: &clock,
+        ticker,
+    };
+    {
+        let conn = RenderConn {
+            clock: &clock,
+            events: &mut events,
+        };
+        Player::new(timer, conn).play(sheet);
+    }
+    notes_from_events(&events)
+}
+
+pub fn render_notes_to_wav(notes: &[RenderNote], output: &str) -> MidiReaderResult<()> {
+    let synth = OcarinaSynth::default();
+    let samples = synth.render(notes);
+    let file = File::create(output).map_err(move |e| MidiReaderError::RenderError(anyhow!(e)))?;
+    let mut writer = BufWriter::new(file);
+    write_wav(&mut writer, &samples, synth.sample_rate)
+        .map_err(move |e| MidiReaderError::RenderError(anyhow!(e)))
+}
+
+pub fn render_sheet_to_wav(sheet: &Sheet, ticker: Ticker, output: &str) -> MidiReaderResult<()> {
+    render_notes_to_wav(&notes_from_sheet(sheet, ticker), output)
+}
+
+pub fn render_midi_bytes_to_wav(bytes: Vec<u8>, output: &str) -> MidiReaderResult<()> {
+    let (sheet, ticker) = create_sheet_and_ticker(bytes)?;
+    render_sheet_to_wav(&sheet, ticker, output)
+}
+
+pub fn render_events_to_wav(events: &[InputEvent], output: &str) -> MidiReaderResult<()> {
+    render_notes_to_wav(&notes_from_events(events), output)
+}
+
+struct RenderTimer<'a> {
+    clock: &'a Cell<Duration>,
+    ticker: Ticker,
+}
+
+impl<'a> Timer for RenderTimer<'a> {
+    fn sleep_duration(&mut self, n_ticks: u32) -> Duration {
+        self.ticker.sleep_duration(n_ticks)
+    }
+
+    fn change_tempo(&mut self, tempo: u32) {
+        self.ticker.change_tempo(tempo)
+    }
+
+    fn sleep(&mut self, n_ticks: u32) {
+        let dur = self.sleep_duration(n_ticks);
+        self.clock.set(self.clock.get() + dur);
+    }
+}
+
+struct RenderConn<'a> {
+    clock: &'a Cell<Duration>,
+    events: &'a mut Vec<InputEvent>,
+}
+
+impl<'a> Connection for RenderConn<'a> {
+    fn play(&mut self, event: MidiEvent) -> bool {
+        let (on, key, vel) = match event.message {
+            MidiMessage::NoteOn { key, vel } => (true, key, vel),
+            MidiMessage::NoteOff { key, vel } => (false, key, vel),
+            _ => return true,
+        };
+        self.events.push(InputEvent {
+            at: self.clock.get(),
+            on,
+            key: key.into(),
+            velocity: vel.into(),
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(start_ms: u64, duration_ms: u64, key: u8, velocity: u8) -> RenderNote {
+        RenderNote {
+            start: Duration::from_millis(start_ms),
+            duration: Duration::from_millis(duration_ms),
+            key,
+            velocity,
+        }
+    }
+
+    fn event(at_ms: u64, on: bool, key: u8, velocity: u8) -> InputEvent {
+        InputEvent {
+            at: Duration::from_millis(at_ms),
+            on,
+            key,
+            velocity,
+        }
+    }
+
+    #[test]
+    fn events_are_paired_into_notes() {
+        let events = [
+            event(0, true, 60, 100),
+            event(250, false, 60, 0),
+            event(300, true, 62, 80),
+            event(400, true, 62, 0),
+        ];
+        let notes = notes_from_events(&events);
+        assert_eq!(notes, vec![note(0, 250, 60, 100), note(300, 100, 62, 80)]);
+    }
+
+    #[test]
+    fn renders_a_valid_wav() {
+        let synth = OcarinaSynth::default();
+        let samples = synth.render(&[note(0, 500, 69, 127), note(250, 500, 72, 40)]);
+        let expected =
+            (Duration::from_millis(750) + synth.release).as_secs_f32() * synth.sample_rate as f32;
+        assert_eq!(samples.len(), expected.ceil() as usize);
+        assert!(samples.iter().any(|s| *s != 0));
+
+        let mut wav: Vec<u8> = vec![];
+        write_wav(&mut wav, &samples, synth.sample_rate).unwrap();
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(wav.len(), 44 + samples.len() * 2);
+    }
+
+    #[test]
+    fn silent_velocity_renders_silence() {
+        let samples = OcarinaSynth::default().render(&[note(0, 100, 60, 0)]);
+        assert!(samples.iter().all(|s| *s == 0));
+    }
+}
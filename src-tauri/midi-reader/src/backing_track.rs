@@ -0,0 +1,363 @@
+use crate::errors::{MidiReaderError, MidiReaderResult};
+#[cfg(feature = "verbose")]
+use crate::LOG_TAG;
+use anyhow::anyhow;
+use midir::{MidiOutput, MidiOutputConnection};
+use midly::live::LiveEvent;
+use midly::num::{u4, u7};
+use midly::{MidiMessage, Smf, Track, TrackEventKind};
+use nodi::MidiEvent;
+#[cfg(feature = "verbose")]
+use paris::{info, warn};
+use std::collections::HashSet;
+use utils::mutable_arc::MutableArc;
+
+const OUTPUT_CLIENT_NAME: &str = "InspiraSomMidiOut";
+const OUTPUT_PORT_NAME: &str = "InspireBackingTrack";
+const ALL_NOTES_OFF_CONTROLLER: u8 = 123;
+/// Um arquivo midi guarda a quantidade de trilhas em 16 bits
+pub const MAX_TRACK_COUNT: usize = u16::MAX as usize;
+
+pub type SharedMixer = MutableArc<TrackMixer>;
+
+pub fn list_output_ports() -> MidiReaderResult<Vec<String>> {
+    let midi_out = MidiOutput::new(OUTPUT_CLIENT_NAME)
+        .map_err(|e| MidiReaderError::MidiOutputError(anyhow!(e)))?;
+    let ports = midi_out
+        .ports()
+        .iter()
+        .filter_map(|p| midi_out.port_name(p).ok())
+        .collect();
+    Ok(ports)
+}
+
+pub fn connect_to_output_port(name: &str) -> MidiReaderResult<MidiOutputConnection> {
+    let midi_out = MidiOutput::new(OUTPUT_CLIENT_NAME)
+        .map_err(|e| MidiReaderError::MidiOutputError(anyhow!(e)))?;
+    let ports = midi_out.ports();
+    if ports.is_empty() {
+        return Err(MidiReaderError::NoPortsFound);
+    }
+    for p in ports {
+        let Ok(n) = midi_out.port_name(&p) else {
+            continue;
+        };
+        if n.contains(name) {
+            #[cfg(feature = "verbose")]
+            {
+                info!("[{}] Selected output port {}", LOG_TAG, n);
+            }
+            return midi_out
+                .connect(&p, OUTPUT_PORT_NAME)
+                .map_err(|e| MidiReaderError::MidiOutputError(anyhow!(e.to_string())));
+        }
+    }
+    Err(MidiReaderError::OutputPortNotFound(name.to_string()))
+}
+
+/// Define quais trilhas pertencem ao paciente e quais sao acompanhamento,
+/// as trilhas sao identificadas pelo index, como em [EditableNote::track].
+///
+/// Os eventos tocados so carregam o canal midi, entao cada canal é roteado pelas
+/// trilhas que usam ele na musica carregada: se uma trilha do paciente usa o canal
+/// ele vai para a partitura, senao ele toca se alguma trilha que usa ele puder ser ouvida.
+///
+/// Se nenhuma trilha do paciente for definida todas as trilhas vao para a partitura,
+/// como era antes de existir o acompanhamento.
+///
+/// [EditableNote::track]: crate::song_editor::EditableNote::track
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackMixer {
+    player_tracks: HashSet<usize>,
+    muted: HashSet<usize>,
+    solo: HashSet<usize>,
+    /// Canais usados por cada trilha da musica carregada
+    track_channels: Vec<HashSet<u8>>,
+}
+
+impl TrackMixer {
+    /// Carrega os canais usados por cada trilha da musica que vai ser tocada
+    pub fn load_midi_bytes(&mut self, bytes: &[u8]) -> MidiReaderResult<()> {
+        let smf = Smf::parse(bytes).map_err(move |e| MidiReaderError::InvalidMidiFile(anyhow!(e)))?;
+        self.load_tracks(&smf.tracks);
+        Ok(())
+    }
+
+    fn load_tracks(&mut self, tracks: &[Track]) {
+        self.track_channels = tracks
+            .iter()
+            .map(|track| {
+                track
+                    .iter()
+                    .filter_map(|event| match event.kind {
+                        TrackEventKind::Midi { channel, .. } => Some(channel.as_int()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    pub fn set_player_tracks(&mut self, tracks: &[usize]) -> MidiReaderResult<()> {
+        for t in tracks {
+            Self::check_track(*t)?;
+        }
+        self.player_tracks = tracks.iter().copied().collect();
+        Ok(())
+    }
+
+    pub fn set_muted(&mut self, track: usize, muted: bool) -> MidiReaderResult<()> {
+        Self::check_track(track)?;
+        Self::toggle(&mut self.muted, track, muted);
+        Ok(())
+    }
+
+    pub fn set_solo(&mut self, track: usize, solo: bool) -> MidiReaderResult<()> {
+        Self::check_track(track)?;
+        Self::toggle(&mut self.solo, track, solo);
+        Ok(())
+    }
+
+    pub fn player_tracks(&self) -> Vec<usize> {
+        Self::sorted(&self.player_tracks)
+    }
+
+    pub fn muted_tracks(&self) -> Vec<usize> {
+        Self::sorted(&self.muted)
+    }
+
+    pub fn solo_tracks(&self) -> Vec<usize> {
+        Self::sorted(&self.solo)
+    }
+
+    /// Se as notas desta trilha devem ir para a partitura do paciente
+    pub fn is_player_track(&self, track: usize) -> bool {
+        self.player_tracks.is_empty() || self.player_tracks.contains(&track)
+    }
+
+    /// Se as notas desta trilha devem ser tocadas na saida de acompanhamento
+    pub fn is_track_audible(&self, track: usize) -> bool {
+        if self.is_player_track(track) {
+            false
+        } else if !self.solo.is_empty() {
+            self.solo.contains(&track)
+        } else {
+            !self.muted.contains(&track)
+        }
+    }
+
+    /// Se os eventos deste canal devem ir para a partitura do paciente
+    pub fn is_player_channel(&self, channel: u8) -> bool {
+        self.player_tracks.is_empty()
+            || self
+                .tracks_with_channel(channel)
+                .any(|t| self.player_tracks.contains(&t))
+    }
+
+    /// Se os eventos deste canal devem ser tocados na saida de acompanhamento
+    pub fn is_audible(&self, channel: u8) -> bool {
+        !self.is_player_channel(channel)
+            && self
+                .tracks_with_channel(channel)
+                .any(|t| self.is_track_audible(t))
+    }
+
+    fn tracks_with_channel(&self, channel: u8) -> impl Iterator<Item = usize> + '_ {
+        self.track_channels
+            .iter()
+            .enumerate()
+            .filter(move |(_, channels)| channels.contains(&channel))
+            .map(|(track, _)| track)
+    }
+
+    fn check_track(track: usize) -> MidiReaderResult<()> {
+        if track < MAX_TRACK_COUNT {
+            Ok(())
+        } else {
+            Err(MidiReaderError::InvalidTrack(track))
+        }
+    }
+
+    fn toggle(set: &mut HashSet<usize>, track: usize, on: bool) {
+        if on {
+            set.insert(track);
+        } else {
+            set.remove(&track);
+        }
+    }
+
+    fn sorted(set: &HashSet<usize>) -> Vec<usize> {
+        let mut v: Vec<usize> = set.iter().copied().collect();
+        v.sort_unstable();
+        v
+    }
+}
+
+/// Envia as trilhas de acompanhamento para uma porta de saida midi
+pub struct BackingTrack {
+    connection: MidiOutputConnection,
+    mixer: SharedMixer,
+    on_notes: HashSet<(u8, u8)>,
+}
+
+impl BackingTrack {
+    pub fn new(connection: MidiOutputConnection, mixer: SharedMixer) -> Self {
+        Self {
+            connection,
+            mixer,
+            on_notes: HashSet::new(),
+        }
+    }
+
+    fn audible(&self, channel: u8) -> bool {
+        if let Some(m) = self.mixer.get_data() {
+            m.is_audible(channel)
+        } else {
+            false
+        }
+    }
+
+    fn send_message(&mut self, channel: u4, message: MidiMessage) {
+        let mut buffer: Vec<u8> = Vec::with_capacity(3);
+        let event = LiveEvent::Midi { channel, message };
+        if event.write(&mut buffer).is_ok() {
+            if let Err(_e) = self.connection.send(&buffer) {
+                #[cfg(feature = "verbose")]
+                {
+                    warn!("[{}] Could not send backing event: {}", LOG_TAG, _e);
+                }
+            }
+        }
+    }
+
+    /// Toca o evento se o canal dele puder ser ouvido, notes off sempre sao enviados
+    /// para que nenhuma nota fique presa ao mutar um canal no meio da musica.
+    pub(crate) fn send(&mut self, event: MidiEvent) {
+        let channel: u8 = event.channel.into();
+        match event.message {
+            MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                if self.audible(channel) {
+                    self.on_notes.insert((channel, key.into()));
+                    self.send_message(event.channel, event.message);
+                }
+            }
+            MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+                if self.on_notes.remove(&(channel, key.into())) {
+                    self.send_message(event.channel, event.message);
+                }
+            }
+            _ => {
+                if !self.is_player_channel(channel) {
+                    self.send_message(event.channel, event.message);
+                }
+            }
+        }
+    }
+
+    fn is_player_channel(&self, channel: u8) -> bool {
+        if let Some(m) = self.mixer.get_data() {
+            m.is_player_channel(channel)
+        } else {
+            true
+        }
+    }
+
+    /// Desliga todas as notas que estao soando, usado ao pausar e ao terminar a musica
+    pub(crate) fn silence(&mut self) {
+        let notes: Vec<(u8, u8)> = self.on_notes.drain().collect();
+        let mut channels: HashSet<u8> = HashSet::new();
+        for (channel, key) in notes {
+            channels.insert(channel);
+            self.send_message(
+                u4::new(channel),
+                MidiMessage::NoteOff {
+                    key: u7::new(key),
+                    vel: u7::new(0),
+                },
+            );
+        }
+        for channel in channels {
+            self.send_message(
+                u4::new(channel),
+                MidiMessage::Controller {
+                    controller: u7::new(ALL_NOTES_OFF_CONTROLLER),
+                    value: u7::new(0),
+                },
+            );
+        }
+    }
+}
+
+impl Drop for BackingTrack {
+    fn drop(&mut self) {
+        self.silence();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use midly::num::{u28, u4, u7};
+    use midly::TrackEvent;
+
+    fn note_on(channel: u8) -> TrackEvent<'static> {
+        TrackEvent {
+            delta: u28::new(0),
+            kind: TrackEventKind::Midi {
+                channel: u4::new(channel),
+                message: MidiMessage::NoteOn {
+                    key: u7::new(60),
+                    vel: u7::new(100),
+                },
+            },
+        }
+    }
+
+    /// Trilha 0 no canal 0, trilha 1 no canal 1 e trilha 2 nos canais 1 e 2
+    fn mixer() -> TrackMixer {
+        let mut mixer = TrackMixer::default();
+        mixer.load_tracks(&[
+            vec![note_on(0)],
+            vec![note_on(1)],
+            vec![note_on(1), note_on(2)],
+        ]);
+        mixer
+    }
+
+    #[test]
+    fn without_player_tracks_everything_goes_to_the_chart() {
+        let mixer = mixer();
+        assert!((0..3).all(|t| mixer.is_player_track(t)));
+        assert!((0..16).all(|c| mixer.is_player_channel(c)));
+        assert!((0..16).all(|c| !mixer.is_audible(c)));
+    }
+
+    #[test]
+    fn solo_overrides_mute() {
+        let mut mixer = mixer();
+        mixer.set_player_tracks(&[0]).unwrap();
+        mixer.set_muted(1, true).unwrap();
+        assert!(!mixer.is_track_audible(0));
+        assert!(!mixer.is_track_audible(1));
+        assert!(mixer.is_track_audible(2));
+
+        mixer.set_solo(1, true).unwrap();
+        assert!(mixer.is_track_audible(1));
+        assert!(!mixer.is_track_audible(2));
+        assert!(mixer.set_solo(MAX_TRACK_COUNT, true).is_err());
+    }
+
+    #[test]
+    fn channels_follow_the_tracks_that_use_them() {
+        let mut mixer = mixer();
+        mixer.set_player_tracks(&[1]).unwrap();
+        assert!(mixer.is_player_channel(1));
+        assert!(!mixer.is_player_channel(2));
+        assert!(mixer.is_audible(0));
+        assert!(mixer.is_audible(2));
+
+        mixer.set_muted(2, true).unwrap();
+        assert!(!mixer.is_audible(2));
+        assert!(!mixer.is_audible(3));
+    }
+}
@@ -16,6 +16,18 @@ pub enum MidiReaderError {
     MidiOutputError(#[source] anyhow::Error),
     #[error("No output ports was found")]
     NoPortsFound,
+    #[error("Output port `{0}` was not found")]
+    OutputPortNotFound(String),
+    #[error("Track `{0}` does not exist")]
+    InvalidTrack(usize),
     #[error("Path `{0}` does not exist")]
     FileDoesNotExist(String),
+    #[error("Note with index `{0}` does not exist")]
+    NoteNotFound(usize),
+    #[error("Invalid edit: {0}")]
+    InvalidEdit(String),
+    #[error("Error while writing midi file")]
+    WriteError(#[source] anyhow::Error),
+    #[error("Error while rendering audio")]
+    RenderError(#[source] anyhow::Error),
 }
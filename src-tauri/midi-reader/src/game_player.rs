@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 
+use crate::backing_track::{BackingTrack, SharedMixer};
 use crate::midi_file::{PlayBackCallback, ReadingState};
 use midly::MidiMessage;
 use nodi::{Connection, MidiEvent};
@@ -9,14 +10,31 @@ pub(crate) struct GamePlayer<P: PlayBackCallback> {
     callback: MutableArc<P>,
     on_notes: HashSet<u8>,
     reading_state: MutableArc<ReadingState>,
+    mixer: SharedMixer,
+    backing: Option<MutableArc<BackingTrack>>,
 }
 
 impl<P: PlayBackCallback> GamePlayer<P> {
-    pub fn new(callback: MutableArc<P>, reading_state: MutableArc<ReadingState>) -> Self {
+    pub fn new(
+        callback: MutableArc<P>,
+        reading_state: MutableArc<ReadingState>,
+        mixer: SharedMixer,
+        backing: Option<MutableArc<BackingTrack>>,
+    ) -> Self {
         Self {
             callback,
             on_notes: HashSet::new(),
             reading_state,
+            mixer,
+            backing,
+        }
+    }
+
+    fn is_player_channel(&self, channel: u8) -> bool {
+        if let Some(m) = self.mixer.get_data() {
+            m.is_player_channel(channel)
+        } else {
+            true
         }
     }
 }
@@ -31,6 +49,14 @@ impl<P: PlayBackCallback> Connection for GamePlayer<P> {
                 _ => {}
             }
         }
+        if !self.is_player_channel(event.channel.into()) {
+            if let Some(b) = &self.backing {
+                if let Some(mut b) = b.get_data() {
+                    b.send(event);
+                }
+            }
+            return true;
+        }
         match event.message {
             MidiMessage::NoteOff { key, vel } => {
                 let k: u8 = key.into();
@@ -61,5 +87,10 @@ impl<P: PlayBackCallback> Connection for GamePlayer<P> {
             }
         }
         self.on_notes.clear();
+        if let Some(b) = &self.backing {
+            if let Some(mut b) = b.get_data() {
+                b.silence();
+            }
+        }
     }
 }
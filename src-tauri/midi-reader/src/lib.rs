@@ -1,9 +1,13 @@
+pub mod audio_renderer;
+pub mod backing_track;
 pub mod errors;
 mod game_player;
 pub mod midi_file;
-pub use midi_length_calc::calculate_midi_length;
+pub use midi_length_calc::{calculate_midi_bytes_length, calculate_midi_length};
 mod midi_length_calc;
+pub mod midi_writer;
 pub mod player_wrapper;
+pub mod song_editor;
 #[cfg(test)]
 mod test_callback;
 mod timer;
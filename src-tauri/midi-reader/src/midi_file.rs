@@ -2,6 +2,7 @@ use midi_reader_writer::midly_0_5::exports::Smf;
 
 use std::time::Duration;
 
+use crate::backing_track::{BackingTrack, SharedMixer, TrackMixer};
 use crate::errors::{MidiReaderError, MidiReaderResult};
 use crate::game_player::GamePlayer;
 use crate::midi_length_calc::calc_midi_sheet_length;
@@ -51,6 +52,20 @@ where
     fn create_sheet_player<P: PlayBackCallback>(
         &mut self,
         play_back_callback: P,
+    ) -> MidiReaderResult<PlayerWrapper<P>> {
+        self.create_sheet_player_with_backing(
+            play_back_callback,
+            MutableArc::from(TrackMixer::default()),
+            None,
+        )
+    }
+    /// Cria o player separando os canais do paciente dos canais de acompanhamento,
+    /// que sao enviados para o `backing` caso exista
+    fn create_sheet_player_with_backing<P: PlayBackCallback>(
+        &mut self,
+        play_back_callback: P,
+        mixer: SharedMixer,
+        backing: Option<BackingTrack>,
     ) -> MidiReaderResult<PlayerWrapper<P>>;
     fn pause(&mut self);
     fn unpause(&mut self);
@@ -90,12 +105,14 @@ impl MidiFile {
         &self,
         reading_state: MutableArc<ReadingState>,
         pause_callback: MutableArc<P>,
+        backing: Option<MutableArc<BackingTrack>>,
     ) -> MidiPauseTimer<P> {
         MidiPauseTimer::new(
             self.ticker,
             reading_state,
             pause_callback,
             self.elapsed_time.clone(),
+            backing,
         )
     }
     fn update_reading_state(&self, reading_state: ReadingState) {
@@ -129,6 +146,7 @@ impl MidiFile {
             MutableArc::from(ReadingState::Playing),
             MutableArc::from(p),
             MutableArc::from(Duration::ZERO),
+            None,
         );
 
         let sheet = match header.format {
@@ -171,9 +189,11 @@ impl MidiFilePlayer for MidiFile {
         unimplemented!()
     }
 
-    fn create_sheet_player<P: PlayBackCallback>(
+    fn create_sheet_player_with_backing<P: PlayBackCallback>(
         &mut self,
         play_back_callback: P,
+        mixer: SharedMixer,
+        backing: Option<BackingTrack>,
     ) -> MidiReaderResult<PlayerWrapper<P>> {
         if let Some(m) = self.reading_state.get_data() {
             if *m != ReadingState::NotRunning {
@@ -181,10 +201,20 @@ impl MidiFilePlayer for MidiFile {
             }
         }
         let callback_arc = MutableArc::from(play_back_callback);
+        let backing = backing.map(MutableArc::from);
         self.update_reading_state(ReadingState::Playing);
         Ok(PlayerWrapper::new(
-            self.create_timer(self.reading_state.clone(), callback_arc.clone()),
-            GamePlayer::new(callback_arc.clone(), self.reading_state.clone()),
+            self.create_timer(
+                self.reading_state.clone(),
+                callback_arc.clone(),
+                backing.clone(),
+            ),
+            GamePlayer::new(
+                callback_arc.clone(),
+                self.reading_state.clone(),
+                mixer,
+                backing,
+            ),
             self.reading_state.clone(),
             callback_arc.clone(),
             self.sheet.to_owned(),
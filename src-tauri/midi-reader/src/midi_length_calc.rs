@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use crate::errors::MidiReaderResult;
 use crate::midi_file::{create_sheet_and_ticker, load_midi_bytes};
 use nodi::{timers::Ticker, Connection, Player, Sheet, Timer};
 
@@ -8,6 +9,11 @@ pub fn calculate_midi_length(file: &str) -> Duration {
     calc_midi_sheet_length(&sheet, timer)
 }
 
+pub fn calculate_midi_bytes_length(bytes: Vec<u8>) -> MidiReaderResult<Duration> {
+    let (sheet, timer) = create_sheet_and_ticker(bytes)?;
+    Ok(calc_midi_sheet_length(&sheet, timer))
+}
+
 pub(crate) fn calc_midi_sheet_length(sheet: &Sheet, ticker: Ticker) -> Duration {
     let mut dur = Duration::ZERO;
     let timer = MidiLengthCalculator::new(&mut dur, ticker);
@@ -0,0 +1,88 @@
+use crate::errors::{MidiReaderError, MidiReaderResult};
+use crate::song_editor::EditableSong;
+use anyhow::anyhow;
+use midly::num::{u15, u24, u28, u4, u7};
+use midly::{Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind};
+
+/// Ordem dos eventos que acontecem no mesmo tick, tempo sempre vem primeiro
+/// e notas que terminam sao desligadas antes das notas que comecam.
+const TEMPO_ORDER: u8 = 0;
+const META_ORDER: u8 = 1;
+const NOTE_OFF_ORDER: u8 = 2;
+const CHANNEL_ORDER: u8 = 3;
+const NOTE_ON_ORDER: u8 = 4;
+
+/// Escreve a musica em um novo arquivo midi.
+///
+/// O tempo é escrito na primeira trilha, e cada nota volta para a trilha da qual ela veio.
+pub fn write_midi(song: &EditableSong) -> MidiReaderResult<Vec<u8>> {
+    let mut tracks: Vec<Vec<(u32, u8, TrackEventKind<'static>)>> = vec![vec![]; song.track_count];
+    tracks[0].push((
+        0,
+        TEMPO_ORDER,
+        TrackEventKind::Meta(MetaMessage::Tempo(u24::new(song.tempo))),
+    ));
+    for event in song.kept_events.iter() {
+        let order = match event.kind {
+            TrackEventKind::Meta(_) => META_ORDER,
+            _ => CHANNEL_ORDER,
+        };
+        tracks[event.track].push((event.tick, order, event.kind));
+    }
+    for note in song.notes.iter() {
+        let channel = u4::new(note.channel);
+        let key = u7::new(note.key);
+        tracks[note.track].push((
+            note.start,
+            NOTE_ON_ORDER,
+            TrackEventKind::Midi {
+                channel,
+                message: MidiMessage::NoteOn {
+                    key,
+                    vel: u7::new(note.velocity),
+                },
+            },
+        ));
+        tracks[note.track].push((
+            note.end(),
+            NOTE_OFF_ORDER,
+            TrackEventKind::Midi {
+                channel,
+                message: MidiMessage::NoteOff {
+                    key,
+                    vel: u7::new(0),
+                },
+            },
+        ));
+    }
+    let format = if song.track_count == 1 {
+        Format::SingleTrack
+    } else {
+        Format::Parallel
+    };
+    let mut smf = Smf::new(Header::new(
+        format,
+        Timing::Metrical(u15::new(song.ticks_per_beat)),
+    ));
+    for mut events in tracks {
+        events.sort_by_key(|(tick, order, _)| (*tick, *order));
+        let mut last_tick: u32 = 0;
+        let mut track: Vec<TrackEvent<'static>> = Vec::with_capacity(events.len() + 1);
+        for (tick, _, kind) in events {
+            track.push(TrackEvent {
+                delta: u28::new(tick - last_tick),
+                kind,
+            });
+            last_tick = tick;
+        }
+        track.push(TrackEvent {
+            delta: u28::new(0),
+            kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+        });
+        smf.tracks.push(track);
+    }
+    let mut bytes: Vec<u8> = vec![];
+    smf.write(&mut bytes)
+        .map_err(move |e| MidiReaderError::WriteError(anyhow!(e)))?;
+    Ok(bytes)
+}
@@ -0,0 +1,343 @@
+use crate::errors::{MidiReaderError, MidiReaderResult};
+use crate::midi_writer::write_midi;
+use anyhow::anyhow;
+use midly::{Format, MetaMessage, MidiMessage, Smf, Timing, TrackEventKind};
+
+/// Tempo padrao de um arquivo midi quando nenhum evento de tempo é encontrado (120 bpm)
+pub const DEFAULT_TEMPO: u32 = 500_000;
+const MICROS_PER_MINUTE: f64 = 60_000_000f64;
+const MIN_BPM: f64 = 20f64;
+const MAX_BPM: f64 = 400f64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditableNote {
+    pub track: usize,
+    pub channel: u8,
+    pub key: u8,
+    pub velocity: u8,
+    /// Tick em que a nota comeca
+    pub start: u32,
+    /// Duracao da nota em ticks
+    pub duration: u32,
+}
+
+impl EditableNote {
+    pub fn end(&self) -> u32 {
+        self.start.saturating_add(self.duration)
+    }
+}
+
+/// Eventos que nao sao notas mas que precisam ser mantidos ao reescrever o arquivo,
+/// como troca de instrumento, control change, formula de compasso, etc.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct KeptEvent {
+    pub track: usize,
+    pub tick: u32,
+    pub kind: TrackEventKind<'static>,
+}
+
+/// Uma musica carregada em memoria para edicao.
+///
+/// As notas ficam ordenadas pelo tick de inicio, entao o index de uma nota
+/// pode mudar depois de qualquer edicao, por isso todas as funcoes que alteram
+/// uma nota devolvem o novo index dela.
+#[derive(Debug, Clone)]
+pub struct EditableSong {
+    pub(crate) ticks_per_beat: u16,
+    pub(crate) tempo: u32,
+    pub(crate) track_count: usize,
+    pub(crate) notes: Vec<EditableNote>,
+    pub(crate) kept_events: Vec<KeptEvent>,
+}
+
+impl EditableSong {
+    pub fn from_bytes(bytes: &[u8]) -> MidiReaderResult<Self> {
+        let smf = Smf::parse(bytes).map_err(move |e| MidiReaderError::InvalidMidiFile(anyhow!(e)))?;
+        let ticks_per_beat = match smf.header.timing {
+            Timing::Metrical(t) => t.as_int(),
+            Timing::Timecode(_, _) => {
+                return Err(MidiReaderError::InvalidMidiFile(anyhow!(
+                    "Timecode based midi files cannot be edited"
+                )))
+            }
+        };
+        let mut tempo: Option<u32> = None;
+        let mut notes: Vec<EditableNote> = vec![];
+        let mut kept_events: Vec<KeptEvent> = vec![];
+        let mut track_offset: u32 = 0;
+        for (track_index, track) in smf.tracks.iter().enumerate() {
+            let mut tick: u32 = track_offset;
+            let mut open_notes: Vec<EditableNote> = vec![];
+            for event in track {
+                tick = tick.saturating_add(event.delta.as_int());
+                match event.kind {
+                    TrackEventKind::Midi { channel, message } => match message {
+                        MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                            open_notes.push(EditableNote {
+                                track: track_index,
+                                channel: channel.as_int(),
+                                key: key.as_int(),
+                                velocity: vel.as_int(),
+                                start: tick,
+                                duration: 0,
+                            });
+                        }
+                        MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+                            let position = open_notes.iter().position(|n| {
+                                n.key == key.as_int() && n.channel == channel.as_int()
+                            });
+                            if let Some(p) = position {
+                                Self::close_note(&mut notes, open_notes.remove(p), tick);
+                            }
+                        }
+                        _ => kept_events.push(KeptEvent {
+                            track: track_index,
+                            tick,
+                            kind: TrackEventKind::Midi { channel, message },
+                        }),
+                    },
+                    TrackEventKind::Meta(MetaMessage::Tempo(t)) if tempo.is_none() => {
+                        tempo = Some(t.as_int());
+                    }
+                    TrackEventKind::Meta(m @ MetaMessage::TimeSignature(..))
+                    | TrackEventKind::Meta(m @ MetaMessage::KeySignature(..)) => {
+                        kept_events.push(KeptEvent {
+                            track: track_index,
+                            tick,
+                            kind: TrackEventKind::Meta(m.to_static()),
+                        })
+                    }
+                    _ => {}
+                }
+            }
+            for note in open_notes {
+                Self::close_note(&mut notes, note, tick);
+            }
+            if smf.header.format == Format::Sequential {
+                track_offset = tick;
+            }
+        }
+        let mut song = Self {
+            ticks_per_beat,
+            tempo: tempo.unwrap_or(DEFAULT_TEMPO),
+            track_count: smf.tracks.len().max(1),
+            notes,
+            kept_events,
+        };
+        song.sort_notes();
+        Ok(song)
+    }
+
+    pub fn to_bytes(&self) -> MidiReaderResult<Vec<u8>> {
+        write_midi(self)
+    }
+
+    pub fn notes(&self) -> &[EditableNote] {
+        &self.notes
+    }
+
+    pub fn ticks_per_beat(&self) -> u16 {
+        self.ticks_per_beat
+    }
+
+    /// Tempo em microsegundos por batida
+    pub fn tempo(&self) -> u32 {
+        self.tempo
+    }
+
+    pub fn tempo_bpm(&self) -> f64 {
+        MICROS_PER_MINUTE / self.tempo as f64
+    }
+
+    pub fn track_count(&self) -> usize {
+        self.track_count
+    }
+
+    /// Troca o tempo da musica inteira, todas as outras mudancas de tempo
+    /// do arquivo original sao descartadas.
+    pub fn set_tempo_bpm(&mut self, bpm: f64) -> MidiReaderResult<()> {
+        if !(MIN_BPM..=MAX_BPM).contains(&bpm) {
+            return Err(MidiReaderError::InvalidEdit(format!(
+                "Tempo must be between {} and {} bpm",
+                MIN_BPM, MAX_BPM
+            )));
+        }
+        self.tempo = (MICROS_PER_MINUTE / bpm).round() as u32;
+        Ok(())
+    }
+
+    /// Move a nota para um novo tick e altura, devolve o novo index dela
+    pub fn move_note(&mut self, index: usize, start: u32, key: u8) -> MidiReaderResult<usize> {
+        let mut note = self.note(index)?;
+        note.start = start;
+        note.key = key;
+        self.replace_note(index, note)
+    }
+
+    /// Altera a duracao da nota, devolve o novo index dela
+    pub fn resize_note(&mut self, index: usize, duration: u32) -> MidiReaderResult<usize> {
+        let mut note = self.note(index)?;
+        note.duration = duration;
+        self.replace_note(index, note)
+    }
+
+    /// Altera a velocidade da nota, que tambem é a pressao alvo do sopro nela
+    pub fn set_velocity(&mut self, index: usize, velocity: u8) -> MidiReaderResult<usize> {
+        let mut note = self.note(index)?;
+        note.velocity = velocity;
+        self.replace_note(index, note)
+    }
+
+    pub fn delete_note(&mut self, index: usize) -> MidiReaderResult<EditableNote> {
+        self.note(index)?;
+        Ok(self.notes.remove(index))
+    }
+
+    /// Adiciona uma nova nota, devolve o index dela
+    pub fn insert_note(&mut self, note: EditableNote) -> MidiReaderResult<usize> {
+        Self::validate_note(&note, self.track_count)?;
+        let index = self.notes.partition_point(|n| Self::order(n) <= Self::order(&note));
+        self.notes.insert(index, note);
+        Ok(index)
+    }
+
+    pub fn note(&self, index: usize) -> MidiReaderResult<EditableNote> {
+        self.notes
+            .get(index)
+            .copied()
+            .ok_or(MidiReaderError::NoteNotFound(index))
+    }
+
+    fn replace_note(&mut self, index: usize, note: EditableNote) -> MidiReaderResult<usize> {
+        Self::validate_note(&note, self.track_count)?;
+        self.notes.remove(index);
+        self.insert_note(note)
+    }
+
+    fn validate_note(note: &EditableNote, track_count: usize) -> MidiReaderResult<()> {
+        if note.key > 127 || note.velocity > 127 || note.velocity == 0 {
+            return Err(MidiReaderError::InvalidEdit(
+                "Note key and velocity must be between 1 and 127".to_string(),
+            ));
+        }
+        if note.channel > 15 {
+            return Err(MidiReaderError::InvalidEdit(
+                "Channel must be between 0 and 15".to_string(),
+            ));
+        }
+        if note.duration == 0 {
+            return Err(MidiReaderError::InvalidEdit(
+                "Note duration must be greater than zero".to_string(),
+            ));
+        }
+        if note.track >= track_count {
+            return Err(MidiReaderError::InvalidEdit(format!(
+                "Track {} does not exist",
+                note.track
+            )));
+        }
+        Ok(())
+    }
+
+    /// Notas que terminam no mesmo tick em que comecam sao descartadas,
+    /// o editor nao aceita notas sem duracao
+    fn close_note(notes: &mut Vec<EditableNote>, mut note: EditableNote, tick: u32) {
+        note.duration = tick - note.start;
+        if note.duration > 0 {
+            notes.push(note);
+        }
+    }
+
+    #[inline]
+    fn order(note: &EditableNote) -> (u32, u8) {
+        (note.start, note.key)
+    }
+
+    fn sort_notes(&mut self) {
+        self.notes.sort_by_key(Self::order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use midly::num::{u15, u24, u28, u4, u7};
+    use midly::{Header, TrackEvent};
+
+    fn midi_event(delta: u32, on: bool, key: u8) -> TrackEvent<'static> {
+        let (key, vel) = (u7::new(key), u7::new(if on { 100 } else { 0 }));
+        TrackEvent {
+            delta: u28::new(delta),
+            kind: TrackEventKind::Midi {
+                channel: u4::new(0),
+                message: if on {
+                    MidiMessage::NoteOn { key, vel }
+                } else {
+                    MidiMessage::NoteOff { key, vel }
+                },
+            },
+        }
+    }
+
+    fn note(key: u8, start: u32, duration: u32) -> EditableNote {
+        EditableNote {
+            track: 0,
+            channel: 0,
+            key,
+            velocity: 100,
+            start,
+            duration,
+        }
+    }
+
+    fn song_bytes() -> Vec<u8> {
+        let mut smf = Smf::new(Header::new(
+            Format::SingleTrack,
+            Timing::Metrical(u15::new(96)),
+        ));
+        smf.tracks.push(vec![
+            TrackEvent {
+                delta: u28::new(0),
+                kind: TrackEventKind::Meta(MetaMessage::Tempo(u24::new(400_000))),
+            },
+            midi_event(0, true, 60),
+            midi_event(0, true, 62),
+            midi_event(0, false, 62),
+            midi_event(96, false, 60),
+            midi_event(0, true, 64),
+            midi_event(96, false, 64),
+            TrackEvent {
+                delta: u28::new(0),
+                kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+            },
+        ]);
+        let mut bytes = vec![];
+        smf.write_std(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn zero_length_notes_are_dropped_on_load() {
+        let song = EditableSong::from_bytes(&song_bytes()).unwrap();
+        assert_eq!(song.tempo(), 400_000);
+        assert_eq!(song.notes(), &[note(60, 0, 96), note(64, 96, 96)]);
+    }
+
+    #[test]
+    fn edited_song_survives_a_round_trip() {
+        let mut song = EditableSong::from_bytes(&song_bytes()).unwrap();
+        song.set_tempo_bpm(100f64).unwrap();
+        let index = song.move_note(1, 0, 67).unwrap();
+        song.resize_note(index, 48).unwrap();
+        song.insert_note(note(72, 48, 48)).unwrap();
+        assert!(song.resize_note(0, 0).is_err());
+
+        let reloaded = EditableSong::from_bytes(&song.to_bytes().unwrap()).unwrap();
+        assert_eq!(reloaded.tempo(), song.tempo());
+        assert_eq!(reloaded.ticks_per_beat(), 96);
+        assert_eq!(
+            reloaded.notes(),
+            &[note(60, 0, 96), note(67, 0, 48), note(72, 48, 48)]
+        );
+    }
+}
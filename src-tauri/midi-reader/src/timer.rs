@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use crate::backing_track::BackingTrack;
 use crate::midi_file::{PlayBackCallback, ReadingState};
 use nodi::{
     timers::{sleep, Ticker},
@@ -11,13 +12,13 @@ use utils::mutable_arc::MutableArc;
 
 const GAME_PAUSE_CHECK_DELAY_MS: u32 = 4_000;
 
-#[derive(Debug)]
 pub struct MidiPauseTimer<P: PlayBackCallback> {
     check_delay: u32,
     ticker: Ticker,
     reading_state: MutableArc<ReadingState>,
     pause_callback: MutableArc<P>,
     elapsed_time: MutableArc<Duration>,
+    backing: Option<MutableArc<BackingTrack>>,
 }
 
 impl<P: PlayBackCallback> MidiPauseTimer<P> {
@@ -26,6 +27,7 @@ impl<P: PlayBackCallback> MidiPauseTimer<P> {
         reading_state: MutableArc<ReadingState>,
         pause_callback: MutableArc<P>,
         elapsed_time: MutableArc<Duration>,
+        backing: Option<MutableArc<BackingTrack>>,
     ) -> Self {
         Self {
             check_delay: GAME_PAUSE_CHECK_DELAY_MS,
@@ -33,6 +35,7 @@ impl<P: PlayBackCallback> MidiPauseTimer<P> {
             reading_state,
             pause_callback,
             elapsed_time,
+            backing,
         }
     }
 
@@ -56,6 +59,11 @@ impl<P: PlayBackCallback> MidiPauseTimer<P> {
     }
 
     fn on_pause(&self) {
+        if let Some(b) = &self.backing {
+            if let Some(mut b) = b.get_data() {
+                b.silence();
+            }
+        }
         if let Some(c) = self.pause_callback.get_data() {
             #[cfg(feature = "verbose")]
             {
@@ -20,6 +20,18 @@ pub struct Model {
     pub total_breathing_duration: Option<i32>,
     pub total_early_misses: Option<i32>,
     pub total_misses: Option<i32>,
+    pub total_interruptions: Option<i32>,
+    pub interrupted_duration: Option<i32>,
+    pub player: Option<i32>,
+    pub scoring_profile: Option<String>,
+    pub sustain_score: Option<i32>,
+    pub steadiness_score: Option<i32>,
+    pub average_sustain: Option<i32>,
+    pub average_steadiness: Option<i32>,
+    pub time_in_zone: Option<i32>,
+    pub accuracy: Option<i32>,
+    pub grade: Option<String>,
+    pub stars: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
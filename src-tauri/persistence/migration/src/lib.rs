@@ -5,6 +5,13 @@ mod m20220101_000001_create_table;
 mod m20240908_003611_load_musics;
 mod m20241003_012943_extra_data;
 mod m20241004_041209_total_misses;
+mod m20241020_184512_interruptions;
+mod m20241103_201530_score_player;
+mod m20241110_143020_duration_millis;
+mod m20241117_190412_scoring_profile;
+mod m20241124_163255_sustain;
+mod m20241201_172840_time_in_zone;
+mod m20241208_154617_grades;
 
 pub struct Migrator;
 
@@ -16,6 +23,13 @@ impl MigratorTrait for Migrator {
             Box::new(m20240908_003611_load_musics::Migration),
             Box::new(m20241003_012943_extra_data::Migration),
             Box::new(m20241004_041209_total_misses::Migration),
+            Box::new(m20241020_184512_interruptions::Migration),
+            Box::new(m20241103_201530_score_player::Migration),
+            Box::new(m20241110_143020_duration_millis::Migration),
+            Box::new(m20241117_190412_scoring_profile::Migration),
+            Box::new(m20241124_163255_sustain::Migration),
+            Box::new(m20241201_172840_time_in_zone::Migration),
+            Box::new(m20241208_154617_grades::Migration),
         ]
     }
 }
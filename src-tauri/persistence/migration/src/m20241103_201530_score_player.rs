@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Score::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Score::Player)
+                            .integer()
+                            .default(0)
+                    )
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Score::Table)
+                    .drop_column(Score::Player)
+                    .to_owned()
+            ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Score {
+    Table,
+    Player
+}
@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// As duracoes do score passam a ser salvas em milissegundos
+const DURATION_COLUMNS: [Score; 3] = [
+    Score::HighestBreathingDuration,
+    Score::TotalBreathingDuration,
+    Score::InterruptedDuration
+];
+
+async fn scale_durations(manager: &SchemaManager<'_>, scale: impl Fn(Expr) -> SimpleExpr) -> Result<(), DbErr> {
+    for col in DURATION_COLUMNS {
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Score::Table)
+                    .value(col, scale(Expr::col(col)))
+                    .to_owned()
+            ).await?;
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        scale_durations(manager, |col| col.mul(1000)).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        scale_durations(manager, |col| col.div(1000)).await
+    }
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum Score {
+    Table,
+    HighestBreathingDuration,
+    TotalBreathingDuration,
+    InterruptedDuration
+}
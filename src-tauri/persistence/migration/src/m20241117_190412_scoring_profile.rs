@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Perfil de pontuacao usado em cada score, scores antigos ficam sem perfil
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Score::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Score::ScoringProfile)
+                            .string()
+                            .null()
+                    )
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Score::Table)
+                    .drop_column(Score::ScoringProfile)
+                    .to_owned()
+            ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Score {
+    Table,
+    ScoringProfile
+}
@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+async fn add_col<T: IntoIden>(schema_manager: &SchemaManager<'_>, col: T) -> Result<(), DbErr> {
+    schema_manager
+        .alter_table(
+            Table::alter()
+                .table(Score::Table)
+                .add_column_if_not_exists(
+                    ColumnDef::new(col)
+                        .integer()
+                        .null()
+                )
+                .to_owned()
+        ).await
+}
+
+async fn drop_col<T: IntoIden>(schema_manager: &SchemaManager<'_>, col: T) -> Result<(), DbErr> {
+    schema_manager
+        .alter_table(
+            Table::alter()
+                .table(Score::Table)
+                .drop_column(col)
+                .to_owned()
+        ).await
+}
+
+/// Pontos e medias das notas seguradas, scores antigos ficam sem esses valores
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        add_col(manager, Score::SustainScore).await?;
+        add_col(manager, Score::SteadinessScore).await?;
+        add_col(manager, Score::AverageSustain).await?;
+        add_col(manager, Score::AverageSteadiness).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        drop_col(manager, Score::SustainScore).await?;
+        drop_col(manager, Score::SteadinessScore).await?;
+        drop_col(manager, Score::AverageSustain).await?;
+        drop_col(manager, Score::AverageSteadiness).await
+    }
+}
+
+#[derive(DeriveIden)]
+#[allow(clippy::enum_variant_names)]
+enum Score {
+    Table,
+    SustainScore,
+    SteadinessScore,
+    AverageSustain,
+    AverageSteadiness
+}
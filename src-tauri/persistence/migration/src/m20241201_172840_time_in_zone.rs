@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Media do tempo das notas com a pressao dentro da zona alvo, scores antigos ficam sem esse valor
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Score::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Score::TimeInZone)
+                            .integer()
+                            .null()
+                    )
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Score::Table)
+                    .drop_column(Score::TimeInZone)
+                    .to_owned()
+            ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Score {
+    Table,
+    TimeInZone
+}
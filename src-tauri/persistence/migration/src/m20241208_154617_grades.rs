@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+async fn add_col(schema_manager: &SchemaManager<'_>, col: &mut ColumnDef) -> Result<(), DbErr> {
+    schema_manager
+        .alter_table(
+            Table::alter()
+                .table(Score::Table)
+                .add_column_if_not_exists(col.null())
+                .to_owned()
+        ).await
+}
+
+async fn drop_col<T: IntoIden>(schema_manager: &SchemaManager<'_>, col: T) -> Result<(), DbErr> {
+    schema_manager
+        .alter_table(
+            Table::alter()
+                .table(Score::Table)
+                .drop_column(col)
+                .to_owned()
+        ).await
+}
+
+/// Precisao, nota e estrelas de cada tentativa, nos scores antigos elas ficam vazias e sao
+/// calculadas na leitura com os acertos salvos
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        add_col(manager, ColumnDef::new(Score::Accuracy).integer()).await?;
+        add_col(manager, ColumnDef::new(Score::Grade).string()).await?;
+        add_col(manager, ColumnDef::new(Score::Stars).integer()).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        drop_col(manager, Score::Accuracy).await?;
+        drop_col(manager, Score::Grade).await?;
+        drop_col(manager, Score::Stars).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Score {
+    Table,
+    Accuracy,
+    Grade,
+    Stars
+}
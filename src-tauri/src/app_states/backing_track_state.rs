@@ -0,0 +1,74 @@
+use crate::commands::ServiceResult;
+use crate::constants::errors::STATE_ACQUIRE_ERROR;
+use midi_reader::backing_track::{connect_to_output_port, BackingTrack, SharedMixer, TrackMixer};
+use midi_reader::errors::MidiReaderResult;
+use std::sync::Mutex;
+
+/// Configuracao do acompanhamento: a porta de saida escolhida e o mixer dos canais.
+///
+/// O mixer é compartilhado com o player, entao mudar mute/solo no meio da musica
+/// ja altera o que esta tocando.
+pub struct BackingTrackState {
+    output_port: Mutex<Option<String>>,
+    mixer: SharedMixer,
+}
+
+impl Default for BackingTrackState {
+    fn default() -> Self {
+        Self {
+            output_port: Mutex::new(None),
+            mixer: SharedMixer::from(TrackMixer::default()),
+        }
+    }
+}
+
+impl BackingTrackState {
+    pub fn select_output_port(&self, port_name: Option<String>) -> ServiceResult<()> {
+        if let Ok(mut p) = self.output_port.lock() {
+            *p = port_name;
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn output_port(&self) -> ServiceResult<Option<String>> {
+        if let Ok(p) = self.output_port.lock() {
+            Ok(p.clone())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn mixer(&self) -> SharedMixer {
+        self.mixer.clone()
+    }
+
+    pub fn update_mixer<T>(
+        &self,
+        update: impl FnOnce(&mut TrackMixer) -> MidiReaderResult<T>,
+    ) -> ServiceResult<T> {
+        if let Some(mut m) = self.mixer.get_data() {
+            Ok(update(&mut m)?)
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn read_mixer<T>(&self, read: impl FnOnce(&TrackMixer) -> T) -> ServiceResult<T> {
+        if let Some(m) = self.mixer.get_data() {
+            Ok(read(&m))
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Conecta na porta escolhida, retorna [None] se nenhuma porta foi escolhida
+    pub fn connect(&self) -> ServiceResult<Option<BackingTrack>> {
+        let Some(port) = self.output_port()? else {
+            return Ok(None);
+        };
+        let connection = connect_to_output_port(&port)?;
+        Ok(Some(BackingTrack::new(connection, self.mixer.clone())))
+    }
+}
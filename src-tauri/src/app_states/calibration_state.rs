@@ -0,0 +1,105 @@
+use crate::app_states::midi_device_state::PlayerSlot;
+use crate::commands::ServiceResult;
+use crate::constants::errors::{STATE_ACQUIRE_ERROR, USER_CALIBRATION_NOT_STARTED};
+use arduino_comm::breath_calibration::{BreathCalibration, BreathCalibrator, CalibrationStep};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Curva de sopro de cada jogador e a calibracao em andamento.
+///
+/// A curva é aplicada na pressao crua do dispositivo do jogador antes dela chegar no
+/// [crate::app_states::monitoring_state::MonitoringState] e no front. Jogadores sem
+/// calibracao usam a curva padrao. So um jogador calibra por vez.
+#[derive(Default)]
+pub struct CalibrationState {
+    calibrations: Mutex<HashMap<PlayerSlot, BreathCalibration>>,
+    calibrator: Mutex<Option<(PlayerSlot, BreathCalibrator)>>,
+}
+
+impl CalibrationState {
+    pub fn set_calibration(&self, player: PlayerSlot, calibration: BreathCalibration) -> ServiceResult<()> {
+        if let Ok(mut c) = self.calibrations.lock() {
+            c.insert(player, calibration);
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn calibration(&self, player: PlayerSlot) -> ServiceResult<BreathCalibration> {
+        if let Ok(c) = self.calibrations.lock() {
+            Ok(c.get(&player).copied().unwrap_or_default())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Pressao relativa a capacidade do jogador
+    pub fn apply(&self, player: PlayerSlot, raw: u8) -> u8 {
+        match self.calibrations.lock() {
+            Ok(c) => c.get(&player).copied().unwrap_or_default().apply(raw),
+            Err(_) => raw
+        }
+    }
+
+    /// Recebe a pressao crua do dispositivo do jogador, so é usada durante a calibracao dele
+    pub fn receive_raw_sample(&self, player: PlayerSlot, raw: u8) {
+        if let Ok(mut c) = self.calibrator.lock() {
+            if let Some((calibrating, calibrator)) = c.as_mut() {
+                if *calibrating == player {
+                    calibrator.receive_sample(raw);
+                }
+            }
+        }
+    }
+
+    /// Começa a gravar uma etapa, comecando uma nova calibracao se nao tiver nenhuma
+    /// ou se a atual for de outro jogador
+    pub fn start_step(&self, player: PlayerSlot, step: CalibrationStep) -> ServiceResult<()> {
+        if let Ok(mut c) = self.calibrator.lock() {
+            if c.as_ref().is_some_and(|(calibrating, _)| *calibrating != player) {
+                c.take();
+            }
+            c.get_or_insert_with(|| (player, BreathCalibrator::default())).1.start_step(step);
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn finish_step(&self) -> ServiceResult<u8> {
+        if let Ok(mut c) = self.calibrator.lock() {
+            match c.as_mut() {
+                Some((_, calibrator)) => Ok(calibrator.finish_step()?),
+                None => Err(USER_CALIBRATION_NOT_STARTED.into())
+            }
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Termina a calibracao em andamento e passa a usar a curva medida,
+    /// retorna o jogador calibrado
+    pub fn finish_calibration(&self) -> ServiceResult<(PlayerSlot, BreathCalibration)> {
+        let (player, calibration) = if let Ok(mut c) = self.calibrator.lock() {
+            match c.as_ref() {
+                Some((player, calibrator)) => {
+                    let result = (*player, calibrator.calibration()?);
+                    c.take();
+                    result
+                }
+                None => return Err(USER_CALIBRATION_NOT_STARTED.into())
+            }
+        } else {
+            return Err(STATE_ACQUIRE_ERROR.into());
+        };
+        self.set_calibration(player, calibration)?;
+        Ok((player, calibration))
+    }
+
+    pub fn cancel_calibration(&self) {
+        if let Ok(mut c) = self.calibrator.lock() {
+            c.take();
+        }
+    }
+}
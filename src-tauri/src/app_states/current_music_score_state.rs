@@ -1,9 +1,11 @@
+use crate::app_states::midi_device_state::{PlayerSlot, MAIN_PLAYER};
+use crate::commands::{GradeThresholds, OnNotePrecision, ScoringProfile};
 use persistence::storage::{StorageResult, StorageSavable, Store};
 use serde::ser::Error;
 use serde::{Serialize, Serializer};
+use std::collections::HashMap;
 use std::sync::Mutex;
 
-const DEFAULT_SCORE: Score = 10;
 const SCORE_KEY: &str = "__score";
 const NUMBER_OF_ATTEMPTS_KEY: &str = "__attempts";
 
@@ -14,11 +16,17 @@ pub struct CurrentMusicScore {
     pub total_score: Score,
     pub hit_streak: u32,
     pub highest_streak: u32,
+    /// Parte do total que veio das notas seguradas
+    pub sustain_score: Score,
+    /// Parte do total que veio da estabilidade do sopro
+    pub steadiness_score: Score,
 }
 
+/// Score de cada jogador na musica atual e o perfil de pontuacao usado nela
 #[derive(Default)]
 pub struct CurrentMusicScoreState {
-    pub score: Mutex<CurrentMusicScore>,
+    pub scores: Mutex<HashMap<PlayerSlot, CurrentMusicScore>>,
+    profile: Mutex<ScoringProfile>,
 }
 
 impl StorageSavable for CurrentMusicScore {}
@@ -42,26 +50,55 @@ impl CurrentMusicScoreState {
         format!("{}{}", music_id, key_type)
     }
     pub fn reset(&self) {
-        if let Ok(mut score) = self.score.lock() {
-            *score = Default::default();
+        if let Ok(mut scores) = self.scores.lock() {
+            scores.clear();
         }
     }
-    pub fn add_to_total_score(&self, multiplier: f32, is_miss: bool) -> (Score, Score, u32) {
-        if let Ok(mut score) = self.score.lock() {
-            if is_miss {
-                score.hit_streak = 0;
-            } else {
-                score.hit_streak += 1;
-                if score.hit_streak > score.highest_streak {
-                    score.highest_streak = score.highest_streak;
-                }
-            };
-            let score_to_add = DEFAULT_SCORE * (score.hit_streak as Score) * (multiplier as Score);
-            score.total_score += score_to_add;
-            (score.total_score, score_to_add, score.hit_streak)
+    /// Perfil usado nas proximas notas, trocado quando a musica começa
+    pub fn set_profile(&self, profile: ScoringProfile) {
+        if let Ok(mut p) = self.profile.lock() {
+            *p = profile;
+        }
+    }
+    pub fn profile_id(&self) -> Option<String> {
+        self.profile.lock().ok().map(|p| p.id.clone())
+    }
+    pub fn grade_thresholds(&self) -> GradeThresholds {
+        self.profile.lock().map(|p| p.grades).unwrap_or_default()
+    }
+    pub fn breath_zone_tolerance(&self) -> u8 {
+        self.profile.lock().map(|p| p.breath_zone_tolerance).unwrap_or_default()
+    }
+    /// Soma a nota no score do jogador, retorna o total, quanto mudou e a sequencia de acertos
+    pub fn add_to_total_score(&self, player: PlayerSlot, precision: OnNotePrecision) -> (Score, Score, u32) {
+        let (Ok(mut scores), Ok(profile)) = (self.scores.lock(), self.profile.lock()) else {
+            return (0, 0, 0);
+        };
+        let score = scores.entry(player).or_default();
+        if bool::from(precision) {
+            score.hit_streak += 1;
+            score.highest_streak = score.highest_streak.max(score.hit_streak);
         } else {
-            (0, 0, 0)
+            score.hit_streak = 0;
         }
+        let total = score.total_score.saturating_add(profile.note_score(precision, score.hit_streak));
+        let total = if profile.allow_negative_total { total } else { total.max(0) };
+        let gained = total - score.total_score;
+        score.total_score = total;
+        (total, gained, score.hit_streak)
+    }
+    /// Soma a avaliacao de uma nota segurada, retorna o total, os pontos de cada parte e a sequencia
+    /// de acertos, que nao muda
+    pub fn add_sustain_score(&self, player: PlayerSlot, sustain: f32, steadiness: Option<f32>) -> (Score, Score, Score, u32) {
+        let (Ok(mut scores), Ok(profile)) = (self.scores.lock(), self.profile.lock()) else {
+            return (0, 0, 0, 0);
+        };
+        let score = scores.entry(player).or_default();
+        let (sustain_score, steadiness_score) = profile.sustain_score(sustain, steadiness);
+        score.sustain_score += sustain_score;
+        score.steadiness_score += steadiness_score;
+        score.total_score = score.total_score.saturating_add(sustain_score + steadiness_score);
+        (score.total_score, sustain_score, steadiness_score, score.hit_streak)
     }
 }
 
@@ -70,8 +107,8 @@ impl Serialize for CurrentMusicScoreState {
     where
         S: Serializer,
     {
-        if let Ok(score) = self.score.lock() {
-            score.serialize(serializer)
+        if let Ok(scores) = self.scores.lock() {
+            scores.get(&MAIN_PLAYER).map(|s| s.total_score).unwrap_or_default().serialize(serializer)
         } else {
             Err(Error::custom("mutex poisoned"))
         }
@@ -0,0 +1,170 @@
+use crate::app_states::midi_device_state::{PlayerSlot, MAIN_PLAYER};
+use crate::commands::ServiceResult;
+use crate::constants::errors::{INVALID_PARAMETER, STATE_ACQUIRE_ERROR};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Segundo jogador do dueto, o primeiro é o [MAIN_PLAYER]
+pub const DUET_PARTNER: PlayerSlot = 1;
+
+/// Modo dueto, as notas da partitura sao divididas entre dois pacientes.
+///
+/// Os jogadores se revezam a cada `notes_per_turn` notas, o note off sempre vai
+/// para o jogador que recebeu o note on da mesma nota.
+#[derive(Default)]
+pub struct DuetState {
+    splitter: Mutex<Option<DuetSplitter>>,
+}
+
+struct DuetSplitter {
+    notes_per_turn: u32,
+    notes_in_turn: u32,
+    turn: PlayerSlot,
+    /// Jogador de cada nota ligada
+    active: HashMap<u8, PlayerSlot>,
+}
+
+impl DuetSplitter {
+    fn new(notes_per_turn: u32) -> Self {
+        Self {
+            notes_per_turn,
+            notes_in_turn: 0,
+            turn: MAIN_PLAYER,
+            active: HashMap::new(),
+        }
+    }
+
+    fn assign(&mut self, on: bool, key: u8, velocity: u8) -> PlayerSlot {
+        // muitos arquivos desligam a nota com um note on de velocidade 0
+        if !on || velocity == 0 {
+            return self.active.remove(&key).unwrap_or(self.turn);
+        }
+        if self.notes_in_turn >= self.notes_per_turn {
+            self.notes_in_turn = 0;
+            self.turn = if self.turn == MAIN_PLAYER { DUET_PARTNER } else { MAIN_PLAYER };
+        }
+        self.notes_in_turn += 1;
+        self.active.insert(key, self.turn);
+        self.turn
+    }
+}
+
+impl DuetState {
+    /// Liga o dueto, `notes_per_turn` precisa ser maior que 0
+    pub fn enable(&self, notes_per_turn: u32) -> ServiceResult<()> {
+        if notes_per_turn == 0 {
+            return Err(INVALID_PARAMETER.into());
+        }
+        if let Ok(mut s) = self.splitter.lock() {
+            *s = Some(DuetSplitter::new(notes_per_turn));
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn disable(&self) {
+        if let Ok(mut s) = self.splitter.lock() {
+            s.take();
+        }
+    }
+
+    /// Notas de cada vez, [None] se o dueto estiver desligado
+    pub fn notes_per_turn(&self) -> Option<u32> {
+        match self.splitter.lock() {
+            Ok(s) => s.as_ref().map(|d| d.notes_per_turn),
+            Err(_) => None
+        }
+    }
+
+    /// Volta a vez para o primeiro jogador, chamado quando a musica começa
+    pub fn restart(&self) {
+        if let Ok(mut s) = self.splitter.lock() {
+            if let Some(d) = s.as_mut() {
+                *d = DuetSplitter::new(d.notes_per_turn);
+            }
+        }
+    }
+
+    /// Jogador que deve tocar a nota da partitura, sem dueto é sempre o [MAIN_PLAYER]
+    pub fn assign(&self, on: bool, key: u8, velocity: u8) -> PlayerSlot {
+        match self.splitter.lock() {
+            Ok(mut s) => s.as_mut().map(|d| d.assign(on, key, velocity)).unwrap_or(MAIN_PLAYER),
+            Err(_) => MAIN_PLAYER
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn players_take_turns_every_notes_per_turn() {
+        let mut splitter = DuetSplitter::new(2);
+        let players: Vec<PlayerSlot> = [60, 62, 64, 65, 67]
+            .iter()
+            .map(|k| {
+                let player = splitter.assign(true, *k, 64);
+                splitter.assign(false, *k, 0);
+                player
+            })
+            .collect();
+        assert_eq!(players, vec![MAIN_PLAYER, MAIN_PLAYER, DUET_PARTNER, DUET_PARTNER, MAIN_PLAYER]);
+    }
+
+    #[test]
+    fn note_off_goes_to_the_player_of_the_note_on() {
+        let mut splitter = DuetSplitter::new(1);
+        assert_eq!(splitter.assign(true, 60, 64), MAIN_PLAYER);
+        assert_eq!(splitter.assign(true, 62, 64), DUET_PARTNER);
+        // A vez ja é do jogador 0 de novo, mas a nota 62 foi do jogador 1
+        assert_eq!(splitter.assign(false, 62, 0), DUET_PARTNER);
+        assert_eq!(splitter.assign(false, 60, 0), MAIN_PLAYER);
+    }
+
+    #[test]
+    fn note_on_without_velocity_is_a_note_off() {
+        let mut splitter = DuetSplitter::new(1);
+        assert_eq!(splitter.assign(true, 60, 64), MAIN_PLAYER);
+        assert_eq!(splitter.assign(true, 60, 0), MAIN_PLAYER);
+        assert_eq!(splitter.assign(true, 62, 64), DUET_PARTNER);
+        assert_eq!(splitter.assign(true, 62, 0), DUET_PARTNER);
+        assert_eq!(splitter.assign(true, 64, 64), MAIN_PLAYER);
+
+        let state = DuetState::default();
+        state.enable(2).unwrap();
+        let players: Vec<PlayerSlot> = [60, 62, 64]
+            .iter()
+            .map(|k| {
+                let player = state.assign(true, *k, 80);
+                assert_eq!(state.assign(true, *k, 0), player);
+                player
+            })
+            .collect();
+        assert_eq!(players, vec![MAIN_PLAYER, MAIN_PLAYER, DUET_PARTNER]);
+    }
+
+    #[test]
+    fn orphan_note_off_goes_to_the_current_turn() {
+        let mut splitter = DuetSplitter::new(1);
+        splitter.assign(true, 60, 64);
+        splitter.assign(true, 62, 64);
+        assert_eq!(splitter.assign(false, 70, 0), DUET_PARTNER);
+    }
+
+    #[test]
+    fn state_rejects_zero_notes_per_turn_and_restarts_on_player_zero() {
+        let state = DuetState::default();
+        assert!(state.enable(0).is_err());
+        assert_eq!(state.assign(true, 60, 64), MAIN_PLAYER);
+        state.enable(1).unwrap();
+        assert_eq!(state.assign(true, 60, 64), MAIN_PLAYER);
+        assert_eq!(state.assign(true, 62, 64), DUET_PARTNER);
+        state.restart();
+        assert_eq!(state.assign(true, 64, 64), MAIN_PLAYER);
+        state.disable();
+        assert_eq!(state.notes_per_turn(), None);
+        assert_eq!(state.assign(true, 65, 64), MAIN_PLAYER);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::commands::ServiceResult;
+use crate::constants::errors::STATE_ACQUIRE_ERROR;
+use std::sync::Mutex;
+
+/// Pausa automatica do jogo quando o dispositivo desconecta no meio da musica.
+///
+/// Cada interrupcao recebe um id, assim uma contagem para voltar o jogo sabe
+/// se a interrupcao dela ainda é a atual.
+#[derive(Default)]
+pub struct GameInterruptionState {
+    interruption: Mutex<Interruption>,
+}
+
+#[derive(Default)]
+struct Interruption {
+    /// Segundos de contagem antes de voltar sozinho, [None] espera o usuario
+    auto_resume: Option<u32>,
+    current: Option<u64>,
+    next_id: u64,
+}
+
+impl GameInterruptionState {
+    pub fn set_auto_resume(&self, countdown: Option<u32>) -> ServiceResult<()> {
+        if let Ok(mut i) = self.interruption.lock() {
+            i.auto_resume = countdown;
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn auto_resume(&self) -> Option<u32> {
+        match self.interruption.lock() {
+            Ok(i) => i.auto_resume,
+            Err(_) => None
+        }
+    }
+
+    /// Começa uma interrupcao, retorna o id dela
+    pub fn begin(&self) -> ServiceResult<u64> {
+        if let Ok(mut i) = self.interruption.lock() {
+            i.next_id += 1;
+            let id = i.next_id;
+            i.current = Some(id);
+            Ok(id)
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn current(&self) -> Option<u64> {
+        match self.interruption.lock() {
+            Ok(i) => i.current,
+            Err(_) => None
+        }
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.current().is_some()
+    }
+
+    /// Termina a interrupcao atual, retorna false se nao tinha nenhuma
+    pub fn finish(&self) -> bool {
+        match self.interruption.lock() {
+            Ok(mut i) => i.current.take().is_some(),
+            Err(_) => false
+        }
+    }
+}
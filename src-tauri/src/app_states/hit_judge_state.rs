@@ -0,0 +1,330 @@
+use crate::app_states::midi_device_state::PlayerSlot;
+use crate::commands::{HitWindows, OnNotePrecision, ServiceResult};
+use crate::constants::errors::STATE_ACQUIRE_ERROR;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Julga as notas tocadas pelos jogadores contra as notas da partitura.
+///
+/// A nota da partitura chega no momento em que deve ser tocada e fica aberta ate a
+/// [HitWindows::left_ms] fechar, valendo o melhor toque do jogador nela. Um toque
+/// `Middle` fecha a nota na hora. Uma nota tocada antes da partitura fica guardada ate
+/// a [HitWindows::early_miss_ms], e uma nota da partitura que ninguem tocou vira
+/// [OnNotePrecision::Miss].
+#[derive(Default)]
+pub struct HitJudgeState {
+    windows: Mutex<HitWindows>,
+    players: Mutex<HashMap<PlayerSlot, PlayerJudge>>,
+}
+
+/// Resultado de uma nota da partitura
+#[derive(Debug, Clone, Copy)]
+pub struct Judgement {
+    pub player: PlayerSlot,
+    pub key: u8,
+    pub precision: OnNotePrecision,
+}
+
+#[derive(Default)]
+struct PlayerJudge {
+    /// Notas da partitura esperando a janela fechar
+    chart: VecDeque<ChartNote>,
+    /// Notas tocadas antes da partitura chegar
+    played: VecDeque<(u8, Instant)>,
+}
+
+struct ChartNote {
+    key: u8,
+    at: Instant,
+    /// Melhor toque recebido ate agora
+    best: Option<OnNotePrecision>,
+}
+
+/// Quanto menor melhor, usado para escolher o melhor toque de uma nota
+fn rank(precision: OnNotePrecision) -> u8 {
+    match precision {
+        OnNotePrecision::Middle => 0,
+        OnNotePrecision::Left | OnNotePrecision::Right => 1,
+        OnNotePrecision::EarlyMiss => 2,
+        OnNotePrecision::Miss => 3,
+    }
+}
+
+impl HitWindows {
+    /// Precisao de uma nota tocada em `played_at` para a nota da partitura em `chart_at`,
+    /// [None] se estiver fora de todas as janelas
+    fn classify(&self, chart_at: Instant, played_at: Instant) -> Option<OnNotePrecision> {
+        let ms = |d: Duration| d.as_millis() as u64;
+        if played_at >= chart_at {
+            let late = ms(played_at - chart_at);
+            if late <= self.middle_ms as u64 {
+                Some(OnNotePrecision::Middle)
+            } else if late <= self.left_ms as u64 {
+                Some(OnNotePrecision::Left)
+            } else {
+                None
+            }
+        } else {
+            let early = ms(chart_at - played_at);
+            if early <= self.middle_ms as u64 {
+                Some(OnNotePrecision::Middle)
+            } else if early <= self.right_ms as u64 {
+                Some(OnNotePrecision::Right)
+            } else if early <= self.early_miss_ms as u64 {
+                Some(OnNotePrecision::EarlyMiss)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+impl PlayerJudge {
+    /// Fecha as notas da partitura cuja janela ja passou com o melhor toque delas, ou
+    /// [OnNotePrecision::Miss] sem nenhum toque, e descarta os toques que ficaram velhos
+    fn expire(&mut self, windows: &HitWindows, now: Instant) -> Vec<(u8, OnNotePrecision)> {
+        let late = Duration::from_millis(windows.left_ms as u64);
+        let early = Duration::from_millis(windows.early_miss_ms as u64);
+        self.played.retain(|(_, at)| now.saturating_duration_since(*at) <= early);
+        let mut closed = vec![];
+        self.chart.retain(|note| {
+            let expired = now.saturating_duration_since(note.at) > late;
+            if expired {
+                closed.push((note.key, note.best.unwrap_or(OnNotePrecision::Miss)));
+            }
+            !expired
+        });
+        closed
+    }
+
+    fn chart_note(&mut self, windows: &HitWindows, key: u8, at: Instant) -> Option<OnNotePrecision> {
+        let found = self
+            .played
+            .iter()
+            .enumerate()
+            .filter(|(_, (k, _))| *k == key)
+            .filter_map(|(i, (_, played_at))| Some((i, windows.classify(at, *played_at)?)))
+            .min_by_key(|(_, precision)| rank(*precision));
+        if let Some((i, _)) = found {
+            self.played.remove(i);
+        }
+        let best = found.map(|(_, precision)| precision);
+        if best == Some(OnNotePrecision::Middle) {
+            return best;
+        }
+        self.chart.push_back(ChartNote { key, at, best });
+        None
+    }
+
+    fn played_note(&mut self, windows: &HitWindows, key: u8, at: Instant) -> Option<OnNotePrecision> {
+        let found = self
+            .chart
+            .iter()
+            .enumerate()
+            .filter(|(_, note)| note.key == key)
+            .filter_map(|(i, note)| Some((i, windows.classify(note.at, at)?)))
+            .min_by_key(|(_, precision)| rank(*precision));
+        let Some((i, precision)) = found else {
+            self.played.push_back((key, at));
+            return None;
+        };
+        if precision == OnNotePrecision::Middle {
+            self.chart.remove(i);
+            return Some(precision);
+        }
+        let note = &mut self.chart[i];
+        if note.best.is_none_or(|best| rank(precision) < rank(best)) {
+            note.best = Some(precision);
+        }
+        None
+    }
+}
+
+impl HitJudgeState {
+    pub fn set_windows(&self, windows: HitWindows) -> ServiceResult<()> {
+        if let Ok(mut w) = self.windows.lock() {
+            *w = windows;
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn windows(&self) -> ServiceResult<HitWindows> {
+        if let Ok(w) = self.windows.lock() {
+            Ok(*w)
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Nota da partitura que o `player` deve tocar agora
+    pub fn chart_note(&self, player: PlayerSlot, key: u8) -> Vec<Judgement> {
+        let now = Instant::now();
+        self.judge(player, now, |judge, windows| judge.chart_note(windows, key, now).map(|p| (key, p)))
+    }
+
+    /// Nota tocada pelo `player` em `played_at`, ja descontada a latencia do dispositivo
+    pub fn played_note(&self, player: PlayerSlot, key: u8, played_at: Instant) -> Vec<Judgement> {
+        self.judge(player, Instant::now(), |judge, windows| {
+            judge.played_note(windows, key, played_at).map(|p| (key, p))
+        })
+    }
+
+    /// Fecha as notas da partitura de todos os jogadores cuja janela ja passou, chamado
+    /// em intervalos durante a musica para o [OnNotePrecision::Miss] sair na hora
+    pub fn expire(&self) -> Vec<Judgement> {
+        let Ok(windows) = self.windows() else {
+            return vec![];
+        };
+        let Ok(mut players) = self.players.lock() else {
+            return vec![];
+        };
+        let now = Instant::now();
+        players
+            .iter_mut()
+            .flat_map(|(player, judge)| {
+                judge.expire(&windows, now).into_iter().map(|(key, precision)| Judgement {
+                    player: *player,
+                    key,
+                    precision,
+                })
+            })
+            .collect()
+    }
+
+    /// Termina a musica, as notas da partitura que ainda esperavam ficam com o melhor
+    /// toque delas ou viram [OnNotePrecision::Miss]
+    pub fn finish(&self) -> Vec<Judgement> {
+        let Ok(mut players) = self.players.lock() else {
+            return vec![];
+        };
+        let judgements = players
+            .iter()
+            .flat_map(|(player, judge)| {
+                judge.chart.iter().map(|note| Judgement {
+                    player: *player,
+                    key: note.key,
+                    precision: note.best.unwrap_or(OnNotePrecision::Miss),
+                })
+            })
+            .collect();
+        players.clear();
+        judgements
+    }
+
+    /// Esquece as notas esperando sem julgar, usado quando a musica pausa
+    pub fn clear(&self) {
+        if let Ok(mut players) = self.players.lock() {
+            players.clear();
+        }
+    }
+
+    fn judge(
+        &self,
+        player: PlayerSlot,
+        now: Instant,
+        judge: impl FnOnce(&mut PlayerJudge, &HitWindows) -> Option<(u8, OnNotePrecision)>,
+    ) -> Vec<Judgement> {
+        let Ok(windows) = self.windows() else {
+            return vec![];
+        };
+        let Ok(mut players) = self.players.lock() else {
+            return vec![];
+        };
+        let player_judge = players.entry(player).or_default();
+        let mut judged = player_judge.expire(&windows, now);
+        judged.extend(judge(player_judge, &windows));
+        judged
+            .into_iter()
+            .map(|(key, precision)| Judgement { player, key, precision })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn classify_follows_the_windows() {
+        let windows = HitWindows::default();
+        let chart = Instant::now() + ms(1_000);
+        let classify = |played: Instant| windows.classify(chart, played);
+        assert_eq!(classify(chart), Some(OnNotePrecision::Middle));
+        assert_eq!(classify(chart + ms(60)), Some(OnNotePrecision::Middle));
+        assert_eq!(classify(chart - ms(60)), Some(OnNotePrecision::Middle));
+        assert_eq!(classify(chart + ms(100)), Some(OnNotePrecision::Left));
+        assert_eq!(classify(chart + ms(150)), Some(OnNotePrecision::Left));
+        assert_eq!(classify(chart + ms(151)), None);
+        assert_eq!(classify(chart - ms(100)), Some(OnNotePrecision::Right));
+        assert_eq!(classify(chart - ms(200)), Some(OnNotePrecision::EarlyMiss));
+        assert_eq!(classify(chart - ms(300)), Some(OnNotePrecision::EarlyMiss));
+        assert_eq!(classify(chart - ms(301)), None);
+    }
+
+    #[test]
+    fn middle_press_closes_the_note_at_once() {
+        let windows = HitWindows::default();
+        let start = Instant::now();
+        let mut judge = PlayerJudge::default();
+        assert_eq!(judge.chart_note(&windows, 60, start), None);
+        assert_eq!(judge.played_note(&windows, 60, start + ms(30)), Some(OnNotePrecision::Middle));
+        assert!(judge.chart.is_empty());
+
+        // tocada antes da partitura chegar
+        assert_eq!(judge.played_note(&windows, 62, start + ms(970)), None);
+        assert_eq!(judge.chart_note(&windows, 62, start + ms(1_000)), Some(OnNotePrecision::Middle));
+        assert!(judge.played.is_empty());
+    }
+
+    #[test]
+    fn note_stays_open_and_keeps_the_best_press() {
+        let windows = HitWindows::default();
+        let start = Instant::now();
+        let mut judge = PlayerJudge::default();
+        judge.played_note(&windows, 60, start);
+        judge.played_note(&windows, 60, start + ms(150));
+        assert_eq!(judge.chart_note(&windows, 60, start + ms(250)), None);
+        assert_eq!(judge.chart[0].best, Some(OnNotePrecision::Right));
+        assert_eq!(judge.played_note(&windows, 60, start + ms(350)), None);
+        assert_eq!(judge.chart[0].best, Some(OnNotePrecision::Right));
+        assert_eq!(judge.expire(&windows, start + ms(400)), vec![]);
+        assert_eq!(judge.expire(&windows, start + ms(401)), vec![(60, OnNotePrecision::Right)]);
+
+        assert_eq!(judge.chart_note(&windows, 62, start + ms(1_000)), None);
+        judge.played_note(&windows, 62, start + ms(1_120));
+        assert_eq!(judge.played_note(&windows, 62, start + ms(1_050)), Some(OnNotePrecision::Middle));
+        assert!(judge.chart.is_empty());
+    }
+
+    #[test]
+    fn expire_misses_unplayed_notes_and_drops_old_presses() {
+        let windows = HitWindows::default();
+        let start = Instant::now();
+        let mut judge = PlayerJudge::default();
+        judge.played_note(&windows, 64, start);
+        judge.chart_note(&windows, 60, start);
+        judge.chart_note(&windows, 62, start + ms(100));
+        assert_eq!(judge.expire(&windows, start + ms(200)), vec![(60, OnNotePrecision::Miss)]);
+        assert_eq!(judge.expire(&windows, start + ms(301)), vec![(62, OnNotePrecision::Miss)]);
+        assert!(judge.played.is_empty());
+        assert_eq!(judge.chart_note(&windows, 64, start + ms(310)), None);
+    }
+
+    #[test]
+    fn state_expires_every_player() {
+        let state = HitJudgeState::default();
+        state.chart_note(0, 60);
+        state.chart_note(1, 62);
+        assert!(state.expire().is_empty());
+        let finished = state.finish();
+        assert_eq!(finished.len(), 2);
+        assert!(finished.iter().all(|j| j.precision == OnNotePrecision::Miss));
+    }
+}
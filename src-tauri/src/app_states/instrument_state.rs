@@ -0,0 +1,85 @@
+use crate::app_states::profile_loader::{load_profiles, with_default_first};
+use crate::commands::ServiceResult;
+use crate::constants::errors::{INSTRUMENT_NOT_FOUND, STATE_ACQUIRE_ERROR};
+use arduino_comm::instrument_profile::{InstrumentNote, InstrumentProfile};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Instrumentos disponiveis e qual deles esta sendo usado.
+///
+/// O instrumento escolhido pelo usuario tem prioridade sobre o do dispositivo
+/// conectado, se nenhum for escolhido usa o [InstrumentProfile::default].
+pub struct InstrumentState {
+    profiles: Vec<InstrumentProfile>,
+    device_instrument: Mutex<Option<String>>,
+    user_instrument: Mutex<Option<String>>,
+}
+
+impl Default for InstrumentState {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+impl InstrumentState {
+    pub fn new(profiles: Vec<InstrumentProfile>) -> Self {
+        Self {
+            profiles: with_default_first(profiles),
+            device_instrument: Mutex::new(None),
+            user_instrument: Mutex::new(None),
+        }
+    }
+
+    /// Instrumentos da pasta de recursos, o padrao existe mesmo sem arquivo
+    pub fn load(dir: &Path) -> Self {
+        Self::new(load_profiles(dir))
+    }
+
+    pub fn profiles(&self) -> &[InstrumentProfile] {
+        &self.profiles
+    }
+
+    pub fn set_device_instrument(&self, instrument_id: Option<String>) -> ServiceResult<()> {
+        if let Ok(mut d) = self.device_instrument.lock() {
+            *d = instrument_id;
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn set_user_instrument(&self, instrument_id: Option<String>) -> ServiceResult<()> {
+        if let Some(id) = instrument_id.as_ref() {
+            self.profile(id)?;
+        }
+        if let Ok(mut u) = self.user_instrument.lock() {
+            *u = instrument_id;
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn profile(&self, instrument_id: &str) -> ServiceResult<&InstrumentProfile> {
+        self.profiles
+            .iter()
+            .find(|p| p.id == instrument_id)
+            .ok_or(INSTRUMENT_NOT_FOUND.into())
+    }
+
+    pub fn active(&self) -> ServiceResult<InstrumentProfile> {
+        let (Ok(user), Ok(device)) = (self.user_instrument.lock(), self.device_instrument.lock()) else {
+            return Err(STATE_ACQUIRE_ERROR.into());
+        };
+        let selected = user.as_ref().or(device.as_ref());
+        let profile = selected
+            .and_then(|id| self.profiles.iter().find(|p| &p.id == id))
+            .unwrap_or(&self.profiles[0]);
+        Ok(profile.clone())
+    }
+
+    /// Nota do instrumento ativo, erro se ele nao tiver essa nota
+    pub fn note(&self, key: u8) -> ServiceResult<InstrumentNote> {
+        Ok(self.active()?.note(key)?.clone())
+    }
+}
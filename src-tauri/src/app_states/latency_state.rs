@@ -0,0 +1,101 @@
+use crate::app_states::midi_device_state::PlayerSlot;
+use crate::commands::ServiceResult;
+use crate::constants::errors::{DEVICE_LATENCY_NOT_CALIBRATING, STATE_ACQUIRE_ERROR};
+use arduino_comm::latency::LatencyCalibration;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Prefixo da chave da latencia de cada dispositivo no store
+const LATENCY_KEY: &str = "__latency_";
+
+/// Latencia de entrada do dispositivo de cada jogador e a calibracao em andamento.
+///
+/// A latencia é descontada do momento em que cada nota chega, antes de comparar
+/// com o relogio da musica. So um jogador calibra por vez.
+#[derive(Default)]
+pub struct LatencyState {
+    offsets: Mutex<HashMap<PlayerSlot, Duration>>,
+    calibration: Mutex<Option<(PlayerSlot, Instant, LatencyCalibration)>>,
+}
+
+impl LatencyState {
+    /// Chave da latencia de um dispositivo no store, em milissegundos
+    pub fn key(port_name: &str) -> String {
+        format!("{}{}", LATENCY_KEY, port_name)
+    }
+
+    pub fn set_offset(&self, player: PlayerSlot, offset: Duration) -> ServiceResult<()> {
+        if let Ok(mut o) = self.offsets.lock() {
+            o.insert(player, offset);
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn offset(&self, player: PlayerSlot) -> Duration {
+        match self.offsets.lock() {
+            Ok(o) => o.get(&player).copied().unwrap_or_default(),
+            Err(_) => Duration::ZERO
+        }
+    }
+
+    /// Momento em que a nota do jogador foi tocada, descontando a latencia
+    pub fn played_at(&self, player: PlayerSlot, received_at: Instant) -> Instant {
+        received_at.checked_sub(self.offset(player)).unwrap_or(received_at)
+    }
+
+    /// Começa uma calibracao do jogador agora, retorna o inicio dela
+    pub fn start_calibration(&self, player: PlayerSlot, calibration: LatencyCalibration) -> ServiceResult<Instant> {
+        if let Ok(mut c) = self.calibration.lock() {
+            let started_at = Instant::now();
+            *c = Some((player, started_at, calibration));
+            Ok(started_at)
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Se a calibracao que começou em `started_at` ainda esta rodando
+    pub fn is_calibrating(&self, started_at: Instant) -> bool {
+        match self.calibration.lock() {
+            Ok(c) => c.as_ref().is_some_and(|(_, s, _)| *s == started_at),
+            Err(_) => false
+        }
+    }
+
+    /// Recebe o momento em que uma nota do jogador chegou, sem a latencia descontada
+    pub fn record_tap(&self, player: PlayerSlot, received_at: Instant) {
+        if let Ok(mut c) = self.calibration.lock() {
+            if let Some((calibrating, started_at, calibration)) = c.as_mut() {
+                if *calibrating == player {
+                    calibration.record_tap(received_at.saturating_duration_since(*started_at));
+                }
+            }
+        }
+    }
+
+    /// Termina a calibracao e passa a usar a latencia medida, retorna o jogador calibrado
+    pub fn finish_calibration(&self) -> ServiceResult<(PlayerSlot, Duration)> {
+        let (player, offset) = if let Ok(mut c) = self.calibration.lock() {
+            match c.take() {
+                Some((player, _, calibration)) => (player, calibration.offset()?),
+                None => return Err(DEVICE_LATENCY_NOT_CALIBRATING.into())
+            }
+        } else {
+            return Err(STATE_ACQUIRE_ERROR.into());
+        };
+        self.set_offset(player, offset)?;
+        Ok((player, offset))
+    }
+
+    /// Cancela a calibracao se ela for do jogador
+    pub fn cancel_calibration(&self, player: PlayerSlot) {
+        if let Ok(mut c) = self.calibration.lock() {
+            if c.as_ref().is_some_and(|(calibrating, _, _)| *calibrating == player) {
+                c.take();
+            }
+        }
+    }
+}
@@ -1,18 +1,51 @@
 use crate::commands::{MidiFileState, ServiceResult};
-use crate::constants::errors::{DEVICE_ALREADY_CONNECTED, DEVICE_NOT_CONNECTED, FILE_ALREADY_PLAYING, NO_FILE_BEING_PLAYED, STATE_ACQUIRE_ERROR};
-use arduino_comm::midi_connection::{ConnectionHolder, MidiConnection};
+use crate::constants::errors::{DEVICE_ALREADY_CONNECTED, DEVICE_NOT_CONNECTED, DEVICE_THRU_NOT_ENABLED, FILE_ALREADY_PLAYING, NO_FILE_BEING_PLAYED, STATE_ACQUIRE_ERROR};
+use arduino_comm::breath_pressure::PressureSampler;
+use arduino_comm::device_config::{connect_sysex_output, DeviceConfigClient, DeviceResponses};
+use arduino_comm::device_profile::DeviceProfile;
+use arduino_comm::device_watcher::{DevicePort, DeviceWatcher};
+use arduino_comm::input_filter::InputFilterHandle;
+use arduino_comm::input_recording::InputRecorder;
+use arduino_comm::input_source::{start_listening, ConnectionHolder, InputSource};
+use arduino_comm::midi_subscriptions::MidiSubscriptions;
+use arduino_comm::midi_thru::{MidiThru, ThruConfig};
 use arduino_comm::midi_wrapper::MidiWrapper;
+use arduino_comm::osc::OscOutput;
+use arduino_comm::virtual_input::VirtualInputSender;
+use midi_reader::backing_track::{BackingTrack, SharedMixer};
 use midi_reader::midi_file::{MidiFile, MidiFilePlayer, PlayBackCallback, ReadingState};
 use midi_reader::player_wrapper::PlayerWrapper;
 use paris::{error, info, success, warn};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::sync::Mutex;
 use std::time::Duration;
 
+/// Jogador dono de um dispositivo, cada jogador tem no maximo um dispositivo conectado
+pub type PlayerSlot = u8;
+/// Jogador usado quando so um dispositivo é conectado
+pub const MAIN_PLAYER: PlayerSlot = 0;
+
 #[derive(Default)]
 pub struct MidiState {
-    midi_input_conn: Mutex<Option<MidiConnection>>,
-    midi_connection_holder: Mutex<Option<ConnectionHolder>>,
+    midi_input_conn: Mutex<HashMap<PlayerSlot, Box<dyn InputSource>>>,
+    midi_connection_holder: Mutex<HashMap<PlayerSlot, ConnectionHolder>>,
     midi_file: Mutex<Option<(MidiFile, i32)>>,
+    midi_thru: Mutex<Option<MidiThru>>,
+    /// Saida OSC das notas e da pressao de todos os jogadores
+    osc_output: Mutex<Option<OscOutput>>,
+    pressure_sampler: Mutex<HashMap<PlayerSlot, PressureSampler>>,
+    device_profile: Mutex<HashMap<PlayerSlot, DeviceProfile>>,
+    input_filter: Mutex<HashMap<PlayerSlot, InputFilterHandle>>,
+    /// SysEx do firmware recebidas de cada dispositivo conectado
+    device_responses: Mutex<HashMap<PlayerSlot, DeviceResponses>>,
+    virtual_input: Mutex<HashMap<PlayerSlot, VirtualInputSender>>,
+    input_recorder: InputRecorder,
+    /// Nome do dispositivo de cada jogador e a janela que recebe os eventos dele
+    connected_device: Mutex<HashMap<PlayerSlot, (String, String)>>,
+    /// Ultima porta usada por cada jogador, reaberta quando o dispositivo volta
+    reconnect_target: Mutex<HashMap<PlayerSlot, (DevicePort, String)>>,
+    device_watcher: Mutex<Option<DeviceWatcher>>,
 }
 
 impl MidiState {
@@ -20,10 +53,10 @@ impl MidiState {
         Self::default()
     }
 
-    fn check_connection(&self) -> ServiceResult<()> {
+    fn check_connection(&self, player: PlayerSlot) -> ServiceResult<()> {
         match self.midi_connection_holder.lock() {
             Ok(c) => {
-                if c.is_some() {
+                if c.contains_key(&player) {
                     Err(DEVICE_ALREADY_CONNECTED.into())
                 } else {
                     Ok(())
@@ -33,17 +66,50 @@ impl MidiState {
         }
     }
 
+    /// Se algum jogador tem um dispositivo conectado
     pub fn has_connection(&self) -> bool {
         match self.midi_connection_holder.lock() {
-            Ok(c) => c.is_some(),
+            Ok(c) => !c.is_empty(),
+            Err(_) => false
+        }
+    }
+
+    pub fn has_player_connection(&self, player: PlayerSlot) -> bool {
+        match self.midi_connection_holder.lock() {
+            Ok(c) => c.contains_key(&player),
+            Err(_) => false
+        }
+    }
+
+    /// Jogadores com dispositivo conectado, em ordem
+    pub fn connected_players(&self) -> Vec<PlayerSlot> {
+        match self.midi_connection_holder.lock() {
+            Ok(c) => {
+                let mut players: Vec<PlayerSlot> = c.keys().copied().collect();
+                players.sort();
+                players
+            },
+            Err(_) => vec![]
+        }
+    }
+
+    /// Se a fonte do jogador ainda esta enviando dados, ver [ConnectionHolder::is_alive]
+    pub fn is_connection_alive(&self, player: PlayerSlot) -> bool {
+        match self.midi_connection_holder.lock() {
+            Ok(c) => c.get(&player).is_some_and(|h| h.is_alive()),
             Err(_) => false
         }
     }
 
-    pub fn connected_port_name(&self) -> ServiceResult<String> {
-        if let Ok(con) = self.midi_input_conn.lock() {
-            if let Some(c) = con.as_ref() {
-                Ok(c.port_name.to_owned())
+    pub fn connected_port_name(&self, player: PlayerSlot) -> ServiceResult<String> {
+        Ok(self.connected_device(player)?.0)
+    }
+
+    /// Nome e janela do dispositivo do jogador
+    pub fn connected_device(&self, player: PlayerSlot) -> ServiceResult<(String, String)> {
+        if let Ok(d) = self.connected_device.lock() {
+            if let Some(device) = d.get(&player) {
+                Ok(device.clone())
             } else {
                 Err(DEVICE_NOT_CONNECTED.into())
             }
@@ -52,15 +118,87 @@ impl MidiState {
         }
     }
 
-    pub fn start_listening_to_device<C: Fn(MidiWrapper) + Send + 'static>(&self, callback: C) -> ServiceResult<()> {
+    /// Jogador que esta usando o dispositivo com esse nome
+    pub fn player_with_device(&self, port_name: &str) -> Option<PlayerSlot> {
+        match self.connected_device.lock() {
+            Ok(d) => d.iter().find(|(_, (name, _))| name == port_name).map(|(p, _)| *p),
+            Err(_) => None
+        }
+    }
+
+    /// Guarda o dispositivo que acabou de conectar, fontes sem [DevicePort] nao sao reconectadas
+    pub fn set_connected_device(
+        &self,
+        player: PlayerSlot,
+        port_name: String,
+        window_label: String,
+        port: Option<DevicePort>
+    ) -> ServiceResult<()> {
+        if let (Ok(mut d), Ok(mut r)) = (self.connected_device.lock(), self.reconnect_target.lock()) {
+            match port {
+                Some(p) => r.insert(player, (p, window_label.clone())),
+                None => r.remove(&player)
+            };
+            d.insert(player, (port_name, window_label));
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Jogador, porta e janela que devem ser reabertos quando o dispositivo com esse nome voltar
+    pub fn reconnect_target(&self, port_name: &str) -> Option<(PlayerSlot, DevicePort, String)> {
+        match self.reconnect_target.lock() {
+            Ok(r) => r
+                .iter()
+                .find(|(_, (port, _))| port.name() == port_name)
+                .map(|(player, (port, label))| (*player, port.clone(), label.clone())),
+            Err(_) => None
+        }
+    }
+
+    /// Jogadores que perderam o dispositivo e ainda esperam ele voltar
+    pub fn missing_players(&self) -> Vec<PlayerSlot> {
+        let waiting: Vec<PlayerSlot> = match self.reconnect_target.lock() {
+            Ok(r) => r.keys().copied().collect(),
+            Err(_) => return vec![]
+        };
+        waiting
+            .into_iter()
+            .filter(|p| !self.has_player_connection(*p))
+            .collect()
+    }
+
+    /// Esquece a ultima porta do jogador, usado quando o usuario desconecta
+    pub fn forget_reconnect_target(&self, player: PlayerSlot) {
+        if let Ok(mut r) = self.reconnect_target.lock() {
+            r.remove(&player);
+        }
+    }
+
+    pub fn set_device_watcher(&self, watcher: DeviceWatcher) -> ServiceResult<()> {
+        if let Ok(mut w) = self.device_watcher.lock() {
+            *w = Some(watcher);
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn start_listening_to_device(
+        &self,
+        player: PlayerSlot,
+        subscriptions: MidiSubscriptions
+    ) -> ServiceResult<()> {
         if let Ok(mut m) = self.midi_input_conn.lock() {
-            if let Some(c) = m.take() {
+            if let Some(c) = m.remove(&player) {
                 if let Ok(mut m) = self.midi_connection_holder.lock() {
-                    if  m.is_some() {
-                        Err(DEVICE_ALREADY_CONNECTED.into())
-                    } else {
-                        *m = Some(c.start_connection(callback)?);
-                        Ok(())
+                    match m.entry(player) {
+                        Entry::Occupied(_) => Err(DEVICE_ALREADY_CONNECTED.into()),
+                        Entry::Vacant(e) => {
+                            e.insert(start_listening(c, subscriptions)?);
+                            Ok(())
+                        }
                     }
                 } else {
                     Err(STATE_ACQUIRE_ERROR.into())
@@ -73,11 +211,18 @@ impl MidiState {
         }
     }
 
-    pub fn set_device_connection(&self, connection: MidiConnection) -> ServiceResult<()> {
-        self.check_connection()?;
+    pub fn set_device_connection(&self, player: PlayerSlot, connection: Box<dyn InputSource>) -> ServiceResult<()> {
+        self.check_connection(player)?;
         match self.midi_input_conn.lock() {
             Ok(mut m) => {
-                *m = Some(connection);
+                // a conexao do jogador principal passa pelo gravador, assim a gravacao pode ser
+                // ligada a qualquer momento. A gravacao so guarda um dispositivo
+                let connection = if player == MAIN_PLAYER {
+                    self.input_recorder.wrap(connection)
+                } else {
+                    connection
+                };
+                m.insert(player, connection);
                 Ok(())
             }
             Err(_) => {
@@ -86,10 +231,101 @@ impl MidiState {
         }
     }
 
-    pub fn drop_device_connection(&self) {
-        info!("Dropping connection...");
+    /// Guarda o perfil do dispositivo do jogador e o sampler que le a pressao dele
+    pub fn set_pressure_sampler(
+        &self,
+        player: PlayerSlot,
+        profile: DeviceProfile,
+        sampler: PressureSampler
+    ) -> ServiceResult<()> {
+        if let (Ok(mut s), Ok(mut p)) = (self.pressure_sampler.lock(), self.device_profile.lock()) {
+            s.insert(player, sampler);
+            p.insert(player, profile);
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn device_profile(&self, player: PlayerSlot) -> ServiceResult<DeviceProfile> {
+        if let Ok(p) = self.device_profile.lock() {
+            if let Some(profile) = p.get(&player) {
+                Ok(profile.clone())
+            } else {
+                Err(DEVICE_NOT_CONNECTED.into())
+            }
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn set_input_filter(&self, player: PlayerSlot, filter: InputFilterHandle) -> ServiceResult<()> {
+        if let Ok(mut f) = self.input_filter.lock() {
+            f.insert(player, filter);
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Filtro das notas do dispositivo do jogador
+    pub fn input_filter(&self, player: PlayerSlot) -> ServiceResult<InputFilterHandle> {
+        if let Ok(f) = self.input_filter.lock() {
+            if let Some(filter) = f.get(&player) {
+                Ok(filter.clone())
+            } else {
+                Err(DEVICE_NOT_CONNECTED.into())
+            }
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn set_device_responses(&self, player: PlayerSlot, responses: DeviceResponses) -> ServiceResult<()> {
+        if let Ok(mut r) = self.device_responses.lock() {
+            r.insert(player, responses);
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Cliente para ler e gravar a configuracao do firmware do dispositivo do jogador
+    pub fn device_config_client(&self, player: PlayerSlot) -> ServiceResult<DeviceConfigClient> {
+        let responses = if let Ok(r) = self.device_responses.lock() {
+            match r.get(&player) {
+                Some(responses) => responses.clone(),
+                None => return Err(DEVICE_NOT_CONNECTED.into())
+            }
+        } else {
+            return Err(STATE_ACQUIRE_ERROR.into());
+        };
+        let output = connect_sysex_output(&self.connected_port_name(player)?)?;
+        Ok(DeviceConfigClient::new(Box::new(output), responses))
+    }
+
+    pub fn drop_device_connection(&self, player: PlayerSlot) {
+        info!("Dropping connection of player {}...", player);
+        if let Ok(mut s) = self.pressure_sampler.lock() {
+            s.remove(&player);
+        }
+        if let Ok(mut p) = self.device_profile.lock() {
+            p.remove(&player);
+        }
+        if let Ok(mut f) = self.input_filter.lock() {
+            f.remove(&player);
+        }
+        if let Ok(mut r) = self.device_responses.lock() {
+            r.remove(&player);
+        }
+        if let Ok(mut v) = self.virtual_input.lock() {
+            v.remove(&player);
+        }
+        if let Ok(mut d) = self.connected_device.lock() {
+            d.remove(&player);
+        }
         if let Ok(mut ch) = self.midi_connection_holder.lock() {
-            if ch.take().is_none() {
+            if ch.remove(&player).is_none() {
                 warn!("There is no active input device connection...");
             } else {
                 success!("Dropped active input device connection");
@@ -97,6 +333,106 @@ impl MidiState {
         }
     }
     
+    pub fn input_recorder(&self) -> &InputRecorder {
+        &self.input_recorder
+    }
+
+    pub fn set_virtual_input(&self, player: PlayerSlot, sender: VirtualInputSender) -> ServiceResult<()> {
+        if let Ok(mut v) = self.virtual_input.lock() {
+            v.insert(player, sender);
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Sender do dispositivo virtual do jogador, so existe enquanto ele estiver conectado
+    pub fn virtual_input(&self, player: PlayerSlot) -> ServiceResult<VirtualInputSender> {
+        if let Ok(v) = self.virtual_input.lock() {
+            match v.get(&player) {
+                Some(sender) if sender.is_connected() => Ok(sender.clone()),
+                _ => Err(DEVICE_NOT_CONNECTED.into())
+            }
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Troca a saida do midi thru, [None] desliga o thru
+    pub fn set_midi_thru(&self, thru: Option<MidiThru>) -> ServiceResult<()> {
+        if let Ok(mut t) = self.midi_thru.lock() {
+            *t = thru;
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn update_midi_thru_config(&self, config: ThruConfig) -> ServiceResult<()> {
+        if let Ok(mut t) = self.midi_thru.lock() {
+            if let Some(thru) = t.as_mut() {
+                Ok(thru.set_config(config)?)
+            } else {
+                Err(DEVICE_THRU_NOT_ENABLED.into())
+            }
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn midi_thru_config(&self) -> ServiceResult<Option<(String, ThruConfig)>> {
+        if let Ok(t) = self.midi_thru.lock() {
+            Ok(t.as_ref().map(|thru| (thru.port_name.to_owned(), thru.config())))
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Repassa a nota recebida do dispositivo para a saida do thru, caso ele esteja ligado
+    pub fn forward_to_thru(&self, wrapper: &MidiWrapper) {
+        if let Ok(mut t) = self.midi_thru.lock() {
+            if let Some(thru) = t.as_mut() {
+                thru.forward(wrapper);
+            }
+        }
+    }
+
+    /// Troca a saida OSC, [None] desliga
+    pub fn set_osc_output(&self, output: Option<OscOutput>) -> ServiceResult<()> {
+        if let Ok(mut o) = self.osc_output.lock() {
+            *o = output;
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Endereco para onde a saida OSC envia, [None] se ela estiver desligada
+    pub fn osc_output_target(&self) -> ServiceResult<Option<String>> {
+        if let Ok(o) = self.osc_output.lock() {
+            Ok(o.as_ref().map(|output| output.target().to_string()))
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Envia a nota do jogador pela saida OSC, caso ela esteja ligada
+    pub fn forward_note_to_osc(&self, player: PlayerSlot, wrapper: &MidiWrapper) {
+        if let Ok(o) = self.osc_output.lock() {
+            if let Some(output) = o.as_ref() {
+                output.send_note(player, wrapper);
+            }
+        }
+    }
+
+    pub fn forward_pressure_to_osc(&self, player: PlayerSlot, pressure: u8) {
+        if let Ok(o) = self.osc_output.lock() {
+            if let Some(output) = o.as_ref() {
+                output.send_pressure(player, pressure);
+            }
+        }
+    }
+
     pub fn current_midi_file_id(&self) -> ServiceResult<i32> {
         if let Ok(m) = self.midi_file.lock() {
             if let Some((_, id)) = m.as_ref() {
@@ -109,6 +445,16 @@ impl MidiState {
         }
     }
 
+    /// Se a musica esta tocando agora, uma musica pausada retorna false
+    pub fn is_midi_file_running(&self) -> bool {
+        match self.midi_file.lock() {
+            Ok(m) => m
+                .as_ref()
+                .is_some_and(|(file, _)| matches!(file.current_state(), ReadingState::Playing)),
+            Err(_) => false
+        }
+    }
+
     pub fn is_playing_midi_file(&self) -> ServiceResult<()> {
         if let Ok(m) = self.midi_file.lock() {
             if let Some((file, _)) = m.as_ref() {
@@ -130,7 +476,9 @@ impl MidiState {
         &self, 
         music_id: i32,
         bytes: Vec<u8>,
-        playback: P
+        playback: P,
+        mixer: SharedMixer,
+        backing: Option<BackingTrack>
     ) -> ServiceResult<PlayerWrapper<P>> {
         if let Ok(mut f) = self.midi_file.lock() {
             let m = MidiFile::from_bytes_vector(bytes)?;
@@ -139,7 +487,7 @@ impl MidiState {
                 .as_mut()
                 .unwrap()
                 .0
-                .create_sheet_player(playback)?;
+                .create_sheet_player_with_backing(playback, mixer, backing)?;
             Ok(player_wrapper)
         } else {
             error!("{}", STATE_ACQUIRE_ERROR.message);
@@ -1,5 +1,18 @@
+pub mod backing_track_state;
+pub mod calibration_state;
 pub mod current_music_score_state;
 pub mod midi_device_state;
 pub mod database_state;
+pub mod duet_state;
+pub mod game_interruption_state;
+pub mod hit_judge_state;
+pub mod instrument_state;
+pub mod latency_state;
+pub mod scoring_state;
 pub mod store_state;
+pub mod sustain_state;
 pub mod monitoring_state;
+pub mod performance_state;
+pub mod profile_loader;
+pub mod song_editor_state;
+pub mod user_state;
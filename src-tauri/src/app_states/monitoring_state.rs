@@ -1,18 +1,23 @@
+use crate::app_states::midi_device_state::{PlayerSlot, MAIN_PLAYER};
 use crate::commands::OnNotePrecision;
 use anyhow::{anyhow, Error};
-use std::collections::VecDeque;
-use std::ops::DerefMut;
+use arduino_comm::midi_wrapper::MidiWrapper;
+use arduino_comm::note::Note;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Mutex;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant};
 
 const BUFFER_SIZE: usize = 3;
+/// 20 minutos de amostras a 50Hz, evita crescer sem limite com o dispositivo parado
+const BREATH_CURVE_LIMIT: usize = 60_000;
 const DEFAULT_ERROR_MSG: &str = "Unexpected error while updating monitor data";
 
 type MonitorResult<T> = Result<T, Error>;
 
+/// Dados de monitoramento de cada jogador da musica atual
 #[derive(Default)]
 pub struct MonitoringState {
-    pub(crate) data: Mutex<Option<MonitoringData>>
+    pub(crate) data: Mutex<HashMap<PlayerSlot, MonitoringData>>
 }
 
 pub(crate) struct MonitoringData {
@@ -26,42 +31,126 @@ pub(crate) struct MonitoringData {
     pub average_strength: i32,
     pub breath_strength_buffer: VecDeque<u8>,
     pub current_breath_strengths: Vec<u8>,
-    pub breath_start_time: Duration,
+    /// Tempo do dispositivo em que o sopro atual começou
+    pub breath_start_time: Option<Duration>,
+    /// Notas ligadas no sopro atual, o sopro acaba quando todas desligam
+    pub held_notes: HashSet<u8>,
+    /// Se o dispositivo ja mandou alguma nota, senao a duracao dos sopros vem da pressao
+    pub breaths_from_notes: bool,
+    /// Momento da primeira amostra de pressao do sopro atual
+    pub pressure_breath_start: Option<Instant>,
+    pub total_strength_sum: u64,
+    pub total_strength_samples: u64,
+    /// Amostras de pressao recebidas em taxa fixa
+    pub breath_curve: Vec<u8>,
+    /// Vezes que o jogo pausou porque o dispositivo desconectou
+    pub total_interruptions: u32,
+    pub interrupted_time: Duration,
+    pub interruption_start_time: Option<Instant>,
+    /// Soma da parte segurada de cada nota avaliada, de 0 a 1
+    pub total_sustain: f32,
+    pub sustained_notes: u32,
+    /// Soma da estabilidade de cada nota com amostras suficientes, de 0 a 1
+    pub total_steadiness: f32,
+    pub steadiness_notes: u32,
+    /// Soma da parte de cada nota com a pressao dentro da zona alvo, de 0 a 1
+    pub total_time_in_zone: f32,
+    pub zone_notes: u32,
 }
 
 impl MonitoringState {
-    fn lock(&self, lock: impl Fn(&mut MonitoringData) -> MonitorResult<()>) -> MonitorResult<()> {
+    fn lock(&self, player: PlayerSlot, lock: impl Fn(&mut MonitoringData) -> MonitorResult<()>) -> MonitorResult<()> {
         if let Ok(mut data) = self.data.lock() {
-            let d = if let Some(d) = data.deref_mut() {
-                d
-            } else {
-                *data = Some(MonitoringData::default());
-                let Some(a) = data.deref_mut() else {
-                    return Err(anyhow!(DEFAULT_ERROR_MSG));
-                };
-                a
-            };
-            lock(d)
+            lock(data.entry(player).or_default())
         } else {
             Err(anyhow!(DEFAULT_ERROR_MSG))
         }
     }
-    pub fn receive_breath_data(&self, strength_byte: u8, state: bool) -> MonitorResult<()> {
-        self.lock(move |data| {
-            data.receive_breath_data(strength_byte, state);
+
+    /// Aplica em todos os jogadores, o [MAIN_PLAYER] sempre existe
+    fn lock_all(&self, lock: impl Fn(&mut MonitoringData) -> MonitorResult<()>) -> MonitorResult<()> {
+        if let Ok(mut data) = self.data.lock() {
+            data.entry(MAIN_PLAYER).or_default();
+            data.values_mut().try_for_each(lock)
+        } else {
+            Err(anyhow!(DEFAULT_ERROR_MSG))
+        }
+    }
+
+    /// Recebe uma nota do dispositivo do jogador, a duracao dos sopros usa o tempo da propria nota
+    pub fn receive_breath_data(&self, player: PlayerSlot, wrapper: &MidiWrapper) -> MonitorResult<()> {
+        self.lock(player, move |data| {
+            data.receive_breath_data(wrapper.note.byte, wrapper.state == Note::STATE_ON, wrapper.time());
             Ok(())
         })
     }
     
-    pub fn receive_score(&self, precision: OnNotePrecision) -> MonitorResult<()> {
-        self.lock(move |data| {
+    /// Recebe uma amostra do stream de pressao do jogador, chamado em taxa fixa
+    pub fn receive_pressure_sample(&self, player: PlayerSlot, strength_byte: u8) -> MonitorResult<()> {
+        self.lock(player, move |data| {
+            if data.breath_curve.len() < BREATH_CURVE_LIMIT {
+                data.breath_curve.push(strength_byte);
+            }
+            data.receive_strength_sample(strength_byte, Instant::now());
+            Ok(())
+        })
+    }
+
+    pub fn breath_curve(&self, player: PlayerSlot) -> MonitorResult<Vec<u8>> {
+        if let Ok(data) = self.data.lock() {
+            Ok(data.get(&player).map(|d| d.breath_curve.clone()).unwrap_or_default())
+        } else {
+            Err(anyhow!(DEFAULT_ERROR_MSG))
+        }
+    }
+
+    /// Marca o inicio de uma pausa causada pelo dispositivo, a pausa vale para todos os jogadores
+    pub fn start_interruption(&self) -> MonitorResult<()> {
+        self.lock_all(move |data| {
+            data.start_interruption();
+            Ok(())
+        })
+    }
+
+    pub fn end_interruption(&self) -> MonitorResult<()> {
+        self.lock_all(move |data| {
+            data.end_interruption();
+            Ok(())
+        })
+    }
+
+    /// Recebe a avaliacao de uma nota da partitura segurada pelo jogador
+    pub fn receive_sustain(
+        &self,
+        player: PlayerSlot,
+        sustain: f32,
+        steadiness: Option<f32>,
+        time_in_zone: Option<f32>
+    ) -> MonitorResult<()> {
+        self.lock(player, move |data| {
+            data.total_sustain += sustain;
+            data.sustained_notes += 1;
+            if let Some(s) = steadiness {
+                data.total_steadiness += s;
+                data.steadiness_notes += 1;
+            }
+            if let Some(t) = time_in_zone {
+                data.total_time_in_zone += t;
+                data.zone_notes += 1;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn receive_score(&self, player: PlayerSlot, precision: OnNotePrecision) -> MonitorResult<()> {
+        self.lock(player, move |data| {
             data.receive_note(precision);
             Ok(())
         })
     }
     
     pub fn reset(&self) {
-        self.data.lock().unwrap().take();
+        self.data.lock().unwrap().clear();
     }
 }
 
@@ -79,14 +168,50 @@ impl Default for MonitoringData {
             breath_strength_buffer: VecDeque::with_capacity(BUFFER_SIZE),
             current_breath_strengths: Default::default(),
             breath_start_time: Default::default(),
+            held_notes: Default::default(),
+            breaths_from_notes: Default::default(),
+            pressure_breath_start: Default::default(),
+            total_strength_sum: Default::default(),
+            total_strength_samples: Default::default(),
+            breath_curve: Default::default(),
+            total_interruptions: Default::default(),
+            interrupted_time: Default::default(),
+            interruption_start_time: Default::default(),
+            total_sustain: Default::default(),
+            sustained_notes: Default::default(),
+            total_steadiness: Default::default(),
+            steadiness_notes: Default::default(),
+            total_time_in_zone: Default::default(),
+            zone_notes: Default::default(),
         }
     }
 }
 
 impl MonitoringData {
-    #[inline]
-    fn duration_from_epoch() -> Duration {
-        SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap()
+    /// Media da parte segurada das notas em porcentagem, [None] sem notas avaliadas
+    pub fn average_sustain(&self) -> Option<i32> {
+        (self.sustained_notes > 0).then(|| (self.total_sustain / self.sustained_notes as f32 * 100f32).round() as i32)
+    }
+    pub fn average_steadiness(&self) -> Option<i32> {
+        (self.steadiness_notes > 0).then(|| (self.total_steadiness / self.steadiness_notes as f32 * 100f32).round() as i32)
+    }
+    pub fn average_time_in_zone(&self) -> Option<i32> {
+        (self.zone_notes > 0).then(|| (self.total_time_in_zone / self.zone_notes as f32 * 100f32).round() as i32)
+    }
+    /// Os tempos do dispositivo recomeçam quando ele reconecta, entao o sopro em andamento é descartado
+    pub(super) fn start_interruption(&mut self) {
+        if self.interruption_start_time.is_none() {
+            self.total_interruptions += 1;
+            self.interruption_start_time = Some(Instant::now());
+        }
+        self.held_notes.clear();
+        self.breath_start_time = None;
+        self.pressure_breath_start = None;
+    }
+    pub(super) fn end_interruption(&mut self) {
+        if let Some(start) = self.interruption_start_time.take() {
+            self.interrupted_time += start.elapsed();
+        }
     }
     pub(super) fn receive_note(&mut self, precision: OnNotePrecision) {
         match precision {
@@ -100,53 +225,141 @@ impl MonitoringData {
                 self.total_close_scores += 1
             }
             OnNotePrecision::Miss => {
-                self.total_early_misses += 1
+                self.total_misses += 1
             }
             OnNotePrecision::EarlyMiss => {
                 self.total_early_misses += 1
             }
         }
     }
+    /// recebe uma nota ligada ou desligada em `at`, o tempo monotonico dado pelo dispositivo.
+    ///
+    /// O sopro vai da primeira nota ligada ate a ultima ser desligada.
+    pub(crate) fn receive_breath_data(&mut self, key: u8, state: bool, at: Duration) {
+        if state {
+            self.breaths_from_notes = true;
+            if self.held_notes.is_empty() {
+                self.breath_start_time = Some(at);
+            }
+            self.held_notes.insert(key);
+            return;
+        }
+        if !self.held_notes.remove(&key) || !self.held_notes.is_empty() {
+            return;
+        }
+        let Some(start) = self.breath_start_time.take() else {
+            return;
+        };
+        self.record_breath(at.saturating_sub(start));
+    }
+    fn record_breath(&mut self, breath_time: Duration) {
+        if self.highest_breath_time < breath_time {
+            self.highest_breath_time = breath_time;
+        }
+        self.total_breath_time += breath_time;
+    }
     /// recebe a forca do ultimo sinal emitido.
     /// manda 0 para sinalizar que parou
-    pub(crate) fn receive_breath_data(&mut self, strength_byte: u8, state: bool) {
-        if strength_byte == 0 || !state {
-            if self.breath_strength_buffer.len() == 0 {
+    ///
+    /// Cada chamada é uma amostra, a media é feita sobre todas as amostras
+    /// recebidas durante os sopros. Em dispositivos que so mandam pressao, sem notas,
+    /// a duracao do sopro vai da primeira amostra ate o 0, medida em `at`.
+    pub(crate) fn receive_strength_sample(&mut self, strength_byte: u8, at: Instant) {
+        if strength_byte == 0 {
+            if self.current_breath_strengths.is_empty() {
                 return;
             }
-            let breath_time = Self::duration_from_epoch();
-            let breath_time = breath_time - self.breath_start_time;
-            if self.highest_breath_time < breath_time {
-                self.highest_breath_time = breath_time;
+            if let Some(start) = self.pressure_breath_start.take() {
+                if !self.breaths_from_notes {
+                    self.record_breath(at.saturating_duration_since(start));
+                }
             }
-            self.total_breath_time += breath_time;
-            self.breath_start_time = Duration::ZERO;
-            let mut current_sum: usize = 0;
             for byte in self.current_breath_strengths.iter() {
-                current_sum += *byte as usize;
+                self.total_strength_sum += *byte as u64;
             }
+            self.total_strength_samples += self.current_breath_strengths.len() as u64;
             self.breath_strength_buffer.clear();
             self.current_breath_strengths.clear();
-            let avg = current_sum / self.current_breath_strengths.len();
-            self.average_strength = if self.average_strength > 0 {
-                (self.average_strength + avg as i32) / 2
-            } else {
-                avg as i32
-            };
+            self.average_strength = (self.total_strength_sum / self.total_strength_samples) as i32;
             return;
         }
-        let buffer_len = self.breath_strength_buffer.len();
-        if buffer_len == 0 {
-            self.breath_start_time = Self::duration_from_epoch();
-            self.current_breath_strengths.push(strength_byte);
-        } else {
-            if !self.breath_strength_buffer.contains(&strength_byte) {
-                self.current_breath_strengths.push(strength_byte);
-            }
-            if buffer_len >= BUFFER_SIZE {
-                self.breath_strength_buffer.pop_front();
-            }
+        if self.current_breath_strengths.is_empty() {
+            self.pressure_breath_start.get_or_insert(at);
+        }
+        self.current_breath_strengths.push(strength_byte);
+        if self.breath_strength_buffer.len() >= BUFFER_SIZE {
+            self.breath_strength_buffer.pop_front();
         }
         self.breath_strength_buffer.push_back(strength_byte);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn overlapping_notes_are_a_single_breath() {
+        let mut data = MonitoringData::default();
+        data.receive_breath_data(60, true, millis(1_000));
+        data.receive_breath_data(62, true, millis(1_200));
+        data.receive_breath_data(60, false, millis(1_500));
+        data.receive_breath_data(62, false, millis(2_250));
+        assert_eq!(data.total_breath_time, millis(1_250));
+        assert_eq!(data.highest_breath_time, millis(1_250));
+
+        data.receive_breath_data(64, true, millis(3_000));
+        data.receive_breath_data(64, false, millis(3_400));
+        assert_eq!(data.total_breath_time, millis(1_650));
+        assert_eq!(data.highest_breath_time, millis(1_250));
+    }
+
+    #[test]
+    fn interruption_discards_the_breath_in_progress() {
+        let mut data = MonitoringData::default();
+        data.receive_breath_data(60, true, millis(5_000));
+        data.start_interruption();
+        data.end_interruption();
+        // o dispositivo reconectou e o tempo dele recomeçou
+        data.receive_breath_data(60, false, millis(100));
+        assert_eq!(data.total_breath_time, Duration::ZERO);
+        assert_eq!(data.total_interruptions, 1);
+
+        data.receive_breath_data(60, true, millis(200));
+        data.receive_breath_data(60, false, millis(700));
+        assert_eq!(data.total_breath_time, millis(500));
+    }
+
+    #[test]
+    fn orphan_note_off_is_ignored() {
+        let mut data = MonitoringData::default();
+        data.receive_breath_data(60, false, millis(1_000));
+        assert_eq!(data.total_breath_time, Duration::ZERO);
+
+        data.receive_breath_data(60, true, millis(2_000));
+        data.receive_breath_data(62, false, millis(2_100));
+        data.receive_breath_data(60, false, millis(2_300));
+        assert_eq!(data.total_breath_time, millis(300));
+    }
+
+    #[test]
+    fn pressure_times_breaths_only_without_notes() {
+        let start = Instant::now();
+        let mut data = MonitoringData::default();
+        data.receive_strength_sample(40, start);
+        data.receive_strength_sample(60, start + millis(20));
+        data.receive_strength_sample(0, start + millis(800));
+        assert_eq!(data.total_breath_time, millis(800));
+        assert_eq!(data.average_strength, 50);
+
+        data.receive_breath_data(60, true, millis(10_000));
+        data.receive_strength_sample(40, start + millis(1_000));
+        data.receive_strength_sample(0, start + millis(1_900));
+        data.receive_breath_data(60, false, millis(10_600));
+        assert_eq!(data.total_breath_time, millis(1_400));
+    }
+}
@@ -0,0 +1,56 @@
+use midi_reader::audio_renderer::InputEvent;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Grava as notas tocadas pelo paciente durante a ultima musica, para que
+/// depois seja possivel gerar um audio da sessao.
+///
+/// A gravacao comeca no `start_game` e para no fim do jogo, ela é mantida ate
+/// a proxima musica começar.
+#[derive(Default)]
+pub struct PerformanceState {
+    recording: Mutex<Recording>,
+}
+
+#[derive(Default)]
+struct Recording {
+    started_at: Option<Instant>,
+    events: Vec<InputEvent>,
+}
+
+impl PerformanceState {
+    pub fn start(&self) {
+        if let Ok(mut r) = self.recording.lock() {
+            r.started_at = Some(Instant::now());
+            r.events.clear();
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Ok(mut r) = self.recording.lock() {
+            r.started_at = None;
+        }
+    }
+
+    /// Grava uma nota, `played_at` ja deve estar compensado pela latencia do dispositivo
+    pub fn record(&self, on: bool, key: u8, velocity: u8, played_at: Instant) {
+        if let Ok(mut r) = self.recording.lock() {
+            if let Some(started_at) = r.started_at {
+                r.events.push(InputEvent {
+                    at: played_at.saturating_duration_since(started_at),
+                    on,
+                    key,
+                    velocity,
+                });
+            }
+        }
+    }
+
+    pub fn events(&self) -> Vec<InputEvent> {
+        if let Ok(r) = self.recording.lock() {
+            r.events.clone()
+        } else {
+            vec![]
+        }
+    }
+}
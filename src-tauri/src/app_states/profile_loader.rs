@@ -0,0 +1,125 @@
+use crate::commands::{ScoringProfile, DEFAULT_SCORING_PROFILE_ID};
+use arduino_comm::instrument_profile::{InstrumentProfile, DEFAULT_INSTRUMENT_ID};
+use paris::{error, info, warn};
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::Path;
+
+/// Perfil lido de um arquivo `.json` da pasta de recursos
+pub(crate) trait JsonProfile: DeserializeOwned + Default {
+    /// Id do perfil que sempre existe, mesmo sem arquivo
+    const DEFAULT_ID: &'static str;
+    /// Tipo do perfil nos logs
+    const KIND: &'static str;
+
+    fn id(&self) -> &str;
+    fn name(&self) -> &str;
+    fn check(self) -> Result<Self, String>;
+}
+
+impl JsonProfile for InstrumentProfile {
+    const DEFAULT_ID: &'static str = DEFAULT_INSTRUMENT_ID;
+    const KIND: &'static str = "instrument";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn check(self) -> Result<Self, String> {
+        self.validated().map_err(|e| e.to_string())
+    }
+}
+
+impl JsonProfile for ScoringProfile {
+    const DEFAULT_ID: &'static str = DEFAULT_SCORING_PROFILE_ID;
+    const KIND: &'static str = "scoring profile";
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn check(self) -> Result<Self, String> {
+        self.validated().map_err(|e| e.message)
+    }
+}
+
+/// Carrega todos os arquivos `.json` da pasta, arquivos invalidos sao ignorados
+pub(crate) fn load_profiles<P: JsonProfile>(dir: &Path) -> Vec<P> {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(e) => {
+            warn!("Could not read {} folder {}: {}", P::KIND, dir.display(), e);
+            return vec![];
+        }
+    };
+    let mut profiles = vec![];
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let profile = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<P>(&s).map_err(|e| e.to_string()))
+            .and_then(P::check);
+        match profile {
+            Ok(p) => {
+                info!("Loaded {} {} from {}", P::KIND, p.id(), path.display());
+                profiles.push(p);
+            }
+            Err(e) => error!("Invalid {} file {}: {}", P::KIND, path.display(), e),
+        }
+    }
+    profiles
+}
+
+/// Ordena os perfis pelo nome com o padrao na primeira posicao, ele é criado se nao veio
+/// de nenhum arquivo
+pub(crate) fn with_default_first<P: JsonProfile>(mut profiles: Vec<P>) -> Vec<P> {
+    let default = profiles
+        .iter()
+        .position(|p| p.id() == P::DEFAULT_ID)
+        .map(|i| profiles.remove(i))
+        .unwrap_or_default();
+    profiles.sort_by(|a, b| a.name().cmp(b.name()));
+    profiles.insert(0, default);
+    profiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(id: &str, name: &str) -> ScoringProfile {
+        ScoringProfile { id: id.to_string(), name: name.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn default_profile_is_created_and_stays_first() {
+        let profiles = with_default_first(vec![profile("b", "Beta"), profile("a", "Alfa")]);
+        let ids: Vec<&str> = profiles.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec![DEFAULT_SCORING_PROFILE_ID, "a", "b"]);
+
+        let custom_default = profile(DEFAULT_SCORING_PROFILE_ID, "Zeta");
+        let profiles = with_default_first(vec![profile("a", "Alfa"), custom_default.clone()]);
+        assert_eq!(profiles[0], custom_default);
+        assert_eq!(profiles.len(), 2);
+    }
+
+    #[test]
+    fn invalid_and_other_files_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("profile-loader-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("valid.json"), serde_json::to_string(&profile("easy", "Facil")).unwrap()).unwrap();
+        fs::write(dir.join("invalid.json"), serde_json::to_string(&profile("", "Vazio")).unwrap()).unwrap();
+        fs::write(dir.join("broken.json"), "{").unwrap();
+        fs::write(dir.join("notes.txt"), "easy").unwrap();
+        let profiles = load_profiles::<ScoringProfile>(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(profiles, vec![profile("easy", "Facil")]);
+        assert!(load_profiles::<ScoringProfile>(&dir).is_empty());
+    }
+}
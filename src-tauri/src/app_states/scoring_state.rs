@@ -0,0 +1,68 @@
+use crate::app_states::profile_loader::{load_profiles, with_default_first};
+use crate::commands::{ScoringProfile, ServiceResult};
+use crate::constants::errors::{STATE_ACQUIRE_ERROR, USER_SCORING_PROFILE_NOT_FOUND};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Perfis de pontuacao disponiveis e qual deles o usuario escolheu.
+///
+/// O perfil padrao sempre existe, os outros vem dos arquivos da pasta de pontuacao.
+pub struct ScoringState {
+    profiles: Vec<ScoringProfile>,
+    user_profile: Mutex<Option<String>>,
+}
+
+impl Default for ScoringState {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+impl ScoringState {
+    pub fn new(profiles: Vec<ScoringProfile>) -> Self {
+        Self {
+            profiles: with_default_first(profiles),
+            user_profile: Mutex::new(None),
+        }
+    }
+
+    /// Perfis de pontuacao da pasta de recursos
+    pub fn load(dir: &Path) -> Self {
+        Self::new(load_profiles(dir))
+    }
+
+    pub fn profiles(&self) -> &[ScoringProfile] {
+        &self.profiles
+    }
+
+    pub fn profile(&self, profile_id: &str) -> ServiceResult<&ScoringProfile> {
+        self.profiles
+            .iter()
+            .find(|p| p.id == profile_id)
+            .ok_or(USER_SCORING_PROFILE_NOT_FOUND.into())
+    }
+
+    /// Escolhe o perfil do usuario, [None] volta para o padrao
+    pub fn set_user_profile(&self, profile_id: Option<String>) -> ServiceResult<()> {
+        if let Some(id) = profile_id.as_ref() {
+            self.profile(id)?;
+        }
+        if let Ok(mut u) = self.user_profile.lock() {
+            *u = profile_id;
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn active(&self) -> ServiceResult<ScoringProfile> {
+        let Ok(user) = self.user_profile.lock() else {
+            return Err(STATE_ACQUIRE_ERROR.into());
+        };
+        let profile = user
+            .as_ref()
+            .and_then(|id| self.profiles.iter().find(|p| &p.id == id))
+            .unwrap_or(&self.profiles[0]);
+        Ok(profile.clone())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::commands::ServiceResult;
+use crate::constants::errors::{EDITOR_NO_MUSIC_OPEN, STATE_ACQUIRE_ERROR};
+use midi_reader::errors::MidiReaderResult;
+use midi_reader::song_editor::EditableSong;
+use std::sync::Mutex;
+
+/// Guarda a musica que esta aberta no editor, junto com o id dela no banco
+#[derive(Default)]
+pub struct SongEditorState {
+    song: Mutex<Option<(EditableSong, i32)>>,
+}
+
+impl SongEditorState {
+    pub fn open(&self, music_id: i32, song: EditableSong) -> ServiceResult<()> {
+        if let Ok(mut s) = self.song.lock() {
+            *s = Some((song, music_id));
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn close(&self) -> ServiceResult<()> {
+        if let Ok(mut s) = self.song.lock() {
+            if s.take().is_none() {
+                Err(EDITOR_NO_MUSIC_OPEN.into())
+            } else {
+                Ok(())
+            }
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Aplica uma alteracao na musica aberta no editor
+    pub fn edit<T>(
+        &self,
+        edit: impl FnOnce(&mut EditableSong) -> MidiReaderResult<T>,
+    ) -> ServiceResult<T> {
+        if let Ok(mut s) = self.song.lock() {
+            if let Some((song, _)) = s.as_mut() {
+                Ok(edit(song)?)
+            } else {
+                Err(EDITOR_NO_MUSIC_OPEN.into())
+            }
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn read<T>(&self, read: impl FnOnce(&EditableSong, i32) -> T) -> ServiceResult<T> {
+        if let Ok(s) = self.song.lock() {
+            if let Some((song, id)) = s.as_ref() {
+                Ok(read(song, *id))
+            } else {
+                Err(EDITOR_NO_MUSIC_OPEN.into())
+            }
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use crate::app_states::midi_device_state::PlayerSlot;
+use crate::commands::BreathZone;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Notas da partitura mais curtas que isso nao sao avaliadas
+const MIN_SUSTAIN_DURATION: Duration = Duration::from_millis(250);
+/// Amostras minimas de pressao para avaliar a estabilidade
+const MIN_STEADINESS_SAMPLES: usize = 3;
+/// Desvio padrao da pressao (0 a 127) em que a estabilidade chega a 0
+const STEADINESS_TOLERANCE: f32 = 20f32;
+
+/// Compara quanto tempo cada nota da partitura ficou segurada pelo jogador com a
+/// duracao dela, e quao estavel foi a pressao do sopro enquanto isso.
+///
+/// A velocidade de cada nota da partitura é a pressao alvo dela, a pressao do jogador
+/// é comparada com essa zona durante toda a nota.
+#[derive(Default)]
+pub struct SustainState {
+    players: Mutex<HashMap<PlayerSlot, PlayerSustain>>,
+}
+
+/// Avaliacao de uma nota da partitura que terminou
+#[derive(Debug, Clone, Copy)]
+pub struct SustainResult {
+    pub player: PlayerSlot,
+    pub key: u8,
+    /// Parte da nota que o jogador segurou, de 0 a 1
+    pub sustain: f32,
+    /// 1 com a pressao constante, [None] se nao teve amostras suficientes
+    pub steadiness: Option<f32>,
+    /// Parte da nota com a pressao dentro da zona alvo, de 0 a 1
+    pub time_in_zone: Option<f32>,
+}
+
+/// Mudanca da pressao do jogador em relacao a zona alvo de uma nota da partitura
+#[derive(Debug, Clone, Copy)]
+pub struct ZoneChange {
+    pub key: u8,
+    pub zone: BreathZone,
+    pub pressure: u8,
+    pub min: u8,
+    pub max: u8,
+}
+
+#[derive(Default)]
+struct PlayerSustain {
+    /// Notas que o jogador esta segurando e desde quando
+    held: HashMap<u8, Instant>,
+    chart: HashMap<u8, ChartSustain>,
+}
+
+struct ChartSustain {
+    started_at: Instant,
+    held_since: Option<Instant>,
+    held_for: Duration,
+    samples: Vec<u8>,
+    /// Pressao minima e maxima da zona alvo
+    zone: (u8, u8),
+    zone_samples: u32,
+    in_zone_samples: u32,
+    last_zone: Option<BreathZone>,
+}
+
+impl ChartSustain {
+    fn release(&mut self, at: Instant) {
+        if let Some(since) = self.held_since.take() {
+            self.held_for += at.saturating_duration_since(since);
+        }
+    }
+
+    /// Conta a amostra na zona, retorna a nova zona se ela mudou
+    fn receive_zone_sample(&mut self, pressure: u8) -> Option<BreathZone> {
+        let (min, max) = self.zone;
+        let zone = if pressure < min {
+            BreathZone::TooWeak
+        } else if pressure > max {
+            BreathZone::TooStrong
+        } else {
+            self.in_zone_samples += 1;
+            BreathZone::InZone
+        };
+        self.zone_samples += 1;
+        (self.last_zone.replace(zone) != Some(zone)).then_some(zone)
+    }
+}
+
+/// Pressao minima e maxima aceitas para a `velocity` da nota, a `tolerance` vem do perfil de pontuacao
+fn breath_zone(velocity: u8, tolerance: u8) -> (u8, u8) {
+    (
+        velocity.saturating_sub(tolerance),
+        velocity.saturating_add(tolerance).min(127),
+    )
+}
+
+/// Desvio padrao das amostras convertido para 0 a 1
+fn steadiness(samples: &[u8]) -> Option<f32> {
+    if samples.len() < MIN_STEADINESS_SAMPLES {
+        return None;
+    }
+    let n = samples.len() as f32;
+    let mean = samples.iter().map(|s| *s as f32).sum::<f32>() / n;
+    let variance = samples.iter().map(|s| (*s as f32 - mean).powi(2)).sum::<f32>() / n;
+    Some((1f32 - variance.sqrt() / STEADINESS_TOLERANCE).clamp(0f32, 1f32))
+}
+
+impl PlayerSustain {
+    fn chart_note_on(&mut self, key: u8, velocity: u8, tolerance: u8, at: Instant) {
+        let held_since = self.held.contains_key(&key).then_some(at);
+        self.chart.insert(key, ChartSustain {
+            started_at: at,
+            held_since,
+            held_for: Duration::ZERO,
+            samples: vec![],
+            zone: breath_zone(velocity, tolerance),
+            zone_samples: 0,
+            in_zone_samples: 0,
+            last_zone: None,
+        });
+    }
+
+    fn chart_note_off(&mut self, player: PlayerSlot, key: u8, at: Instant) -> Option<SustainResult> {
+        let mut chart = self.chart.remove(&key)?;
+        chart.release(at);
+        let duration = at.saturating_duration_since(chart.started_at);
+        if duration < MIN_SUSTAIN_DURATION {
+            return None;
+        }
+        Some(SustainResult {
+            player,
+            key,
+            sustain: (chart.held_for.as_secs_f32() / duration.as_secs_f32()).min(1f32),
+            steadiness: steadiness(&chart.samples),
+            time_in_zone: (chart.zone_samples > 0)
+                .then(|| chart.in_zone_samples as f32 / chart.zone_samples as f32),
+        })
+    }
+
+    fn played_note(&mut self, key: u8, on: bool, at: Instant) {
+        if on {
+            self.held.insert(key, at);
+            if let Some(chart) = self.chart.get_mut(&key) {
+                chart.held_since.get_or_insert(at.max(chart.started_at));
+            }
+        } else {
+            self.held.remove(&key);
+            if let Some(chart) = self.chart.get_mut(&key) {
+                chart.release(at);
+            }
+        }
+    }
+}
+
+impl SustainState {
+    /// Nota da partitura do `player` começou agora, a `velocity` e a `tolerance` definem a zona alvo
+    pub fn chart_note_on(&self, player: PlayerSlot, key: u8, velocity: u8, tolerance: u8) {
+        let now = Instant::now();
+        self.lock::<()>(player, |p| {
+            p.chart_note_on(key, velocity, tolerance, now);
+            None
+        });
+    }
+
+    /// Nota da partitura do `player` acabou agora, retorna a avaliacao dela
+    pub fn chart_note_off(&self, player: PlayerSlot, key: u8) -> Option<SustainResult> {
+        let now = Instant::now();
+        self.lock(player, |p| p.chart_note_off(player, key, now))
+    }
+
+    /// Nota do jogador ligada ou desligada em `played_at`, ja descontada a latencia
+    pub fn played_note(&self, player: PlayerSlot, key: u8, on: bool, played_at: Instant) {
+        self.lock::<()>(player, |p| {
+            p.played_note(key, on, played_at);
+            None
+        });
+    }
+
+    /// Amostra de pressao do jogador, a estabilidade vale para as notas que ele esta segurando
+    /// e a zona alvo para todas as notas da partitura em andamento.
+    ///
+    /// Retorna as notas em que a pressao entrou ou saiu da zona.
+    pub fn receive_pressure(&self, player: PlayerSlot, pressure: u8) -> Vec<ZoneChange> {
+        self.lock(player, |p| {
+            let changes = p
+                .chart
+                .iter_mut()
+                .filter_map(|(key, c)| {
+                    if c.held_since.is_some() {
+                        c.samples.push(pressure);
+                    }
+                    let zone = c.receive_zone_sample(pressure)?;
+                    Some(ZoneChange {
+                        key: *key,
+                        zone,
+                        pressure,
+                        min: c.zone.0,
+                        max: c.zone.1,
+                    })
+                })
+                .collect();
+            Some(changes)
+        })
+        .unwrap_or_default()
+    }
+
+    /// Esquece as notas da partitura em andamento, usado quando a musica pausa ou termina
+    pub fn clear(&self) {
+        if let Ok(mut players) = self.players.lock() {
+            players.values_mut().for_each(|p| p.chart.clear());
+        }
+    }
+
+    fn lock<T>(&self, player: PlayerSlot, f: impl FnOnce(&mut PlayerSustain) -> Option<T>) -> Option<T> {
+        let mut players = self.players.lock().ok()?;
+        f(players.entry(player).or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn steadiness_needs_samples_and_drops_with_the_deviation() {
+        assert_eq!(steadiness(&[]), None);
+        assert_eq!(steadiness(&[60, 60]), None);
+        assert_eq!(steadiness(&[60, 60, 60]), Some(1f32));
+        // desvio padrao 10, metade da tolerancia
+        assert_eq!(steadiness(&[50, 70, 50, 70]), Some(0.5f32));
+        assert_eq!(steadiness(&[0, 127, 0, 127]), Some(0f32));
+    }
+
+    #[test]
+    fn sustain_is_the_held_part_of_the_chart_note() {
+        let start = Instant::now();
+        let mut player = PlayerSustain::default();
+        player.chart_note_on(60, 64, 15, start);
+        player.played_note(60, true, start + ms(250));
+        let result = player.chart_note_off(0, 60, start + ms(1_000)).unwrap();
+        assert_eq!(result.sustain, 0.75f32);
+        assert_eq!(result.steadiness, None);
+        assert_eq!(result.time_in_zone, None);
+    }
+
+    #[test]
+    fn note_already_held_counts_from_the_chart_start() {
+        let start = Instant::now();
+        let mut player = PlayerSustain::default();
+        player.played_note(60, true, start);
+        player.chart_note_on(60, 64, 15, start + ms(100));
+        player.played_note(60, false, start + ms(600));
+        player.played_note(62, true, start + ms(700));
+        let result = player.chart_note_off(0, 60, start + ms(1_100)).unwrap();
+        assert_eq!(result.sustain, 0.5f32);
+    }
+
+    #[test]
+    fn short_and_unknown_chart_notes_are_not_judged() {
+        let start = Instant::now();
+        let mut player = PlayerSustain::default();
+        player.chart_note_on(60, 64, 15, start);
+        assert!(player.chart_note_off(0, 60, start + ms(249)).is_none());
+        assert!(player.chart_note_off(0, 62, start + ms(1_000)).is_none());
+    }
+
+    #[test]
+    fn state_rates_the_note_held_by_the_player() {
+        let state = SustainState::default();
+        state.played_note(0, 60, true, Instant::now());
+        state.chart_note_on(0, 60, 64, 15);
+        for _ in 0..3 {
+            state.receive_pressure(0, 64);
+        }
+        std::thread::sleep(MIN_SUSTAIN_DURATION);
+        let result = state.chart_note_off(0, 60).unwrap();
+        assert_eq!(result.sustain, 1f32);
+        assert_eq!(result.steadiness, Some(1f32));
+        assert_eq!(result.time_in_zone, Some(1f32));
+    }
+
+    #[test]
+    fn breath_zone_uses_the_tolerance_and_stays_in_range() {
+        assert_eq!(breath_zone(64, 15), (49, 79));
+        assert_eq!(breath_zone(64, 0), (64, 64));
+        assert_eq!(breath_zone(10, 15), (0, 25));
+        assert_eq!(breath_zone(120, 15), (105, 127));
+    }
+
+    #[test]
+    fn zone_samples_report_only_changes() {
+        let start = Instant::now();
+        let mut player = PlayerSustain::default();
+        player.chart_note_on(60, 64, 10, start);
+        let chart = player.chart.get_mut(&60).unwrap();
+        assert_eq!(chart.receive_zone_sample(30), Some(BreathZone::TooWeak));
+        assert_eq!(chart.receive_zone_sample(40), None);
+        assert_eq!(chart.receive_zone_sample(54), Some(BreathZone::InZone));
+        assert_eq!(chart.receive_zone_sample(74), None);
+        assert_eq!(chart.receive_zone_sample(75), Some(BreathZone::TooStrong));
+        assert_eq!(chart.receive_zone_sample(64), Some(BreathZone::InZone));
+        assert_eq!((chart.in_zone_samples, chart.zone_samples), (3, 6));
+        let result = player.chart_note_off(0, 60, start + ms(500)).unwrap();
+        assert_eq!(result.time_in_zone, Some(0.5f32));
+    }
+}
@@ -0,0 +1,42 @@
+use crate::commands::ServiceResult;
+use crate::constants::errors::STATE_ACQUIRE_ERROR;
+use std::sync::Mutex;
+
+/// Instrumento escolhido pelo usuario
+pub const INSTRUMENT_KEY: &str = "__instrument";
+/// Curva de sopro medida na calibracao do usuario
+pub const CALIBRATION_KEY: &str = "__calibration";
+/// Janelas de acerto das notas do usuario
+pub const HIT_WINDOWS_KEY: &str = "__hit_windows";
+/// Perfil de pontuacao escolhido pelo usuario
+pub const SCORING_PROFILE_KEY: &str = "__scoring_profile";
+
+/// Usuario (paciente) usando o app, as preferencias dele sao salvas no store
+#[derive(Default)]
+pub struct UserState {
+    user_id: Mutex<Option<String>>,
+}
+
+impl UserState {
+    pub fn set_user(&self, user_id: Option<String>) -> ServiceResult<()> {
+        if let Ok(mut u) = self.user_id.lock() {
+            *u = user_id;
+            Ok(())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    pub fn user(&self) -> ServiceResult<Option<String>> {
+        if let Ok(u) = self.user_id.lock() {
+            Ok(u.clone())
+        } else {
+            Err(STATE_ACQUIRE_ERROR.into())
+        }
+    }
+
+    /// Chave de uma preferencia do usuario no store
+    pub fn key(user_id: &str, key: &str) -> String {
+        format!("{}{}", user_id, key)
+    }
+}
@@ -0,0 +1,91 @@
+use crate::app_states::backing_track_state::BackingTrackState;
+use crate::app_states::database_state::DatabaseState;
+use crate::commands::commands_utils::midi_file_utils::read_music_from_id;
+use crate::commands::payloads::backing_track::{BackingTrackConfig, MusicTrack};
+use crate::commands::payloads::service_error::ServiceResult;
+use midi_reader::backing_track::list_output_ports;
+use midi_reader::errors::MidiReaderError;
+use midi_reader::song_editor::EditableSong;
+use paris::{info, success};
+use tauri::{AppHandle, Runtime, State};
+
+#[inline]
+fn config(backing_state: &BackingTrackState) -> ServiceResult<BackingTrackConfig> {
+    let port = backing_state.output_port()?;
+    backing_state.read_mixer(move |m| BackingTrackConfig::new(port, m))
+}
+
+#[tauri::command]
+pub async fn list_midi_output_ports() -> ServiceResult<Vec<String>> {
+    info!("Looking for available midi output ports...");
+    let ports = list_output_ports()?;
+    success!("Found output ports: {:?}", ports);
+    Ok(ports)
+}
+
+#[tauri::command]
+pub async fn select_midi_output_port(
+    port_name: Option<String>,
+    backing_state: State<'_, BackingTrackState>,
+) -> ServiceResult<BackingTrackConfig> {
+    if let Some(name) = port_name.as_ref() {
+        if !list_output_ports()?
+            .iter()
+            .any(|p| p.contains(name.as_str()))
+        {
+            return Err(MidiReaderError::OutputPortNotFound(name.to_owned()).into());
+        }
+        info!("Backing track will be sent to {}", name);
+    } else {
+        info!("Backing track output disabled");
+    }
+    backing_state.select_output_port(port_name)?;
+    config(&backing_state)
+}
+
+#[tauri::command]
+pub async fn backing_track_config(
+    backing_state: State<'_, BackingTrackState>,
+) -> ServiceResult<BackingTrackConfig> {
+    config(&backing_state)
+}
+
+#[tauri::command]
+pub async fn set_player_tracks(
+    tracks: Vec<usize>,
+    backing_state: State<'_, BackingTrackState>,
+) -> ServiceResult<BackingTrackConfig> {
+    backing_state.update_mixer(move |m| m.set_player_tracks(&tracks))?;
+    config(&backing_state)
+}
+
+#[tauri::command]
+pub async fn set_track_mute(
+    track: usize,
+    muted: bool,
+    backing_state: State<'_, BackingTrackState>,
+) -> ServiceResult<BackingTrackConfig> {
+    backing_state.update_mixer(move |m| m.set_muted(track, muted))?;
+    config(&backing_state)
+}
+
+#[tauri::command]
+pub async fn set_track_solo(
+    track: usize,
+    solo: bool,
+    backing_state: State<'_, BackingTrackState>,
+) -> ServiceResult<BackingTrackConfig> {
+    backing_state.update_mixer(move |m| m.set_solo(track, solo))?;
+    config(&backing_state)
+}
+
+#[tauri::command]
+pub async fn list_music_tracks<R: Runtime>(
+    music_id: i32,
+    db_state: State<'_, DatabaseState>,
+    handle: AppHandle<R>,
+) -> ServiceResult<Vec<MusicTrack>> {
+    let (_, file) = read_music_from_id(&db_state, &handle, music_id).await?;
+    let song = EditableSong::from_bytes(&file)?;
+    Ok(MusicTrack::from_song(&song))
+}
@@ -0,0 +1,103 @@
+use crate::app_states::calibration_state::CalibrationState;
+use crate::app_states::midi_device_state::{MidiState, MAIN_PLAYER};
+use crate::commands::commands_utils::midi_connection_utils::player_slot;
+use crate::app_states::store_state::StoreState;
+use crate::app_states::user_state::{UserState, CALIBRATION_KEY};
+use crate::commands::payloads::calibration::{BreathCalibrationInfo, BreathCalibrationStep};
+use crate::commands::payloads::service_error::ServiceResult;
+use crate::constants::errors::DEVICE_NOT_CONNECTED;
+use arduino_comm::breath_calibration::BreathCalibration;
+use paris::{info, success, warn};
+use persistence::storage::StorageError;
+use tauri::State;
+
+/// Começa a gravar uma etapa da calibracao do sopro, o dispositivo do jogador
+/// precisa estar conectado
+#[tauri::command]
+pub async fn start_breath_calibration_step(
+    step: BreathCalibrationStep,
+    player: Option<u8>,
+    midi_state: State<'_, MidiState>,
+    calibration_state: State<'_, CalibrationState>,
+) -> ServiceResult<()> {
+    let player = player_slot(player)?;
+    if !midi_state.has_player_connection(player) {
+        return Err(DEVICE_NOT_CONNECTED.into());
+    }
+    info!("Recording breath calibration step {:?} of player {}", step, player);
+    calibration_state.start_step(player, step.into())
+}
+
+/// Termina a etapa gravada e retorna o valor medido nela
+#[tauri::command]
+pub async fn finish_breath_calibration_step(
+    calibration_state: State<'_, CalibrationState>,
+) -> ServiceResult<u8> {
+    let value = calibration_state.finish_step()?;
+    info!("Breath calibration step measured {}", value);
+    Ok(value)
+}
+
+/// Termina a calibracao, a curva passa a ser usada pelo jogador calibrado.
+///
+/// So a curva do [MAIN_PLAYER] é salva para o usuario atual, a do parceiro do dueto
+/// vale ate o app fechar.
+#[tauri::command]
+pub async fn save_breath_calibration(
+    calibration_state: State<'_, CalibrationState>,
+    user_state: State<'_, UserState>,
+    store_state: State<'_, StoreState>,
+) -> ServiceResult<BreathCalibrationInfo> {
+    let (player, calibration) = calibration_state.finish_calibration()?;
+    let calibration = BreathCalibrationInfo::from(calibration);
+    if player != MAIN_PLAYER {
+        success!("Breath calibration {:?} set for player {}", calibration, player);
+    } else if let Some(user) = user_state.user()? {
+        store_state.save(&UserState::key(&user, CALIBRATION_KEY), &calibration)?;
+        store_state.commit()?;
+        success!("Saved breath calibration {:?} for user {}", calibration, user);
+    } else {
+        warn!("No user selected, breath calibration will not be saved");
+    }
+    Ok(calibration)
+}
+
+#[tauri::command]
+pub async fn cancel_breath_calibration(calibration_state: State<'_, CalibrationState>) -> ServiceResult<()> {
+    calibration_state.cancel_calibration();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn breath_calibration(
+    player: Option<u8>,
+    calibration_state: State<'_, CalibrationState>,
+) -> ServiceResult<BreathCalibrationInfo> {
+    Ok(calibration_state.calibration(player_slot(player)?)?.into())
+}
+
+/// Volta para a curva padrao, a calibracao salva do usuario atual so é apagada
+/// para o [MAIN_PLAYER]
+#[tauri::command]
+pub async fn reset_breath_calibration(
+    player: Option<u8>,
+    calibration_state: State<'_, CalibrationState>,
+    user_state: State<'_, UserState>,
+    store_state: State<'_, StoreState>,
+) -> ServiceResult<BreathCalibrationInfo> {
+    let player = player_slot(player)?;
+    calibration_state.cancel_calibration();
+    calibration_state.set_calibration(player, BreathCalibration::default())?;
+    if player != MAIN_PLAYER {
+        return Ok(BreathCalibration::default().into());
+    }
+    if let Some(user) = user_state.user()? {
+        match store_state.remove(&UserState::key(&user, CALIBRATION_KEY)) {
+            Ok(_) | Err(StorageError::KeyNotFound(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
+        store_state.commit()?;
+        info!("Removed breath calibration of user {}", user);
+    }
+    breath_calibration(Some(player), calibration_state).await
+}
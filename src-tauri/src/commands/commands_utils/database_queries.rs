@@ -1,17 +1,21 @@
 use crate::app_states::current_music_score_state::CurrentMusicScoreState;
 use crate::app_states::database_state::DatabaseState;
+use crate::app_states::midi_device_state::{PlayerSlot, MAIN_PLAYER};
 use crate::app_states::monitoring_state::MonitoringState;
+use crate::commands::payloads::grade::Grade;
 use crate::commands::payloads::music::MidiMusicList;
+use crate::commands::payloads::score::grading;
 use crate::commands::payloads::service_error::ServiceResult;
 use crate::constants::errors::FILE_ID_NOT_FOUND;
 use anyhow::{anyhow, Error};
 use entity::music::Model;
-use entity::prelude::Music;
+use entity::prelude::{Music, Score};
+use entity::score;
 use entity::score::ActiveModel;
 use paris::warn;
 use sea_orm::sqlx::types::chrono::Utc;
-use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait};
-use std::ops::Deref;
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter};
+use std::collections::HashMap;
 use tauri::State;
 
 pub async fn get_music(music_id: i32, db_state: &State<'_, DatabaseState>) -> ServiceResult<Model> {
@@ -33,6 +37,7 @@ pub trait ScoreSaver {
 }
 
 impl ScoreSaver for DatabaseState {
+    /// Salva um score para cada jogador que tocou, o [MAIN_PLAYER] sempre é salvo
     async fn save_score(
         &self, 
         finished: bool, 
@@ -40,31 +45,63 @@ impl ScoreSaver for DatabaseState {
         score_state: &CurrentMusicScoreState, 
         monitor_state: &MonitoringState
     ) -> Result<(), Error> {
-        let model = if let Ok(score) = score_state.score.lock() {
+        let mut models = vec![];
+        if let Ok(scores) = score_state.scores.lock() {
             if let Ok(monitor) = monitor_state.data.lock() {
-                let model = ActiveModel {
-                    total: ActiveValue::Set(score.total_score),
-                    date: ActiveValue::Set(Utc::now()),
-                    completed: ActiveValue::Set(finished),
-                    highest_streak: ActiveValue::Set(score.highest_streak as i32),
-                    music_id: ActiveValue::Set(music_id),
-                    ..Default::default()
-                };
-                if let Some(m_data) = monitor.deref() {
-                    ActiveModel {
-                        breath_average_strength: ActiveValue::Set(Some(m_data.average_strength)),
-                        total_close_hits: ActiveValue::Set(Some(m_data.total_close_scores as i32)),
-                        total_great_hits: ActiveValue::Set(Some(m_data.total_great_scores as i32)),
-                        total_ok_hits: ActiveValue::Set(Some(m_data.total_ok_scores as i32)),
-                        highest_breathing_duration: ActiveValue::Set(Some(m_data.highest_breath_time.as_secs() as i32)),
-                        total_breathing_duration: ActiveValue::Set(Some(m_data.total_breath_time.as_secs() as i32)),
-                        total_early_misses: ActiveValue::Set(Some(m_data.total_early_misses as i32)),
-                        total_misses: ActiveValue::Set(Some(m_data.total_misses as i32)),
-                        ..model
-                    }
-                } else {
-                    warn!("No monitoring data recorded");
-                    model
+                let mut players: Vec<PlayerSlot> = scores.keys().chain(monitor.keys()).copied().collect();
+                players.push(MAIN_PLAYER);
+                players.sort();
+                players.dedup();
+                let scoring_profile = score_state.profile_id();
+                let thresholds = score_state.grade_thresholds();
+                for player in players {
+                    let score = scores.get(&player);
+                    let model = ActiveModel {
+                        total: ActiveValue::Set(score.map(|s| s.total_score).unwrap_or_default()),
+                        date: ActiveValue::Set(Utc::now()),
+                        completed: ActiveValue::Set(finished),
+                        highest_streak: ActiveValue::Set(score.map(|s| s.highest_streak as i32).unwrap_or_default()),
+                        sustain_score: ActiveValue::Set(Some(score.map(|s| s.sustain_score).unwrap_or_default())),
+                        steadiness_score: ActiveValue::Set(Some(score.map(|s| s.steadiness_score).unwrap_or_default())),
+                        music_id: ActiveValue::Set(music_id),
+                        player: ActiveValue::Set(Some(player as i32)),
+                        scoring_profile: ActiveValue::Set(scoring_profile.clone()),
+                        ..Default::default()
+                    };
+                    let model = if let Some(m_data) = monitor.get(&player) {
+                        let accuracy = thresholds.accuracy(
+                            m_data.total_great_scores,
+                            m_data.total_ok_scores,
+                            m_data.total_close_scores,
+                            m_data.total_misses,
+                            m_data.total_early_misses,
+                        );
+                        // so tentativas terminadas recebem nota e estrelas
+                        let graded = accuracy.filter(|_| finished);
+                        ActiveModel {
+                            accuracy: ActiveValue::Set(accuracy.map(|a| a.round() as i32)),
+                            grade: ActiveValue::Set(graded.map(|a| thresholds.grade(a).to_string())),
+                            stars: ActiveValue::Set(graded.map(|a| thresholds.stars(a) as i32)),
+                            breath_average_strength: ActiveValue::Set(Some(m_data.average_strength)),
+                            total_close_hits: ActiveValue::Set(Some(m_data.total_close_scores as i32)),
+                            total_great_hits: ActiveValue::Set(Some(m_data.total_great_scores as i32)),
+                            total_ok_hits: ActiveValue::Set(Some(m_data.total_ok_scores as i32)),
+                            highest_breathing_duration: ActiveValue::Set(Some(m_data.highest_breath_time.as_millis() as i32)),
+                            total_breathing_duration: ActiveValue::Set(Some(m_data.total_breath_time.as_millis() as i32)),
+                            total_early_misses: ActiveValue::Set(Some(m_data.total_early_misses as i32)),
+                            total_misses: ActiveValue::Set(Some(m_data.total_misses as i32)),
+                            total_interruptions: ActiveValue::Set(Some(m_data.total_interruptions as i32)),
+                            interrupted_duration: ActiveValue::Set(Some(m_data.interrupted_time.as_millis() as i32)),
+                            average_sustain: ActiveValue::Set(m_data.average_sustain()),
+                            average_steadiness: ActiveValue::Set(m_data.average_steadiness()),
+                            time_in_zone: ActiveValue::Set(m_data.average_time_in_zone()),
+                            ..model
+                        }
+                    } else {
+                        warn!("No monitoring data recorded for player {}", player);
+                        model
+                    };
+                    models.push(model);
                 }
             } else {
                 return Err(anyhow!("Could not acquire monitoring data"))
@@ -72,7 +109,9 @@ impl ScoreSaver for DatabaseState {
         } else {
             return Err(anyhow!("Could not acquire score data"))
         };
-        model.insert(&self.db).await?;
+        for model in models {
+            model.insert(&self.db).await?;
+        }
         Ok(())
     }
 }
@@ -80,4 +119,25 @@ impl ScoreSaver for DatabaseState {
 pub async fn music_list(db_state: &DatabaseState) -> anyhow::Result<MidiMusicList> {
     let a = Music::find().all(&db_state.db).await?;
     Ok(MidiMusicList::from(a))
+}
+
+/// Melhor nota das tentativas terminadas de cada musica, pelo id da musica
+pub async fn best_grades(db_state: &DatabaseState) -> anyhow::Result<HashMap<i32, Grade>> {
+    let scores = Score::find()
+        .filter(score::Column::Completed.eq(true))
+        .all(&db_state.db)
+        .await?;
+    let mut best: HashMap<i32, Grade> = HashMap::new();
+    for score in scores {
+        let (_, Some(grade), _) = grading(&score) else {
+            if score.grade.is_some() {
+                warn!("Ignoring unknown grade {:?} of music {}", score.grade, score.music_id);
+            }
+            continue;
+        };
+        best.entry(score.music_id)
+            .and_modify(|b| *b = (*b).min(grade))
+            .or_insert(grade);
+    }
+    Ok(best)
 }
\ No newline at end of file
@@ -0,0 +1,114 @@
+use crate::app_states::current_music_score_state::CurrentMusicScoreState;
+use crate::app_states::hit_judge_state::{HitJudgeState, Judgement};
+use crate::app_states::monitoring_state::MonitoringState;
+use crate::app_states::midi_device_state::PlayerSlot;
+use crate::app_states::sustain_state::{SustainResult, ZoneChange};
+use crate::commands::payloads::on_note_data::{BreathZoneFeedback, NoteJudgement, OnNotePayload, SustainFeedback};
+use crate::constants::events_name::{BREATH_ZONE, NOTE_JUDGEMENT, NOTE_SUSTAIN};
+use crate::constants::limits::HIT_JUDGE_TICK;
+use paris::{info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{Manager, Runtime, Window};
+
+/// Soma as notas julgadas no score e no monitoramento de cada jogador e avisa o front
+pub fn emit_judgements<R: Runtime, M: Manager<R>>(
+    manager: &M,
+    window_label: &str,
+    judgements: Vec<Judgement>
+) {
+    let score_state = manager.state::<CurrentMusicScoreState>();
+    let monitoring_state = manager.state::<MonitoringState>();
+    for payload in score_judgements(&score_state, &monitoring_state, judgements) {
+        let _ = manager.emit_to(window_label, NOTE_JUDGEMENT, payload);
+    }
+}
+
+/// Fecha as notas da partitura que passaram da janela enquanto `running` estiver ligado,
+/// assim um jogador parado recebe o `Miss` na hora e nao so no proximo toque dele
+pub fn start_judge_ticker<R: Runtime>(window: Window<R>, running: Arc<AtomicBool>) {
+    std::thread::spawn(move || {
+        while running.load(Ordering::Relaxed) {
+            std::thread::sleep(Duration::from_millis(HIT_JUDGE_TICK));
+            let judged = window.state::<HitJudgeState>().expire();
+            emit_judgements(&window, window.label(), judged);
+        }
+    });
+}
+
+/// Soma as notas julgadas no score e no monitoramento, sem avisar o front
+pub fn score_judgements(
+    score_state: &CurrentMusicScoreState,
+    monitoring_state: &MonitoringState,
+    judgements: Vec<Judgement>
+) -> Vec<NoteJudgement> {
+    judgements.into_iter().map(|judgement| {
+        let precision = judgement.precision;
+        info!("Player {} note {} judged as {:?}", judgement.player, judgement.key, precision);
+        let (new_total_score, gained_score, hit_streak) = score_state.add_to_total_score(judgement.player, precision);
+        if let Err(e) = monitoring_state.receive_score(judgement.player, precision) {
+            warn!("Could not monitor the judged note: {}", e);
+        }
+        NoteJudgement {
+            player: judgement.player,
+            key: judgement.key,
+            precision,
+            score: OnNotePayload::new(hit_streak, new_total_score as i64, gained_score),
+        }
+    }).collect()
+}
+
+/// Soma a avaliacao da nota segurada no score e no monitoramento do jogador e avisa o front
+pub fn emit_sustain<R: Runtime, M: Manager<R>>(
+    manager: &M,
+    window_label: &str,
+    result: SustainResult
+) {
+    let percent = |v: f32| (v * 100f32).round() as u8;
+    info!(
+        "Player {} held note {} for {}% with steadiness {:?} and {:?}% in the target zone",
+        result.player,
+        result.key,
+        percent(result.sustain),
+        result.steadiness.map(percent),
+        result.time_in_zone.map(percent)
+    );
+    let (total_score, sustain_score, steadiness_score, hit_streak) = manager
+        .state::<CurrentMusicScoreState>()
+        .add_sustain_score(result.player, result.sustain, result.steadiness);
+    if let Err(e) = manager.state::<MonitoringState>().receive_sustain(result.player, result.sustain, result.steadiness, result.time_in_zone) {
+        warn!("Could not monitor the held note: {}", e);
+    }
+    let payload = SustainFeedback {
+        player: result.player,
+        key: result.key,
+        sustain_percent: percent(result.sustain),
+        steadiness_percent: result.steadiness.map(percent),
+        time_in_zone_percent: result.time_in_zone.map(percent),
+        sustain_score,
+        steadiness_score,
+        score: OnNotePayload::new(hit_streak, total_score as i64, sustain_score + steadiness_score),
+    };
+    let _ = manager.emit_to(window_label, NOTE_SUSTAIN, payload);
+}
+
+/// Avisa o front quando a pressao do jogador entra ou sai da zona alvo das notas da partitura
+pub fn emit_zone_changes<R: Runtime, M: Manager<R>>(
+    manager: &M,
+    window_label: &str,
+    player: PlayerSlot,
+    changes: Vec<ZoneChange>
+) {
+    for change in changes {
+        let payload = BreathZoneFeedback {
+            player,
+            key: change.key,
+            zone: change.zone,
+            pressure: change.pressure,
+            min: change.min,
+            max: change.max,
+        };
+        let _ = manager.emit_to(window_label, BREATH_ZONE, payload);
+    }
+}
@@ -1,85 +1,257 @@
-use crate::app_states::midi_device_state::MidiState;
+use crate::app_states::calibration_state::CalibrationState;
+use crate::app_states::hit_judge_state::HitJudgeState;
+use crate::app_states::instrument_state::InstrumentState;
+use crate::app_states::latency_state::LatencyState;
+use crate::app_states::midi_device_state::{MidiState, PlayerSlot, MAIN_PLAYER};
 use crate::app_states::monitoring_state::MonitoringState;
+use crate::app_states::performance_state::PerformanceState;
+use crate::app_states::store_state::StoreState;
+use crate::app_states::sustain_state::SustainState;
+use crate::commands::commands_utils::hit_judge_utils::{emit_judgements, emit_zone_changes};
+use crate::commands::commands_utils::midi_file_utils::{interrupt_game, offer_resume};
+use crate::commands::payloads::device_connection::{ConnectionChangeReason, DeviceConnectionState};
+use crate::commands::payloads::device_profile::BreathPressure;
 use crate::commands::payloads::midi_payload::MidiPayload;
+use crate::commands::payloads::service_error::ServiceError;
 use crate::commands::ServiceResult;
-use crate::constants::events_name::{MIDI_DEVICE_CONNECTION_STATE, MIDI_NOTE};
-use crate::constants::limits::MIDI_DEVICE_CONNECTION_CHECKER_TIMEOUT;
-use arduino_comm::midi_connection::{list_available_devices, MidiConnection};
+use crate::constants::errors::DEVICE_INVALID_PLAYER;
+use crate::constants::events_name::{BREATH_PRESSURE, MIDI_DEVICE_CONNECTION_STATE, MIDI_NOTE};
+use crate::constants::limits::{MAX_PLAYERS, MIDI_DEVICE_CONNECTION_CHECKER_TIMEOUT};
+use arduino_comm::breath_pressure::{PressureInput, PressureSampler};
+use arduino_comm::device_config::DeviceResponses;
+use arduino_comm::device_profile::DeviceProfile;
+use arduino_comm::device_watcher::{device_lister, DeviceEvent, DevicePort, DeviceWatcher};
+use arduino_comm::input_filter::InputFilterHandle;
+use arduino_comm::input_source::InputSource;
+use arduino_comm::latency::DeviceClock;
+use arduino_comm::midi_subscriptions::MidiSubscriptions;
 use arduino_comm::note::Note;
-use paris::{info, warn};
-use std::time::Duration;
-use tauri::{AppHandle, Manager, State, Window};
+use paris::{error, info, warn};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
 
+/// Valida o jogador recebido do front, sem jogador usa o [MAIN_PLAYER]
+pub fn player_slot(player: Option<PlayerSlot>) -> ServiceResult<PlayerSlot> {
+    match player {
+        None => Ok(MAIN_PLAYER),
+        Some(p) if p < MAX_PLAYERS => Ok(p),
+        Some(_) => Err(DEVICE_INVALID_PLAYER.into())
+    }
+}
+
+/// Conecta a fonte para o jogador, `port` é a porta reaberta se o dispositivo for removido e voltar
 pub fn connect(
-    window: &Window,
+    player: PlayerSlot,
+    window_label: &str,
     app_handle: AppHandle,
     midi_state: State<'_, MidiState>,
-    conn: MidiConnection
+    conn: Box<dyn InputSource>,
+    port: Option<DevicePort>
 ) -> ServiceResult<()> {
-    info!("Connecting to device {}", conn.port_name);
-    midi_state.set_device_connection(conn)?;
-    info!("Successfully connected, listening for inputs...");
-    let window_label = window.label().to_owned();
-    device_connection_listener(
-        midi_state.connected_port_name()?,
-        window_label.clone(),
-        app_handle.clone()
-    );
-    window.emit(MIDI_DEVICE_CONNECTION_STATE, true)?;
-    midi_state.start_listening_to_device(move |wrapper| {
-        let monitoring_state = app_handle.state::<MonitoringState>();
+    connect_with_reason(player, window_label, app_handle, midi_state, conn, port, ConnectionChangeReason::Connected)
+}
+
+fn connect_with_reason(
+    player: PlayerSlot,
+    window_label: &str,
+    app_handle: AppHandle,
+    midi_state: State<'_, MidiState>,
+    conn: Box<dyn InputSource>,
+    port: Option<DevicePort>,
+    reason: ConnectionChangeReason
+) -> ServiceResult<()> {
+    let port_name = conn.name();
+    info!("Connecting to device {} for player {}", port_name, player);
+    let profile = DeviceProfile::for_port(&port_name);
+    info!("Using device profile {}", profile.name);
+    if player == MAIN_PLAYER {
+        app_handle
+            .state::<InstrumentState>()
+            .set_device_instrument(profile.instrument.clone())?;
+    }
+    let latency_state = app_handle.state::<LatencyState>();
+    let latency_ms = app_handle
+        .state::<StoreState>()
+        .retrieve_default::<u64>(&LatencyState::key(&port_name))?;
+    info!("Using input latency of {}ms", latency_ms);
+    latency_state.cancel_calibration(player);
+    latency_state.set_offset(player, Duration::from_millis(latency_ms))?;
+    midi_state.set_device_connection(player, conn)?;
+    let window_label = window_label.to_owned();
+    let pressure = PressureInput::default();
+    let input_filter = InputFilterHandle::new(profile.input_filter)?;
+    midi_state.set_input_filter(player, input_filter.clone())?;
+    let device_responses = DeviceResponses::default();
+    midi_state.set_device_responses(player, device_responses.clone())?;
+    let sampler_handle = app_handle.clone();
+    let sampler_label = window_label.clone();
+    let device_clock = DeviceClock::default();
+    let subscriptions = MidiSubscriptions::new().on_pressure(
+        profile.pressure_source,
+        pressure.clone()
+    ).on_device_responses(device_responses).on_filtered_notes(input_filter, move |mut wrapper| {
+        let received_at = device_clock.instant(wrapper.time(), Instant::now());
+        let latency_state = app_handle.state::<LatencyState>();
+        if wrapper.state == Note::STATE_ON {
+            latency_state.record_tap(player, received_at);
+        }
+        let performance_state = app_handle.state::<PerformanceState>();
+        let midi_state = app_handle.state::<MidiState>();
+        wrapper.air_strength = app_handle.state::<CalibrationState>().apply(player, wrapper.air_strength);
         let input_msg = format!(
             "{} - {} - {:?}",
             wrapper.state, wrapper.air_strength, wrapper.note
         );
-        info!("Received input: {}", input_msg);
-        if let Err(_) = monitoring_state.receive_breath_data(
+        info!("Received input from player {}: {}", player, input_msg);
+        if app_handle.state::<MonitoringState>().receive_breath_data(player, &wrapper).is_err() {
+            warn!("Error while monitoring breath of input {}", input_msg);
+        }
+        midi_state.forward_to_thru(&wrapper);
+        midi_state.forward_note_to_osc(player, &wrapper);
+        let played_at = latency_state.played_at(player, received_at);
+        performance_state.record(
+            wrapper.state == Note::STATE_ON,
+            wrapper.note.byte,
             wrapper.air_strength,
-            wrapper.state == Note::STATE_ON
-        ) {
-            warn!("Error while monitoring breath data {}", input_msg);
-        };
-        let payload = MidiPayload::from_midi_wrapper(wrapper);
-        let _ = app_handle.emit_to(&window_label, MIDI_NOTE, payload);
-    }).map_err(|e| {
-        let _ = window.emit(MIDI_DEVICE_CONNECTION_STATE, false);
-        e
-    })?;
+            played_at
+        );
+        app_handle.state::<SustainState>().played_note(
+            player,
+            wrapper.note.byte,
+            wrapper.state == Note::STATE_ON,
+            played_at
+        );
+        if wrapper.state == Note::STATE_ON && midi_state.is_midi_file_running() {
+            let judged = app_handle.state::<HitJudgeState>().played_note(player, wrapper.note.byte, played_at);
+            emit_judgements(&app_handle, &window_label, judged);
+        }
+        let instrument_state = app_handle.state::<InstrumentState>();
+        match instrument_state.note(wrapper.note.byte) {
+            Ok(note) => {
+                let payload = MidiPayload::from_midi_wrapper(wrapper, &note)
+                    .with_latency(latency_state.offset(player))
+                    .with_player(player);
+                let _ = app_handle.emit_to(&window_label, MIDI_NOTE, payload);
+            }
+            Err(_) => warn!("Note {} is not supported by the instrument, skipping...", wrapper.note.byte)
+        }
+    });
+    midi_state.start_listening_to_device(player, subscriptions)?;
+    info!("Successfully connected, listening for inputs...");
+    midi_state.set_connected_device(player, port_name.clone(), sampler_label.clone(), port)?;
+    let state = DeviceConnectionState::new(true, Some(port_name), reason).with_player(player);
+    sampler_handle.emit_to(&sampler_label, MIDI_DEVICE_CONNECTION_STATE, state)?;
+    let sampler = start_pressure_sampler(
+        player,
+        pressure,
+        profile.pressure_sample_rate,
+        sampler_label.clone(),
+        sampler_handle.clone()
+    );
+    midi_state.set_pressure_sampler(player, profile, sampler)?;
+    if let Err(e) = offer_resume(sampler_handle, sampler_label) {
+        error!("Could not offer to resume the game: {}", e.message);
+    }
     Ok(())
 }
 
-pub fn device_connection_listener(
-    connected_port_name: String,
+/// Envia a pressao do sopro do jogador, com a calibracao dele aplicada, para o
+/// [MonitoringState], para a saida OSC e para o front em uma taxa fixa.
+fn start_pressure_sampler(
+    player: PlayerSlot,
+    pressure: PressureInput,
+    sample_rate: u32,
     window_label: String,
     app_handle: AppHandle,
-) {
-    info!("Starting device health checker");
-    tokio::spawn(async move { 
-        let midi_state = app_handle.state::<MidiState>();
-        let duration = Duration::from_millis(MIDI_DEVICE_CONNECTION_CHECKER_TIMEOUT);
-        loop {
-            tokio::time::sleep(duration).await;
-            if !midi_state.has_connection() {
-                info!("Disconnecting device health checker");
-                break
+) -> PressureSampler {
+    PressureSampler::start(pressure, sample_rate, move |raw| {
+        let calibration_state = app_handle.state::<CalibrationState>();
+        calibration_state.receive_raw_sample(player, raw);
+        let sample = calibration_state.apply(player, raw);
+        let monitoring_state = app_handle.state::<MonitoringState>();
+        if let Err(_) = monitoring_state.receive_pressure_sample(player, sample) {
+            warn!("Error while monitoring breath sample {}", sample);
+        }
+        let zone_changes = app_handle.state::<SustainState>().receive_pressure(player, sample);
+        emit_zone_changes(&app_handle, &window_label, player, zone_changes);
+        app_handle.state::<MidiState>().forward_pressure_to_osc(player, sample);
+        let _ = app_handle.emit_to(&window_label, BREATH_PRESSURE, BreathPressure::new(player, sample));
+    })
+}
+
+/// Observa os dispositivos do computador, derrubando a conexao do jogador quando o dispositivo
+/// dele some e reconectando quando a ultima porta usada por ele volta
+pub fn start_device_watcher(app_handle: AppHandle) -> ServiceResult<()> {
+    info!("Starting device watcher");
+    let mut list_devices = device_lister()?;
+    let lister_handle = app_handle.clone();
+    let event_handle = app_handle.clone();
+    let watcher = DeviceWatcher::with_lister(
+        Duration::from_millis(MIDI_DEVICE_CONNECTION_CHECKER_TIMEOUT),
+        move || {
+            let midi_state = lister_handle.state::<MidiState>();
+            let mut devices = list_devices()?;
+            // uma placa serial que parou de responder continua na lista de portas
+            for player in midi_state.connected_players() {
+                if midi_state.is_connection_alive(player) {
+                    continue;
+                }
+                if let Ok(name) = midi_state.connected_port_name(player) {
+                    devices.retain(|d| d != &name);
+                }
+            }
+            Some(devices)
+        },
+        move |event| on_device_event(&event_handle, event)
+    );
+    app_handle.state::<MidiState>().set_device_watcher(watcher)
+}
+
+fn on_device_event(app_handle: &AppHandle, event: DeviceEvent) {
+    let midi_state = app_handle.state::<MidiState>();
+    match event {
+        DeviceEvent::Detached(name) => {
+            let Some(player) = midi_state.player_with_device(&name) else {
+                return;
+            };
+            let Ok((_, window_label)) = midi_state.connected_device(player) else {
+                return;
+            };
+            midi_state.drop_device_connection(player);
+            warn!("Device {} of player {} lost connection", name, player);
+            let state = DeviceConnectionState::new(false, Some(name), ConnectionChangeReason::DeviceDetached)
+                .with_player(player);
+            let _ = app_handle.emit_to(&window_label, MIDI_DEVICE_CONNECTION_STATE, state);
+            if let Err(e) = interrupt_game(app_handle, &window_label) {
+                error!("Could not pause the game: {}", e.message);
             }
-            let port_available = if let Ok(devices) = list_available_devices() {
-                devices.into_iter().any(|d| d.contains(&connected_port_name))
-            } else { 
-                false
+        }
+        DeviceEvent::Attached(name) => {
+            let Some((player, port, window_label)) = midi_state.reconnect_target(&name) else {
+                return;
             };
-            if !port_available {
-                let _ = app_handle.emit_to(
-                    &window_label, 
-                    MIDI_DEVICE_CONNECTION_STATE, 
-                    false
-                );
-                midi_state.drop_device_connection();
-                warn!("Midi device lost connection");
-                break
-            } else { 
-                info!("Device is still connected")
+            if midi_state.has_player_connection(player) {
+                return;
+            }
+            info!("Device {} of player {} is back, reconnecting...", name, player);
+            let result = port
+                .open()
+                .map_err(ServiceError::from)
+                .and_then(|conn| connect_with_reason(
+                    player,
+                    &window_label,
+                    app_handle.clone(),
+                    midi_state,
+                    conn,
+                    Some(port),
+                    ConnectionChangeReason::Reconnected
+                ));
+            if let Err(e) = result {
+                error!("Could not reconnect to {}: {}", name, e.message);
+                let state = DeviceConnectionState::new(false, Some(name), ConnectionChangeReason::ReconnectFailed)
+                    .with_player(player);
+                let _ = app_handle.emit_to(&window_label, MIDI_DEVICE_CONNECTION_STATE, state);
             }
         }
-    });
-}
\ No newline at end of file
+    }
+}
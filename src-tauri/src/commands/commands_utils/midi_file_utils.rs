@@ -1,38 +1,51 @@
+use crate::app_states::backing_track_state::BackingTrackState;
 use crate::app_states::current_music_score_state::CurrentMusicScoreState;
 use crate::app_states::database_state::DatabaseState;
+use crate::app_states::duet_state::DuetState;
+use crate::app_states::game_interruption_state::GameInterruptionState;
+use crate::app_states::hit_judge_state::HitJudgeState;
 use crate::app_states::midi_device_state::MidiState;
 use crate::app_states::monitoring_state::MonitoringState;
+use crate::app_states::sustain_state::SustainState;
 use crate::commands::commands_utils::database_queries::{music_list, ScoreSaver};
+use crate::commands::commands_utils::hit_judge_utils::{emit_judgements, emit_sustain, score_judgements};
+use crate::commands::payloads::device_connection::GameInterruption;
 use crate::commands::payloads::midi_payload::{MidiFileState, MidiPayload};
 use crate::commands::payloads::music::MidiMusic;
 use crate::commands::payloads::service_error::{ServiceError, ServiceResult};
 use crate::constants::dirs::MUSICS_FOLDER;
-use crate::constants::errors::{FILE_ID_NOT_FOUND, FILE_IS_NOT_A_MIDI, FILE_NOT_FOUND, FILE_TOO_LONG};
-use crate::constants::events_name::{MIDI_READ_NOTE, MIDI_READ_STATE};
+use crate::constants::errors::{DEVICE_NOT_CONNECTED, FILE_ID_NOT_FOUND, FILE_IS_NOT_A_MIDI, FILE_NOT_FOUND, FILE_TOO_LONG};
+use crate::constants::events_name::{GAME_INTERRUPTION, MIDI_READ_NOTE, MIDI_READ_STATE};
 use crate::constants::limits::MIDI_LENGTH_SECONDS_LIMIT;
 use crate::get_resources_path;
-use midi_reader::calculate_midi_length;
+use arduino_comm::instrument_profile::InstrumentProfile;
+use midi_reader::{calculate_midi_bytes_length, calculate_midi_length};
 use midi_reader::errors::MidiReaderError;
 use midi_reader::midi_file::PlayBackCallback;
 use midi_reader::player_wrapper::PlayerWrapper;
-use paris::{error, info, warn, Logger};
+use midi_reader::song_editor::EditableSong;
+use paris::{error, info, success, warn, Logger};
 use std::fs;
-use tauri::{AppHandle, Runtime, State, Window};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime, State, Window};
 
 pub(crate) struct SheetListener<'a> {
     window: &'a Window,
     ignore_note_errors: bool,
+    /// Instrumento usado para validar e nomear as notas da partitura
+    instrument: InstrumentProfile,
 }
 
 impl <'a> SheetListener<'a> {
-    pub fn new(window: &'a Window, ignore_errors: bool) -> Self {
-        Self { window, ignore_note_errors: ignore_errors }
+    pub fn new(window: &'a Window, ignore_errors: bool, instrument: InstrumentProfile) -> Self {
+        Self { window, ignore_note_errors: ignore_errors, instrument }
     }
 }
 
 impl <'a> PlayBackCallback for SheetListener<'a> {
     fn on_note(&self, on: bool, key: u8, vel: u8) -> bool {
-        let payload = match MidiPayload::from_note(key, vel, on) {
+        let payload = match MidiPayload::from_note(&self.instrument, key, vel, on) {
             Ok(p) => p,
             Err(err) => {
                 return if self.ignore_note_errors {
@@ -44,8 +57,22 @@ impl <'a> PlayBackCallback for SheetListener<'a> {
                 }
             }
         };
+        let player = self.window.state::<DuetState>().assign(on, key, vel);
+        let payload = payload.with_player(player);
         info!("Emitting MidiPayload: {}", payload);
-        self.window.emit(MIDI_READ_NOTE, payload).is_ok()
+        if self.window.emit(MIDI_READ_NOTE, payload).is_err() {
+            return false;
+        }
+        let sustain_state = self.window.state::<SustainState>();
+        if on && vel > 0 {
+            let tolerance = self.window.state::<CurrentMusicScoreState>().breath_zone_tolerance();
+            sustain_state.chart_note_on(player, key, vel, tolerance);
+            let judged = self.window.state::<HitJudgeState>().chart_note(player, key);
+            emit_judgements(self.window, self.window.label(), judged);
+        } else if let Some(result) = sustain_state.chart_note_off(player, key) {
+            emit_sustain(self.window, self.window.label(), result);
+        }
+        true
     }
 
     fn on_interrupted(&self) {
@@ -100,6 +127,23 @@ pub fn play_game(
     }
 }
 
+/// Notas das trilhas do jogador que o `instrument` nao consegue tocar
+pub fn unsupported_chart_keys(
+    file: &[u8],
+    instrument: &InstrumentProfile,
+    backing_state: &BackingTrackState,
+) -> ServiceResult<Vec<u8>> {
+    let song = EditableSong::from_bytes(file)?;
+    let keys: Vec<u8> = backing_state.read_mixer(move |m| {
+        song.notes()
+            .iter()
+            .filter(|n| m.is_player_track(n.track))
+            .map(|n| n.key)
+            .collect()
+    })?;
+    Ok(instrument.unsupported_keys(keys))
+}
+
 pub async fn read_music_from_id<R: Runtime>(
     db_state: &DatabaseState,
     handle: &AppHandle<R>,
@@ -114,10 +158,15 @@ pub async fn read_music_from_id<R: Runtime>(
     }
 }
 
-pub fn music<R: Runtime>(handle: &AppHandle<R>, music_name: &str) -> ServiceResult<Vec<u8>> {
+pub fn music_path<R: Runtime>(handle: &AppHandle<R>, music_name: &str) -> ServiceResult<PathBuf> {
     let mut path = get_resources_path(handle)?;
     path.push(MUSICS_FOLDER);
     path.push(music_name);
+    Ok(path)
+}
+
+pub fn music<R: Runtime>(handle: &AppHandle<R>, music_name: &str) -> ServiceResult<Vec<u8>> {
+    let path = music_path(handle, music_name)?;
     if let Ok(vec) = fs::read(&path) {
         Ok(vec)
     } else {
@@ -144,15 +193,33 @@ pub fn check_midi_file(
     }
 }
 
+pub fn check_midi_bytes(bytes: Vec<u8>) -> ServiceResult<u64> {
+    let duration_secs = calculate_midi_bytes_length(bytes)?.as_secs();
+    if duration_secs > MIDI_LENGTH_SECONDS_LIMIT {
+        Err(FILE_TOO_LONG.into())
+    } else {
+        Ok(duration_secs)
+    }
+}
+
 pub async fn end_game(
     finished: bool,
     midi_state: State<'_, MidiState>,
     score_state: State<'_, CurrentMusicScoreState>,
     db_state: State<'_, DatabaseState>,
     monitor_state: State<'_, MonitoringState>,
+    interruption_state: State<'_, GameInterruptionState>,
+    judge_state: State<'_, HitJudgeState>,
 ) -> ServiceResult<()> {
     let mut logger = Logger::new();
     let music_id = midi_state.current_midi_file_id()?;
+    if interruption_state.finish() {
+        finish_interruption(&monitor_state);
+    }
+    let missed = score_judgements(&score_state, &monitor_state, judge_state.finish());
+    if !missed.is_empty() {
+        logger.info(format!("{} chart notes were still open when the game ended", missed.len()));
+    }
     logger.info("Saving score and monitoring data...");
     db_state.save_score(finished, music_id, &score_state, &monitor_state).await?;
     logger.success("Finished saving score and monitoring data");
@@ -162,4 +229,98 @@ pub async fn end_game(
     midi_state.reset_midi_file()?;
     logger.success("Game states has been reset!");
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Pausa a musica quando o dispositivo desconecta no meio dela.
+///
+/// Se o jogo ja estava interrompido uma nova interrupcao é criada, cancelando
+/// a contagem para voltar que estiver rodando.
+pub fn interrupt_game(app_handle: &AppHandle, window_label: &str) -> ServiceResult<()> {
+    let interruption_state = app_handle.state::<GameInterruptionState>();
+    if !interruption_state.is_interrupted() {
+        let midi_state = app_handle.state::<MidiState>();
+        if !midi_state.is_midi_file_running() {
+            return Ok(());
+        }
+        midi_state.change_file_state(MidiFileState::PAUSED)?;
+        app_handle.state::<HitJudgeState>().clear();
+        app_handle.state::<SustainState>().clear();
+        if let Err(e) = app_handle.state::<MonitoringState>().start_interruption() {
+            warn!("Could not record the interruption: {}", e);
+        }
+        app_handle.emit_to(window_label, MIDI_READ_STATE, MidiFileState::PAUSED)?;
+        warn!("Device disconnected mid-song, game paused");
+    }
+    interruption_state.begin()?;
+    app_handle.emit_to(window_label, GAME_INTERRUPTION, GameInterruption::DeviceLost)?;
+    Ok(())
+}
+
+/// Oferece voltar o jogo interrompido quando o dispositivo conecta de novo,
+/// com a volta automatica ligada o jogo continua sozinho depois da contagem
+pub fn offer_resume(app_handle: AppHandle, window_label: String) -> ServiceResult<()> {
+    let interruption_state = app_handle.state::<GameInterruptionState>();
+    let Some(id) = interruption_state.current() else {
+        return Ok(());
+    };
+    let missing = app_handle.state::<MidiState>().missing_players();
+    if !missing.is_empty() {
+        info!("Waiting for the devices of players {:?} before resuming", missing);
+        return Ok(());
+    }
+    let Some(countdown) = interruption_state.auto_resume() else {
+        info!("Device is back, waiting for the player to resume the game");
+        app_handle.emit_to(&window_label, GAME_INTERRUPTION, GameInterruption::WaitingResume)?;
+        return Ok(());
+    };
+    info!("Device is back, resuming the game in {} seconds", countdown);
+    std::thread::spawn(move || {
+        for seconds_left in (1..=countdown).rev() {
+            if app_handle.state::<GameInterruptionState>().current() != Some(id) {
+                return;
+            }
+            let countdown = GameInterruption::ResumeCountdown(seconds_left);
+            let _ = app_handle.emit_to(&window_label, GAME_INTERRUPTION, countdown);
+            std::thread::sleep(Duration::from_secs(1));
+        }
+        if app_handle.state::<GameInterruptionState>().current() != Some(id) {
+            return;
+        }
+        let result = resume_interrupted_game(
+            &app_handle.state::<MidiState>(),
+            &app_handle.state::<GameInterruptionState>(),
+            &app_handle.state::<MonitoringState>(),
+        ).and_then(|_| {
+            app_handle.emit_to(&window_label, MIDI_READ_STATE, MidiFileState::PLAYING)?;
+            app_handle.emit_to(&window_label, GAME_INTERRUPTION, GameInterruption::Resumed)?;
+            Ok(())
+        });
+        if let Err(e) = result {
+            error!("Could not resume the game after the countdown: {}", e.message);
+        }
+    });
+    Ok(())
+}
+
+/// Volta a tocar a musica interrompida, erro se o dispositivo de algum jogador ainda nao voltou
+pub fn resume_interrupted_game(
+    midi_state: &MidiState,
+    interruption_state: &GameInterruptionState,
+    monitor_state: &MonitoringState,
+) -> ServiceResult<()> {
+    if !midi_state.has_connection() || !midi_state.missing_players().is_empty() {
+        return Err(DEVICE_NOT_CONNECTED.into());
+    }
+    if interruption_state.finish() {
+        midi_state.change_file_state(MidiFileState::PLAYING)?;
+        finish_interruption(monitor_state);
+        success!("Game resumed after the device reconnected");
+    }
+    Ok(())
+}
+
+fn finish_interruption(monitor_state: &MonitoringState) {
+    if let Err(e) = monitor_state.end_interruption() {
+        warn!("Could not record the end of the interruption: {}", e);
+    }
+}
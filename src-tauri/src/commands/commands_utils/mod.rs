@@ -1,3 +1,4 @@
 pub(super) mod midi_file_utils;
 pub(super) mod database_queries;
-pub(super) mod midi_connection_utils;
\ No newline at end of file
+pub(super) mod midi_connection_utils;
+pub(super) mod hit_judge_utils;
\ No newline at end of file
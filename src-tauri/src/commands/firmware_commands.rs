@@ -0,0 +1,41 @@
+use crate::app_states::midi_device_state::MidiState;
+use crate::commands::payloads::device_connection::{ConnectionChangeReason, DeviceConnectionState};
+use crate::commands::payloads::firmware::FirmwareUploadProgress;
+use crate::commands::payloads::service_error::ServiceResult;
+use crate::constants::events_name::{FIRMWARE_UPLOAD_PROGRESS, MIDI_DEVICE_CONNECTION_STATE};
+use arduino_comm::firmware_upload::{upload_firmware, FirmwareImage};
+use arduino_comm::serial_connection::serial_device_name;
+use paris::{info, success};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{State, Window};
+
+/// Grava o firmware (`.hex` ou `.bin`) na placa da porta serial pelo bootloader do Arduino.
+///
+/// Se a placa estiver conectada pela mesma porta ela é desconectada antes.
+#[tauri::command]
+pub async fn upload_device_firmware(
+    port_name: String,
+    firmware_path: String,
+    window: Window,
+    midi_state: State<'_, MidiState>,
+) -> ServiceResult<()> {
+    let image = FirmwareImage::load(&firmware_path)?;
+    let device_name = serial_device_name(&port_name);
+    if let Some(player) = midi_state.player_with_device(&device_name) {
+        info!("Disconnecting {} to upload the firmware", device_name);
+        midi_state.forget_reconnect_target(player);
+        midi_state.drop_device_connection(player);
+        let state = DeviceConnectionState::new(false, Some(device_name), ConnectionChangeReason::Disconnected)
+            .with_player(player);
+        window.emit(MIDI_DEVICE_CONNECTION_STATE, state)?;
+    }
+    info!("Uploading {} ({} bytes) to {}", firmware_path, image.len(), port_name);
+    let port = port_name.clone();
+    spawn_blocking(move || {
+        upload_firmware(&port, &image, |progress| {
+            let _ = window.emit(FIRMWARE_UPLOAD_PROGRESS, FirmwareUploadProgress::from(progress));
+        })
+    }).await??;
+    success!("Firmware uploaded to {}", port_name);
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use crate::app_states::backing_track_state::BackingTrackState;
+use crate::app_states::database_state::DatabaseState;
+use crate::app_states::instrument_state::InstrumentState;
+use crate::app_states::store_state::StoreState;
+use crate::app_states::user_state::{UserState, INSTRUMENT_KEY};
+use crate::commands::commands_utils::midi_file_utils::{read_music_from_id, unsupported_chart_keys};
+use crate::commands::payloads::instrument::InstrumentInfo;
+use crate::commands::payloads::service_error::ServiceResult;
+use paris::{info, success, warn};
+use persistence::storage::StorageError;
+use tauri::{AppHandle, Runtime, State};
+
+#[tauri::command]
+pub async fn list_instruments(
+    instrument_state: State<'_, InstrumentState>,
+) -> ServiceResult<Vec<InstrumentInfo>> {
+    Ok(instrument_state.profiles().iter().map(InstrumentInfo::from).collect())
+}
+
+#[tauri::command]
+pub async fn active_instrument(
+    instrument_state: State<'_, InstrumentState>,
+) -> ServiceResult<InstrumentInfo> {
+    Ok(InstrumentInfo::from(&instrument_state.active()?))
+}
+
+/// Escolhe o instrumento do usuario atual, [None] volta a usar o instrumento do dispositivo
+#[tauri::command]
+pub async fn select_instrument(
+    instrument_id: Option<String>,
+    instrument_state: State<'_, InstrumentState>,
+    user_state: State<'_, UserState>,
+    store_state: State<'_, StoreState>,
+) -> ServiceResult<InstrumentInfo> {
+    instrument_state.set_user_instrument(instrument_id.clone())?;
+    if let Some(user) = user_state.user()? {
+        let key = UserState::key(&user, INSTRUMENT_KEY);
+        match instrument_id.as_ref() {
+            Some(id) => store_state.save(&key, id)?,
+            None => match store_state.remove(&key) {
+                Ok(_) | Err(StorageError::KeyNotFound(_)) => {}
+                Err(e) => return Err(e.into()),
+            },
+        }
+        store_state.commit()?;
+        info!("Saved instrument {:?} for user {}", instrument_id, user);
+    }
+    active_instrument(instrument_state).await
+}
+
+/// Notas da partitura do paciente que o instrumento ativo nao consegue tocar
+#[tauri::command]
+pub async fn check_music_instrument<R: Runtime>(
+    music_id: i32,
+    db_state: State<'_, DatabaseState>,
+    instrument_state: State<'_, InstrumentState>,
+    backing_state: State<'_, BackingTrackState>,
+    handle: AppHandle<R>,
+) -> ServiceResult<Vec<u8>> {
+    let (music, file) = read_music_from_id(&db_state, &handle, music_id).await?;
+    let instrument = instrument_state.active()?;
+    let unsupported = unsupported_chart_keys(&file, &instrument, &backing_state)?;
+    if unsupported.is_empty() {
+        success!("{} can be played with {}", music, instrument.name);
+    } else {
+        warn!("{} has notes {:?} that {} cannot play", music, unsupported, instrument.name);
+    }
+    Ok(unsupported)
+}
@@ -0,0 +1,106 @@
+use crate::app_states::latency_state::LatencyState;
+use crate::app_states::midi_device_state::{MidiState, PlayerSlot};
+use crate::app_states::store_state::StoreState;
+use crate::commands::commands_utils::midi_connection_utils::player_slot;
+use crate::commands::payloads::service_error::ServiceResult;
+use crate::constants::errors::DEVICE_NOT_CONNECTED;
+use crate::constants::events_name::LATENCY_CLICK;
+use arduino_comm::latency::{LatencyCalibration, DEFAULT_CLICK_COUNT, DEFAULT_CLICK_INTERVAL};
+use paris::{info, success};
+use std::time::{Duration, Instant};
+use tauri::{Manager, State, Window};
+
+/// Começa a calibracao da latencia do dispositivo do jogador, sem jogador usa o principal.
+///
+/// Emite [LATENCY_CLICK] com o indice de cada clique e retorna a duracao da calibracao
+/// em milissegundos, depois dela chame [finish_latency_calibration]
+#[tauri::command]
+pub async fn start_latency_calibration(
+    interval_ms: Option<u64>,
+    clicks: Option<u32>,
+    player: Option<u8>,
+    window: Window,
+    midi_state: State<'_, MidiState>,
+    latency_state: State<'_, LatencyState>,
+) -> ServiceResult<u64> {
+    let player = player_slot(player)?;
+    if !midi_state.has_player_connection(player) {
+        return Err(DEVICE_NOT_CONNECTED.into());
+    }
+    let calibration = LatencyCalibration::new(
+        interval_ms.map(Duration::from_millis).unwrap_or(DEFAULT_CLICK_INTERVAL),
+        clicks.unwrap_or(DEFAULT_CLICK_COUNT)
+    )?;
+    let click_times = calibration.click_times();
+    let duration = calibration.duration();
+    info!("Starting latency calibration of player {} with {} clicks", player, click_times.len());
+    let started_at = latency_state.start_calibration(player, calibration)?;
+    std::thread::spawn(move || {
+        for (index, click) in click_times.into_iter().enumerate() {
+            std::thread::sleep((started_at + click).saturating_duration_since(Instant::now()));
+            if !window.state::<LatencyState>().is_calibrating(started_at) {
+                break;
+            }
+            let _ = window.emit(LATENCY_CLICK, index);
+        }
+    });
+    Ok(duration.as_millis() as u64)
+}
+
+/// Termina a calibracao, a latencia medida passa a ser usada e é salva para o dispositivo
+#[tauri::command]
+pub async fn finish_latency_calibration(
+    midi_state: State<'_, MidiState>,
+    latency_state: State<'_, LatencyState>,
+    store_state: State<'_, StoreState>,
+) -> ServiceResult<u32> {
+    let (player, latency) = latency_state.finish_calibration()?;
+    let latency_ms = latency.as_millis() as u32;
+    save_latency(player, &midi_state, &store_state, latency_ms)?;
+    Ok(latency_ms)
+}
+
+#[tauri::command]
+pub async fn cancel_latency_calibration(
+    player: Option<u8>,
+    latency_state: State<'_, LatencyState>,
+) -> ServiceResult<()> {
+    latency_state.cancel_calibration(player_slot(player)?);
+    Ok(())
+}
+
+/// Latencia de entrada do jogador usada agora, em milissegundos
+#[tauri::command]
+pub async fn input_latency(
+    player: Option<u8>,
+    latency_state: State<'_, LatencyState>,
+) -> ServiceResult<u32> {
+    Ok(latency_state.offset(player_slot(player)?).as_millis() as u32)
+}
+
+/// Troca a latencia do dispositivo do jogador sem calibrar
+#[tauri::command]
+pub async fn set_input_latency(
+    latency_ms: u32,
+    player: Option<u8>,
+    midi_state: State<'_, MidiState>,
+    latency_state: State<'_, LatencyState>,
+    store_state: State<'_, StoreState>,
+) -> ServiceResult<()> {
+    let player = player_slot(player)?;
+    latency_state.set_offset(player, Duration::from_millis(latency_ms as u64))?;
+    save_latency(player, &midi_state, &store_state, latency_ms)
+}
+
+fn save_latency(
+    player: PlayerSlot,
+    midi_state: &MidiState,
+    store_state: &StoreState,
+    latency_ms: u32
+) -> ServiceResult<()> {
+    let port_name = midi_state.connected_port_name(player)?;
+    store_state.save(&LatencyState::key(&port_name), &(latency_ms as u64))?;
+    store_state.commit()?;
+    success!("Saved input latency of {}ms for {}", latency_ms, port_name);
+    Ok(())
+}
@@ -1,16 +1,44 @@
-use crate::commands::commands_utils::midi_connection_utils::connect;
+use crate::commands::commands_utils::midi_connection_utils::{connect, player_slot};
+use crate::commands::payloads::device_config::{DeviceFirmwareInfo, DeviceSettings};
+use crate::commands::payloads::device_connection::{ConnectionChangeReason, DeviceConnectionState};
+use crate::commands::payloads::device_profile::DeviceProfileInfo;
+use crate::commands::payloads::input_filter::{InputFilterSettings, InputFilterStats};
+use crate::commands::payloads::midi_thru::MidiThruSettings;
 use crate::commands::payloads::service_error::{ServiceError, ServiceResult};
+use crate::constants::events_name::MIDI_DEVICE_CONNECTION_STATE;
 use crate::MidiState;
+use arduino_comm::device_watcher::DevicePort;
+use arduino_comm::input_recording::{InputRecording, ReplayInput};
 use arduino_comm::midi_connection::{connect_to_port, connect_to_port_with_name, list_available_devices};
+use arduino_comm::midi_thru::connect_thru_to_port_with_name;
+use arduino_comm::osc::{OscInput, OscOutput, DEFAULT_OSC_INPUT_ADDRESS, DEFAULT_OSC_INPUT_PORT};
+use arduino_comm::serial_connection::{list_serial_ports, DEFAULT_BAUD_RATE};
+use arduino_comm::virtual_input::{virtual_input, VIRTUAL_DEVICE_NAME};
 use paris::{error, info, success};
+use tauri::async_runtime::spawn_blocking;
 use tauri::{AppHandle, State, Window};
 
+/// Desconecta o dispositivo do jogador, sem jogador desconecta o principal
 #[tauri::command]
-pub fn disconnect_midi(state: State<MidiState>) -> bool {
-    state.drop_device_connection();
+pub fn disconnect_midi(player: Option<u8>, window: Window, state: State<MidiState>) -> bool {
+    let Ok(player) = player_slot(player) else {
+        return false;
+    };
+    let port_name = state.connected_port_name(player).ok();
+    state.forget_reconnect_target(player);
+    state.drop_device_connection(player);
+    let connection_state = DeviceConnectionState::new(false, port_name, ConnectionChangeReason::Disconnected)
+        .with_player(player);
+    let _ = window.emit(MIDI_DEVICE_CONNECTION_STATE, connection_state);
     true
 }
 
+/// Jogadores que tem um dispositivo conectado
+#[tauri::command]
+pub async fn connected_players(state: State<'_, MidiState>) -> ServiceResult<Vec<u8>> {
+    Ok(state.connected_players())
+}
+
 #[tauri::command]
 pub async fn list_midi_devices() -> ServiceResult<Vec<String>> {
     info!("Looking for available midi devices...");
@@ -29,20 +57,290 @@ pub async fn list_midi_devices() -> ServiceResult<Vec<String>> {
 #[tauri::command]
 pub async fn connect_to_midi(
     port_name: &str,
+    player: Option<u8>,
     window: Window,
     state: State<'_, MidiState>,
     app_handle: AppHandle,
 ) -> ServiceResult<()> {
+    let player = player_slot(player)?;
     let conn = connect_to_port_with_name(port_name)?;
-    connect(&window, app_handle, state, conn)
+    let port = DevicePort::Midi(conn.port_name.to_owned());
+    connect(player, window.label(), app_handle, state, Box::new(conn), Some(port))
 }
 
 #[tauri::command]
 pub async fn start_listening_midi(
+    player: Option<u8>,
     window: Window,
     state: State<'_, MidiState>,
     app_handle: AppHandle,
 ) -> ServiceResult<()> {
+    let player = player_slot(player)?;
     let conn = connect_to_port()?;
-    connect(&window, app_handle, state, conn)
+    let port = DevicePort::Midi(conn.port_name.to_owned());
+    connect(player, window.label(), app_handle, state, Box::new(conn), Some(port))
+}
+
+#[tauri::command]
+pub async fn list_serial_devices() -> ServiceResult<Vec<String>> {
+    info!("Looking for available serial devices...");
+    let ports = list_serial_ports()?;
+    success!("Found serial devices: {:?}", ports);
+    Ok(ports)
+}
+
+/// Conecta a placa direto pela serial, para placas sem firmware midi
+#[tauri::command]
+pub async fn connect_to_serial(
+    port_name: &str,
+    baud_rate: Option<u32>,
+    player: Option<u8>,
+    window: Window,
+    state: State<'_, MidiState>,
+    app_handle: AppHandle,
+) -> ServiceResult<()> {
+    let port = DevicePort::Serial {
+        port_name: port_name.to_owned(),
+        baud_rate: baud_rate.unwrap_or(DEFAULT_BAUD_RATE),
+    };
+    let player = player_slot(player)?;
+    let conn = port.open()?;
+    connect(player, window.label(), app_handle, state, conn, Some(port))
+}
+
+/// Conecta um dispositivo virtual, permite jogar pelo teclado sem o instrumento
+#[tauri::command]
+pub async fn connect_virtual_midi(
+    player: Option<u8>,
+    window: Window,
+    state: State<'_, MidiState>,
+    app_handle: AppHandle,
+) -> ServiceResult<()> {
+    let player = player_slot(player)?;
+    let (input, sender) = virtual_input(VIRTUAL_DEVICE_NAME);
+    connect(player, window.label(), app_handle, state.clone(), Box::new(input), None)?;
+    state.set_virtual_input(player, sender)
+}
+
+#[tauri::command]
+pub async fn virtual_midi_input(
+    key: u8,
+    velocity: u8,
+    on: bool,
+    player: Option<u8>,
+    state: State<'_, MidiState>,
+) -> ServiceResult<()> {
+    let sender = state.virtual_input(player_slot(player)?)?;
+    if on {
+        sender.note_on(key, velocity)?;
+    } else {
+        sender.note_off(key)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn connected_device_profile(
+    player: Option<u8>,
+    state: State<'_, MidiState>,
+) -> ServiceResult<DeviceProfileInfo> {
+    Ok(state.device_profile(player_slot(player)?)?.into())
+}
+
+/// Le a identificacao e a versao do firmware da placa do jogador
+#[tauri::command]
+pub async fn connected_device_info(
+    player: Option<u8>,
+    state: State<'_, MidiState>,
+) -> ServiceResult<DeviceFirmwareInfo> {
+    let player = player_slot(player)?;
+    let mut client = state.device_config_client(player)?;
+    let info = spawn_blocking(move || client.device_info()).await??;
+    Ok(DeviceFirmwareInfo::new(state.connected_port_name(player)?, info))
+}
+
+#[tauri::command]
+pub async fn device_settings(
+    player: Option<u8>,
+    state: State<'_, MidiState>,
+) -> ServiceResult<DeviceSettings> {
+    let mut client = state.device_config_client(player_slot(player)?)?;
+    Ok(spawn_blocking(move || client.config()).await??.into())
+}
+
+/// Grava a configuracao no firmware da placa, retorna o que ela passou a usar
+#[tauri::command]
+pub async fn write_device_settings(
+    settings: DeviceSettings,
+    player: Option<u8>,
+    state: State<'_, MidiState>,
+) -> ServiceResult<DeviceSettings> {
+    let mut client = state.device_config_client(player_slot(player)?)?;
+    let written = spawn_blocking(move || client.write_config(settings.into())).await??;
+    success!("Device configuration written: {:?}", written);
+    Ok(written.into())
+}
+
+#[tauri::command]
+pub async fn input_filter_settings(
+    player: Option<u8>,
+    state: State<'_, MidiState>,
+) -> ServiceResult<InputFilterSettings> {
+    Ok(state.input_filter(player_slot(player)?)?.config().into())
+}
+
+/// Troca o filtro das notas do dispositivo do jogador, vale ate ele desconectar
+#[tauri::command]
+pub async fn update_input_filter(
+    settings: InputFilterSettings,
+    player: Option<u8>,
+    state: State<'_, MidiState>,
+) -> ServiceResult<InputFilterSettings> {
+    state.input_filter(player_slot(player)?)?.set_config(settings.into())?;
+    info!("Input filter updated to {:?}", settings);
+    input_filter_settings(player, state).await
+}
+
+#[tauri::command]
+pub async fn input_filter_stats(
+    player: Option<u8>,
+    state: State<'_, MidiState>,
+) -> ServiceResult<InputFilterStats> {
+    Ok(state.input_filter(player_slot(player)?)?.stats().into())
+}
+
+#[tauri::command]
+pub async fn reset_input_filter_stats(
+    player: Option<u8>,
+    state: State<'_, MidiState>,
+) -> ServiceResult<()> {
+    state.input_filter(player_slot(player)?)?.reset_stats();
+    Ok(())
+}
+
+/// Recebe notas e pressao de outros programas por OSC na porta UDP, como se fosse um dispositivo.
+/// Sem `bind_address` so escuta o proprio computador
+#[tauri::command]
+pub async fn connect_osc_input(
+    port: Option<u16>,
+    bind_address: Option<String>,
+    player: Option<u8>,
+    window: Window,
+    state: State<'_, MidiState>,
+    app_handle: AppHandle,
+) -> ServiceResult<()> {
+    let player = player_slot(player)?;
+    let address = bind_address.as_deref().unwrap_or(DEFAULT_OSC_INPUT_ADDRESS);
+    let input = OscInput::bind((address, port.unwrap_or(DEFAULT_OSC_INPUT_PORT)))?;
+    connect(player, window.label(), app_handle, state, Box::new(input), None)
+}
+
+/// Envia as notas e a pressao de todos os jogadores por OSC para `target` (`ip:porta`),
+/// retorna o endereco usado
+#[tauri::command]
+pub async fn enable_osc_output(
+    target: &str,
+    state: State<'_, MidiState>,
+) -> ServiceResult<String> {
+    state.set_osc_output(None)?;
+    let output = OscOutput::connect(target)?;
+    let address = output.target().to_string();
+    state.set_osc_output(Some(output))?;
+    success!("Sending OSC messages to {}", address);
+    Ok(address)
+}
+
+#[tauri::command]
+pub async fn disable_osc_output(state: State<'_, MidiState>) -> ServiceResult<()> {
+    state.set_osc_output(None)?;
+    info!("OSC output disabled");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn osc_output_target(state: State<'_, MidiState>) -> ServiceResult<Option<String>> {
+    state.osc_output_target()
+}
+
+#[tauri::command]
+pub async fn enable_midi_thru(
+    settings: MidiThruSettings,
+    state: State<'_, MidiState>,
+) -> ServiceResult<MidiThruSettings> {
+    let Some(port_name) = settings.port_name.as_ref() else {
+        state.set_midi_thru(None)?;
+        info!("Midi thru disabled");
+        return Ok(MidiThruSettings::default());
+    };
+    state.set_midi_thru(None)?;
+    let thru = connect_thru_to_port_with_name(port_name, settings.config())?;
+    let connected = MidiThruSettings::new(Some(thru.port_name.to_owned()), thru.config());
+    state.set_midi_thru(Some(thru))?;
+    success!("Midi thru enabled on {}", port_name);
+    Ok(connected)
+}
+
+#[tauri::command]
+pub async fn update_midi_thru(
+    settings: MidiThruSettings,
+    state: State<'_, MidiState>,
+) -> ServiceResult<MidiThruSettings> {
+    state.update_midi_thru_config(settings.config())?;
+    midi_thru_settings(state).await
+}
+
+#[tauri::command]
+pub async fn disable_midi_thru(state: State<'_, MidiState>) -> ServiceResult<()> {
+    state.set_midi_thru(None)?;
+    info!("Midi thru disabled");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn midi_thru_settings(state: State<'_, MidiState>) -> ServiceResult<MidiThruSettings> {
+    if let Some((port, config)) = state.midi_thru_config()? {
+        Ok(MidiThruSettings::new(Some(port), config))
+    } else {
+        Ok(MidiThruSettings::default())
+    }
+}
+
+/// Grava os bytes recebidos do dispositivo do jogador principal no arquivo
+#[tauri::command]
+pub async fn start_input_recording(
+    output_path: &str,
+    state: State<'_, MidiState>,
+) -> ServiceResult<()> {
+    state.input_recorder().start(output_path)?;
+    info!("Recording device input to {}", output_path);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_input_recording(state: State<'_, MidiState>) -> ServiceResult<u64> {
+    let count = state.input_recorder().stop()?;
+    success!("Recorded {} device inputs", count);
+    Ok(count)
+}
+
+/// Conecta uma sessao gravada como se fosse o dispositivo do jogador
+#[tauri::command]
+pub async fn replay_input_session(
+    input_path: &str,
+    speed: f32,
+    player: Option<u8>,
+    window: Window,
+    state: State<'_, MidiState>,
+    app_handle: AppHandle,
+) -> ServiceResult<()> {
+    let recording = InputRecording::load(input_path)?;
+    info!(
+        "Replaying {} inputs ({:?}) from {}",
+        recording.inputs.len(),
+        recording.duration(),
+        input_path
+    );
+    let player = player_slot(player)?;
+    let replay = ReplayInput::new(input_path, recording, speed)?;
+    connect(player, window.label(), app_handle, state, Box::new(replay), None)
 }
@@ -1,57 +1,119 @@
 use super::payloads::{
+    device_connection::GameInterruption,
     midi_payload::MidiFileState,
     music::{MidiMusic, MidiMusicList},
 };
+use crate::app_states::backing_track_state::BackingTrackState;
 use crate::app_states::current_music_score_state::CurrentMusicScoreState;
 use crate::app_states::database_state::DatabaseState;
+use crate::app_states::duet_state::{DuetState, DUET_PARTNER};
+use crate::app_states::game_interruption_state::GameInterruptionState;
+use crate::app_states::hit_judge_state::HitJudgeState;
+use crate::app_states::instrument_state::InstrumentState;
 use crate::app_states::monitoring_state::MonitoringState;
-use crate::commands::commands_utils::database_queries::music_list;
-use crate::commands::commands_utils::midi_file_utils::{check_midi_file, end_game as finish, load_file, play_game, read_music_from_id, SheetListener};
+use crate::app_states::performance_state::PerformanceState;
+use crate::app_states::scoring_state::ScoringState;
+use crate::app_states::sustain_state::SustainState;
+use crate::commands::commands_utils::database_queries::{best_grades, music_list};
+use crate::commands::commands_utils::hit_judge_utils::start_judge_ticker;
+use crate::commands::commands_utils::midi_file_utils::{check_midi_file, end_game as finish, load_file, play_game, read_music_from_id, resume_interrupted_game, unsupported_chart_keys, SheetListener};
 use crate::commands::payloads::service_error::ServiceResult;
-use crate::constants::errors::{FILE_COULD_NOT_READ_PATH, FILE_ID_NOT_FOUND, FILE_NAME_ALREADY_EXIST, FILE_NOT_FOUND};
+use crate::constants::errors::{FILE_COULD_NOT_READ_PATH, FILE_ID_NOT_FOUND, FILE_NAME_ALREADY_EXIST, FILE_NOT_FOUND, FILE_NO_PERFORMANCE_RECORDED};
 use crate::{
     app_states::midi_device_state::MidiState,
-    constants::events_name::MIDI_READ_STATE,
+    constants::events_name::{GAME_INTERRUPTION, MIDI_READ_STATE},
     get_resources_path,
 };
 use convert_case::{Case, Casing};
 use entity::prelude::{Music, Score};
 use entity::{music, score};
+use midi_reader::audio_renderer::{render_events_to_wav, render_midi_bytes_to_wav};
 use midi_reader::midi_file::{MidiFile, MidiFilePlayer};
 use paris::{info, success, Logger};
 use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, ModelTrait, QueryFilter, TransactionTrait};
 use std::fs;
 use std::fs::exists;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tauri::{AppHandle, Runtime, State, Window};
 
 #[tauri::command]
 pub async fn list_musics(db_state: State<'_, DatabaseState>) -> ServiceResult<MidiMusicList> {
     info!("Fetching music list...");
-    let list = music_list(&db_state).await?;
+    let mut list = music_list(&db_state).await?;
+    let grades = best_grades(&db_state).await?;
+    for music in list.files.iter_mut() {
+        music.best_grade = grades.get(&music.id).copied();
+    }
     info!("List fetched: {:?}", list);
     Ok(list)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn start_game<R: Runtime>(
     music_id: i32,
     midi_state: State<'_, MidiState>,
     score_state: State<'_, CurrentMusicScoreState>,
     db_state: State<'_, DatabaseState>,
+    performance_state: State<'_, PerformanceState>,
+    backing_state: State<'_, BackingTrackState>,
+    monitor_state: State<'_, MonitoringState>,
+    instrument_state: State<'_, InstrumentState>,
+    duet_state: State<'_, DuetState>,
+    judge_state: State<'_, HitJudgeState>,
+    scoring_state: State<'_, ScoringState>,
+    sustain_state: State<'_, SustainState>,
+    interruption_state: State<'_, GameInterruptionState>,
     handle: AppHandle<R>,
     window: Window,
 ) -> ServiceResult<()> {
     let mut logger = Logger::new();
     midi_state.is_playing_midi_file()?;
     let file = load_file(music_id, &*db_state, handle, &mut logger).await?;
+    let instrument = instrument_state.active()?;
+    backing_state.update_mixer(|m| m.load_midi_bytes(&file))?;
+    logger.info(format!("Validating chart notes with instrument {}", instrument.name));
+    match unsupported_chart_keys(&file, &instrument, &backing_state) {
+        Ok(keys) if keys.is_empty() => logger.success("Every chart note can be played"),
+        // as notas que o instrumento nao toca sao puladas pelo SheetListener
+        Ok(keys) => logger.warn(format!("Skipping chart notes {:?} that {} cannot play", keys, instrument.name)),
+        Err(e) => logger.warn(format!("Could not validate the chart notes: {}", e.message)),
+    };
+    let backing = backing_state.connect()?;
+    if backing.is_some() {
+        logger.info("Sending backing track to the selected midi output");
+    }
     let p = midi_state.create_new_file_player(
         music_id,
         file,
-        SheetListener::new(&window, true)
+        SheetListener::new(&window, true, instrument),
+        backing_state.mixer(),
+        backing
     )?;
+    let scoring_profile = scoring_state.active()?;
+    logger.info(format!("Scoring with profile {}", scoring_profile.id));
+    score_state.set_profile(scoring_profile);
     score_state.reset();
+    monitor_state.reset();
+    judge_state.clear();
+    sustain_state.clear();
+    // Uma interrupcao da musica anterior nao pode pausar a nova
+    interruption_state.finish();
+    duet_state.restart();
+    if let Some(notes_per_turn) = duet_state.notes_per_turn() {
+        logger.info(format!("Duet mode on, players take turns every {} notes", notes_per_turn));
+        if !midi_state.has_player_connection(DUET_PARTNER) {
+            logger.warn("The second player has no device connected");
+        }
+    }
+    performance_state.start();
     let _ = window.emit(MIDI_READ_STATE, MidiFileState::PLAYING);
-    play_game(p, &mut logger)?;
+    let judging = Arc::new(AtomicBool::new(true));
+    start_judge_ticker(window.clone(), judging.clone());
+    let played = play_game(p, &mut logger);
+    judging.store(false, Ordering::Relaxed);
+    played?;
     Ok(())
 }
 
@@ -61,17 +123,25 @@ pub async fn end_game(
     score_state: State<'_, CurrentMusicScoreState>,
     db_state: State<'_, DatabaseState>,
     monitor_state: State<'_, MonitoringState>,
+    performance_state: State<'_, PerformanceState>,
+    interruption_state: State<'_, GameInterruptionState>,
+    judge_state: State<'_, HitJudgeState>,
 ) -> ServiceResult<()> {
-    finish(true, midi_state, score_state, db_state, monitor_state).await
+    performance_state.stop();
+    finish(true, midi_state, score_state, db_state, monitor_state, interruption_state, judge_state).await
 }
 
 #[tauri::command]
 pub async fn pause_game(
     midi_state: State<'_, MidiState>,
+    judge_state: State<'_, HitJudgeState>,
+    sustain_state: State<'_, SustainState>,
     window: Window
 ) -> ServiceResult<()> {
     info!("Pause called...");
     midi_state.change_file_state(MidiFileState::PAUSED)?;
+    judge_state.clear();
+    sustain_state.clear();
     window.emit(MIDI_READ_STATE, MidiFileState::PAUSED)?;
     success!("Midi file playback paused successfully");
     Ok(())
@@ -80,10 +150,17 @@ pub async fn pause_game(
 #[tauri::command]
 pub async fn resume_game(
     midi_state: State<'_, MidiState>,
+    interruption_state: State<'_, GameInterruptionState>,
+    monitor_state: State<'_, MonitoringState>,
     window: Window,
 ) -> ServiceResult<()> {
     info!("Resume called...");
-    midi_state.change_file_state(MidiFileState::PLAYING)?;
+    if interruption_state.is_interrupted() {
+        resume_interrupted_game(&midi_state, &interruption_state, &monitor_state)?;
+        window.emit(GAME_INTERRUPTION, GameInterruption::Resumed)?;
+    } else {
+        midi_state.change_file_state(MidiFileState::PLAYING)?;
+    }
     window.emit(MIDI_READ_STATE, MidiFileState::PLAYING)?;
     success!("Midi file playback resumed successfully");
     Ok(())
@@ -95,11 +172,47 @@ pub async fn stop_game(
     score_state: State<'_, CurrentMusicScoreState>,
     db_state: State<'_, DatabaseState>,
     monitor_state: State<'_, MonitoringState>,
+    performance_state: State<'_, PerformanceState>,
+    interruption_state: State<'_, GameInterruptionState>,
+    judge_state: State<'_, HitJudgeState>,
 ) -> ServiceResult<()> {
     info!("Stop called...");
+    performance_state.stop();
     midi_state.change_file_state(MidiFileState::INTERRUPTED)?;
     success!("Midi file playback stopped successfully");
-    finish(false, midi_state, score_state, db_state, monitor_state).await
+    finish(false, midi_state, score_state, db_state, monitor_state, interruption_state, judge_state).await
+}
+
+/// Segundos de contagem para o jogo voltar sozinho quando o dispositivo reconectar
+/// depois de uma interrupcao, [None] espera o `resume_game`
+#[tauri::command]
+pub async fn set_game_auto_resume(
+    countdown_seconds: Option<u32>,
+    interruption_state: State<'_, GameInterruptionState>,
+) -> ServiceResult<()> {
+    interruption_state.set_auto_resume(countdown_seconds)?;
+    info!("Game auto resume countdown set to {:?}", countdown_seconds);
+    Ok(())
+}
+
+/// Liga o modo dueto, a partitura é dividida entre o jogador 0 e o 1 e eles se revezam
+/// a cada `notes_per_turn` notas. [None] desliga. Vale na hora, a vez volta para o jogador 0
+#[tauri::command]
+pub async fn set_duet_mode(
+    notes_per_turn: Option<u32>,
+    duet_state: State<'_, DuetState>,
+) -> ServiceResult<()> {
+    match notes_per_turn {
+        Some(n) => duet_state.enable(n)?,
+        None => duet_state.disable()
+    }
+    info!("Duet mode set to {:?}", notes_per_turn);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn duet_mode(duet_state: State<'_, DuetState>) -> ServiceResult<Option<u32>> {
+    Ok(duet_state.notes_per_turn())
 }
 
 #[tauri::command]
@@ -188,3 +301,34 @@ pub async fn remove_music<R: Runtime>(
     logger.done().success(format!("Midi file with id {} removed", music_id));
     Ok(())
 }
+
+#[tauri::command]
+pub async fn render_music_preview<R: Runtime>(
+    music_id: i32,
+    output_path: &str,
+    db_state: State<'_, DatabaseState>,
+    handle: AppHandle<R>,
+) -> ServiceResult<()> {
+    let mut logger = Logger::new();
+    logger.info(format!("Rendering music with id {} to {}...", music_id, output_path));
+    let (_, f) = read_music_from_id(&db_state, &handle, music_id).await?;
+    render_midi_bytes_to_wav(f, output_path)?;
+    logger.success("Music preview rendered");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn render_performance(
+    output_path: &str,
+    performance_state: State<'_, PerformanceState>,
+) -> ServiceResult<()> {
+    let mut logger = Logger::new();
+    let events = performance_state.events();
+    if events.is_empty() {
+        return Err(FILE_NO_PERFORMANCE_RECORDED.into());
+    }
+    logger.info(format!("Rendering {} recorded inputs to {}...", events.len(), output_path));
+    render_events_to_wav(&events, output_path)?;
+    logger.success("Performance rendered");
+    Ok(())
+}
@@ -1,8 +1,19 @@
+pub mod backing_track_commands;
+pub mod calibration_commands;
+pub mod firmware_commands;
+pub mod instrument_commands;
+pub mod latency_commands;
 pub mod midi_connection_commands;
 pub mod midi_reader_commands;
 mod payloads;
+pub use commands_utils::midi_connection_utils::start_device_watcher;
+pub use payloads::grade::GradeThresholds;
+pub use payloads::hit_windows::HitWindows;
 pub use payloads::midi_payload::MidiFileState;
-pub use payloads::on_note_data::OnNotePrecision;
+pub use payloads::on_note_data::{BreathZone, OnNotePrecision};
+pub use payloads::scoring_profile::{ScoringProfile, DEFAULT_SCORING_PROFILE_ID};
 pub use payloads::service_error::ServiceResult;
 pub mod score_commands;
+pub mod song_editor_commands;
+pub mod user_commands;
 mod commands_utils;
@@ -0,0 +1,67 @@
+use midi_reader::backing_track::TrackMixer;
+use midi_reader::song_editor::EditableSong;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Configuracao atual do acompanhamento
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/BackingTrackConfig.ts")]
+pub struct BackingTrackConfig {
+    pub output_port: Option<String>,
+    /// Trilhas que vao para a partitura do paciente, vazio significa todas
+    pub player_tracks: Vec<usize>,
+    pub muted_tracks: Vec<usize>,
+    pub solo_tracks: Vec<usize>,
+}
+
+/// Uma trilha da musica com notas, identificada pelo index dela no arquivo
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/MusicTrack.ts")]
+pub struct MusicTrack {
+    pub track: usize,
+    /// Canais midi usados pelas notas da trilha
+    pub channels: Vec<u8>,
+    pub note_count: u32,
+    pub lowest_key: u8,
+    pub highest_key: u8,
+}
+
+impl BackingTrackConfig {
+    pub fn new(output_port: Option<String>, mixer: &TrackMixer) -> Self {
+        Self {
+            output_port,
+            player_tracks: mixer.player_tracks(),
+            muted_tracks: mixer.muted_tracks(),
+            solo_tracks: mixer.solo_tracks(),
+        }
+    }
+}
+
+impl MusicTrack {
+    pub fn from_song(song: &EditableSong) -> Vec<Self> {
+        let mut tracks: Vec<Self> = vec![];
+        for note in song.notes() {
+            if let Some(t) = tracks.iter_mut().find(|t| t.track == note.track) {
+                t.note_count += 1;
+                t.lowest_key = t.lowest_key.min(note.key);
+                t.highest_key = t.highest_key.max(note.key);
+                if !t.channels.contains(&note.channel) {
+                    t.channels.push(note.channel);
+                }
+            } else {
+                tracks.push(Self {
+                    track: note.track,
+                    channels: vec![note.channel],
+                    note_count: 1,
+                    lowest_key: note.key,
+                    highest_key: note.key,
+                });
+            }
+        }
+        for t in tracks.iter_mut() {
+            t.channels.sort_unstable();
+        }
+        tracks.sort_by_key(|t| t.track);
+        tracks
+    }
+}
@@ -0,0 +1,58 @@
+use arduino_comm::breath_calibration::{BreathCalibration, CalibrationStep};
+use persistence::storage::{StorageRetrievable, StorageSavable};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/BreathCalibrationStep.ts")]
+/// Etapa da calibracao do sopro
+pub enum BreathCalibrationStep {
+    /// Paciente sem soprar, mede o ruido do sensor
+    Resting,
+    /// Paciente soprando de forma confortavel
+    Comfortable,
+    /// Paciente soprando o mais forte que conseguir
+    Maximum,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/BreathCalibrationInfo.ts")]
+/// Curva de resposta do sopro do paciente, valores crus do dispositivo (0 a 127)
+pub struct BreathCalibrationInfo {
+    pub noise_floor: u8,
+    pub comfortable: u8,
+    pub maximum: u8,
+}
+
+impl StorageSavable for BreathCalibrationInfo {}
+impl StorageRetrievable for BreathCalibrationInfo {}
+
+impl From<BreathCalibrationStep> for CalibrationStep {
+    fn from(value: BreathCalibrationStep) -> Self {
+        match value {
+            BreathCalibrationStep::Resting => Self::Resting,
+            BreathCalibrationStep::Comfortable => Self::Comfortable,
+            BreathCalibrationStep::Maximum => Self::Maximum,
+        }
+    }
+}
+
+impl From<BreathCalibration> for BreathCalibrationInfo {
+    fn from(value: BreathCalibration) -> Self {
+        Self {
+            noise_floor: value.noise_floor,
+            comfortable: value.comfortable,
+            maximum: value.maximum,
+        }
+    }
+}
+
+impl From<BreathCalibrationInfo> for BreathCalibration {
+    fn from(value: BreathCalibrationInfo) -> Self {
+        Self {
+            noise_floor: value.noise_floor,
+            comfortable: value.comfortable,
+            maximum: value.maximum,
+        }
+    }
+}
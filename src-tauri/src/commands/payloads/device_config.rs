@@ -0,0 +1,91 @@
+use arduino_comm::device_config::{DeviceConfig, DeviceInfo, NoteMapping, SensorThresholds};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/DeviceFirmwareInfo.ts")]
+/// Identificacao da placa conectada, lida do firmware
+pub struct DeviceFirmwareInfo {
+    pub port_name: String,
+    pub device_id: u16,
+    /// `major.minor.patch`
+    pub firmware_version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/DeviceSensorThresholds.ts")]
+/// Sensibilidade dos sensores da placa, de 0 a 127
+pub struct DeviceSensorThresholds {
+    pub breath_on: u8,
+    /// Nao pode ser maior que `breath_on`
+    pub breath_off: u8,
+    pub key_touch: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/DeviceNoteMapping.ts")]
+/// Posicao dos dedos (um bit por furo, 1 é tapado) e a nota tocada com ela
+pub struct DeviceNoteMapping {
+    pub fingering: u16,
+    pub note: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/DeviceSettings.ts")]
+/// Configuracao gravada no firmware da placa
+pub struct DeviceSettings {
+    pub thresholds: DeviceSensorThresholds,
+    pub note_map: Vec<DeviceNoteMapping>,
+}
+
+impl DeviceFirmwareInfo {
+    pub fn new(port_name: String, info: DeviceInfo) -> Self {
+        Self {
+            port_name,
+            device_id: info.device_id,
+            firmware_version: info.firmware_version.to_string(),
+        }
+    }
+}
+
+impl From<DeviceConfig> for DeviceSettings {
+    fn from(value: DeviceConfig) -> Self {
+        let t = value.thresholds;
+        Self {
+            thresholds: DeviceSensorThresholds {
+                breath_on: t.breath_on,
+                breath_off: t.breath_off,
+                key_touch: t.key_touch,
+            },
+            note_map: value
+                .note_map
+                .into_iter()
+                .map(|m| DeviceNoteMapping {
+                    fingering: m.fingering,
+                    note: m.note,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<DeviceSettings> for DeviceConfig {
+    fn from(value: DeviceSettings) -> Self {
+        let t = value.thresholds;
+        Self {
+            thresholds: SensorThresholds {
+                breath_on: t.breath_on,
+                breath_off: t.breath_off,
+                key_touch: t.key_touch,
+            },
+            note_map: value
+                .note_map
+                .into_iter()
+                .map(|m| NoteMapping {
+                    fingering: m.fingering,
+                    note: m.note,
+                })
+                .collect(),
+        }
+    }
+}
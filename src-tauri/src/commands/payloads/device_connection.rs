@@ -0,0 +1,59 @@
+use serde::Serialize;
+use ts_rs::TS;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../src/app/core/model/ConnectionChangeReason.ts")]
+/// Motivo da mudanca na conexao com o dispositivo
+pub enum ConnectionChangeReason {
+    /// Conectado pelo usuario
+    Connected,
+    /// Desconectado pelo usuario
+    Disconnected,
+    /// O dispositivo foi removido ou parou de responder
+    DeviceDetached,
+    /// O dispositivo voltou e foi reconectado automaticamente
+    Reconnected,
+    /// O dispositivo voltou mas nao foi possivel reconectar
+    ReconnectFailed,
+}
+
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/DeviceConnectionState.ts")]
+/// Enviado no evento de conexao sempre que o dispositivo conecta ou desconecta
+pub struct DeviceConnectionState {
+    pub connected: bool,
+    pub port_name: Option<String>,
+    pub reason: ConnectionChangeReason,
+    /// Jogador dono do dispositivo
+    pub player: u8,
+}
+
+impl DeviceConnectionState {
+    pub fn new(connected: bool, port_name: Option<String>, reason: ConnectionChangeReason) -> Self {
+        Self {
+            connected,
+            port_name,
+            reason,
+            player: 0,
+        }
+    }
+
+    pub fn with_player(mut self, player: u8) -> Self {
+        self.player = player;
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../src/app/core/model/GameInterruption.ts")]
+/// Jogo pausado porque o dispositivo desconectou no meio da musica
+pub enum GameInterruption {
+    /// O dispositivo desconectou e a musica foi pausada
+    DeviceLost,
+    /// O dispositivo voltou, o jogo espera o `resume_game`
+    WaitingResume,
+    /// O dispositivo voltou e o jogo continua sozinho, com os segundos que faltam
+    ResumeCountdown(u32),
+    /// A musica voltou a tocar
+    Resumed,
+}
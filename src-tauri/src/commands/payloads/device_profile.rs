@@ -0,0 +1,77 @@
+use arduino_comm::breath_pressure::PressureSource;
+use arduino_comm::device_profile::DeviceProfile;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/BreathPressureSource.ts")]
+/// De onde vem a pressao do sopro do dispositivo
+pub enum BreathPressureSource {
+    NoteVelocity,
+    ControlChange(u8),
+    ChannelPressure,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/DeviceProfileInfo.ts")]
+/// Perfil usado para ler o dispositivo conectado
+pub struct DeviceProfileInfo {
+    pub name: String,
+    pub port_pattern: String,
+    pub pressure_source: BreathPressureSource,
+    /// Amostras de pressao por segundo
+    pub pressure_sample_rate: u32,
+}
+
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/BreathCurve.ts")]
+/// Amostras de pressao do sopro desde o inicio da musica, em taxa fixa
+pub struct BreathCurve {
+    pub sample_rate: u32,
+    pub samples: Vec<u8>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/BreathPressure.ts")]
+/// Amostra de pressao do sopro de um jogador, de 0 a 127
+pub struct BreathPressure {
+    pub player: u8,
+    pub pressure: u8,
+}
+
+impl BreathPressure {
+    pub fn new(player: u8, pressure: u8) -> Self {
+        Self { player, pressure }
+    }
+}
+
+impl From<PressureSource> for BreathPressureSource {
+    fn from(value: PressureSource) -> Self {
+        match value {
+            PressureSource::NoteVelocity => Self::NoteVelocity,
+            PressureSource::ControlChange(cc) => Self::ControlChange(cc),
+            PressureSource::ChannelPressure => Self::ChannelPressure,
+        }
+    }
+}
+
+impl From<BreathPressureSource> for PressureSource {
+    fn from(value: BreathPressureSource) -> Self {
+        match value {
+            BreathPressureSource::NoteVelocity => Self::NoteVelocity,
+            BreathPressureSource::ControlChange(cc) => Self::ControlChange(cc),
+            BreathPressureSource::ChannelPressure => Self::ChannelPressure,
+        }
+    }
+}
+
+impl From<DeviceProfile> for DeviceProfileInfo {
+    fn from(value: DeviceProfile) -> Self {
+        Self {
+            name: value.name,
+            port_pattern: value.port_pattern,
+            pressure_source: value.pressure_source.into(),
+            pressure_sample_rate: value.pressure_sample_rate,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use arduino_comm::firmware_upload::{UploadProgress, UploadStage};
+use serde::Serialize;
+use ts_rs::TS;
+
+#[derive(Debug, Serialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/FirmwareUploadStage.ts")]
+/// Etapa da gravacao do firmware, `Rollback` quando a gravacao falhou e o firmware antigo
+/// esta sendo regravado
+pub enum FirmwareUploadStage {
+    Backup,
+    Write,
+    Verify,
+    Rollback,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/FirmwareUploadProgress.ts")]
+/// Paginas da flash ja processadas na etapa atual
+pub struct FirmwareUploadProgress {
+    pub stage: FirmwareUploadStage,
+    pub done: u32,
+    pub total: u32,
+}
+
+impl From<UploadProgress> for FirmwareUploadProgress {
+    fn from(value: UploadProgress) -> Self {
+        Self {
+            stage: match value.stage {
+                UploadStage::Backup => FirmwareUploadStage::Backup,
+                UploadStage::Write => FirmwareUploadStage::Write,
+                UploadStage::Verify => FirmwareUploadStage::Verify,
+                UploadStage::Rollback => FirmwareUploadStage::Rollback,
+            },
+            done: value.done as u32,
+            total: value.total as u32,
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use ts_rs::TS;
+
+/// Nota de uma tentativa, a ordem é do melhor para o pior
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, TS, Copy, Clone)]
+#[ts(export, export_to = "../../src/app/core/model/Grade.ts")]
+pub enum Grade {
+    S,
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl Grade {
+    const PASSING: [Grade; 5] = [Grade::S, Grade::A, Grade::B, Grade::C, Grade::D];
+}
+
+impl Display for Grade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Grade {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "S" => Ok(Grade::S),
+            "A" => Ok(Grade::A),
+            "B" => Ok(Grade::B),
+            "C" => Ok(Grade::C),
+            "D" => Ok(Grade::D),
+            "F" => Ok(Grade::F),
+            _ => Err(format!("Unknown grade {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, TS)]
+#[ts(export, export_to = "../../src/app/core/model/GradeThresholds.ts")]
+/// Como a precisao de uma tentativa vira nota e estrelas, todas as precisoes sao de 0 a 100
+pub struct GradeThresholds {
+    /// Quanto um acerto `Left` ou `Right` vale na precisao, o `Middle` vale 1
+    pub partial_hit_weight: f32,
+    /// Precisao minima de S, A, B, C e D, abaixo de todas é F
+    pub grades: [f32; 5],
+    /// Precisao minima de cada estrela
+    pub stars: [f32; 5],
+}
+
+impl Default for GradeThresholds {
+    fn default() -> Self {
+        Self {
+            partial_hit_weight: 0.75,
+            grades: [95f32, 90f32, 80f32, 70f32, 60f32],
+            stars: [50f32, 65f32, 80f32, 90f32, 97f32],
+        }
+    }
+}
+
+impl GradeThresholds {
+    /// Peso entre 0 e 1, notas decrescentes e estrelas crescentes
+    pub fn is_valid(&self) -> bool {
+        let in_range = |v: &f32| v.is_finite() && (0f32..=100f32).contains(v);
+        (0f32..=1f32).contains(&self.partial_hit_weight)
+            && self.grades.iter().all(in_range)
+            && self.stars.iter().all(in_range)
+            && self.grades.windows(2).all(|w| w[0] >= w[1])
+            && self.stars.windows(2).all(|w| w[0] <= w[1])
+    }
+
+    /// Precisao das notas julgadas, [None] se nenhuma nota foi julgada
+    pub fn accuracy(&self, great_hits: u32, ok_hits: u32, close_hits: u32, misses: u32, early_misses: u32) -> Option<f32> {
+        let notes = great_hits + ok_hits + close_hits + misses + early_misses;
+        if notes == 0 {
+            return None;
+        }
+        let hits = great_hits as f32 + (ok_hits + close_hits) as f32 * self.partial_hit_weight;
+        Some(hits / notes as f32 * 100f32)
+    }
+
+    pub fn grade(&self, accuracy: f32) -> Grade {
+        Grade::PASSING
+            .into_iter()
+            .zip(self.grades)
+            .find(|(_, min)| accuracy >= *min)
+            .map(|(grade, _)| grade)
+            .unwrap_or(Grade::F)
+    }
+
+    /// Estrelas de 0 a 5
+    pub fn stars(&self, accuracy: f32) -> u8 {
+        self.stars.iter().filter(|min| accuracy >= **min).count() as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accuracy_weights_partial_hits() {
+        let thresholds = GradeThresholds::default();
+        assert_eq!(thresholds.accuracy(0, 0, 0, 0, 0), None);
+        assert_eq!(thresholds.accuracy(10, 0, 0, 0, 0), Some(100f32));
+        assert_eq!(thresholds.accuracy(0, 2, 2, 0, 0), Some(75f32));
+        assert_eq!(thresholds.accuracy(1, 0, 0, 2, 1), Some(25f32));
+        assert_eq!(thresholds.accuracy(0, 0, 0, 3, 3), Some(0f32));
+    }
+
+    #[test]
+    fn grade_is_the_best_threshold_reached() {
+        let thresholds = GradeThresholds::default();
+        assert_eq!(thresholds.grade(100f32), Grade::S);
+        assert_eq!(thresholds.grade(95f32), Grade::S);
+        assert_eq!(thresholds.grade(94.9f32), Grade::A);
+        assert_eq!(thresholds.grade(80f32), Grade::B);
+        assert_eq!(thresholds.grade(70f32), Grade::C);
+        assert_eq!(thresholds.grade(60f32), Grade::D);
+        assert_eq!(thresholds.grade(59.9f32), Grade::F);
+        assert_eq!(thresholds.grade(0f32), Grade::F);
+    }
+
+    #[test]
+    fn stars_count_every_threshold_reached() {
+        let thresholds = GradeThresholds::default();
+        assert_eq!(thresholds.stars(0f32), 0);
+        assert_eq!(thresholds.stars(50f32), 1);
+        assert_eq!(thresholds.stars(89f32), 3);
+        assert_eq!(thresholds.stars(96.9f32), 4);
+        assert_eq!(thresholds.stars(100f32), 5);
+    }
+
+    #[test]
+    fn grades_round_trip_as_text_and_order_best_first() {
+        for grade in Grade::PASSING.into_iter().chain([Grade::F]) {
+            assert_eq!(grade.to_string().parse::<Grade>(), Ok(grade));
+        }
+        assert!("E".parse::<Grade>().is_err());
+        assert!(Grade::S < Grade::F);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::commands::ServiceResult;
+use crate::constants::errors::USER_INVALID_HIT_WINDOWS;
+use persistence::storage::{StorageRetrievable, StorageSavable};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../src/app/core/model/HitWindows.ts")]
+/// Janelas em ms usadas para julgar as notas tocadas, contadas a partir do momento da nota
+/// na partitura. `Right` é antes da nota e `Left` depois, como a nota vindo da direita.
+pub struct HitWindows {
+    /// Antes ou depois, ate esse tempo é `Middle`
+    pub middle_ms: u32,
+    /// Antes da nota, ate esse tempo é `Right`
+    pub right_ms: u32,
+    /// Depois da nota, ate esse tempo é `Left`. Passou disso a nota é `Miss`
+    pub left_ms: u32,
+    /// Antes da nota e fora da `right_ms`, ate esse tempo é `EarlyMiss`
+    pub early_miss_ms: u32,
+}
+
+impl Default for HitWindows {
+    fn default() -> Self {
+        Self {
+            middle_ms: 60,
+            right_ms: 150,
+            left_ms: 150,
+            early_miss_ms: 300,
+        }
+    }
+}
+
+impl HitWindows {
+    pub fn validated(self) -> ServiceResult<Self> {
+        if self.middle_ms > self.right_ms || self.middle_ms > self.left_ms || self.right_ms > self.early_miss_ms {
+            Err(USER_INVALID_HIT_WINDOWS.into())
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl StorageSavable for HitWindows {}
+impl StorageRetrievable for HitWindows {}
@@ -0,0 +1,70 @@
+use arduino_comm::input_filter::{FilterStats, Hysteresis, InputFilterConfig};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/VelocityHysteresis.ts")]
+/// Velocity para ligar a nota e velocity abaixo da qual ela desliga
+pub struct VelocityHysteresis {
+    pub on_velocity: u8,
+    pub off_velocity: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/InputFilterSettings.ts")]
+/// Filtro das notas do dispositivo, `null` desliga a parte correspondente
+pub struct InputFilterSettings {
+    /// Duracao minima de uma nota em ms
+    #[serde(default)]
+    pub min_note_duration_ms: Option<u32>,
+    #[serde(default)]
+    pub hysteresis: Option<VelocityHysteresis>,
+    pub suppress_duplicate_note_ons: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/InputFilterStats.ts")]
+/// Quantas mensagens o filtro segurou desde que o dispositivo conectou
+pub struct InputFilterStats {
+    pub short_notes: u32,
+    pub below_threshold: u32,
+    pub duplicate_note_ons: u32,
+    pub total: u32,
+}
+
+impl From<InputFilterConfig> for InputFilterSettings {
+    fn from(value: InputFilterConfig) -> Self {
+        Self {
+            min_note_duration_ms: value.min_note_duration_ms.map(|d| d as u32),
+            hysteresis: value.hysteresis.map(|h| VelocityHysteresis {
+                on_velocity: h.on_velocity,
+                off_velocity: h.off_velocity,
+            }),
+            suppress_duplicate_note_ons: value.suppress_duplicate_note_ons,
+        }
+    }
+}
+
+impl From<InputFilterSettings> for InputFilterConfig {
+    fn from(value: InputFilterSettings) -> Self {
+        Self {
+            min_note_duration_ms: value.min_note_duration_ms.map(|d| d as u64),
+            hysteresis: value.hysteresis.map(|h| Hysteresis {
+                on_velocity: h.on_velocity,
+                off_velocity: h.off_velocity,
+            }),
+            suppress_duplicate_note_ons: value.suppress_duplicate_note_ons,
+        }
+    }
+}
+
+impl From<FilterStats> for InputFilterStats {
+    fn from(value: FilterStats) -> Self {
+        Self {
+            short_notes: value.short_notes as u32,
+            below_threshold: value.below_threshold as u32,
+            duplicate_note_ons: value.duplicate_note_ons as u32,
+            total: value.total() as u32,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use arduino_comm::instrument_profile::{InstrumentNote, InstrumentProfile};
+use serde::Serialize;
+use ts_rs::TS;
+
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/InstrumentNoteInfo.ts")]
+/// Nota que o instrumento consegue tocar
+pub struct InstrumentNoteInfo {
+    pub key: u8,
+    pub name: String,
+    /// Linha na partitura, o mesmo `note_index` do `MidiSignal`
+    pub ordinal: u8,
+    pub is_bmol: bool,
+}
+
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/InstrumentInfo.ts")]
+/// Instrumento que pode ser escolhido pelo usuario
+pub struct InstrumentInfo {
+    pub id: String,
+    pub name: String,
+    pub lowest_key: u8,
+    pub highest_key: u8,
+    pub notes: Vec<InstrumentNoteInfo>,
+}
+
+impl From<&InstrumentNote> for InstrumentNoteInfo {
+    fn from(value: &InstrumentNote) -> Self {
+        Self {
+            key: value.key,
+            name: value.name.to_owned(),
+            ordinal: value.ordinal(),
+            is_bmol: value.is_bmol(),
+        }
+    }
+}
+
+impl From<&InstrumentProfile> for InstrumentInfo {
+    fn from(value: &InstrumentProfile) -> Self {
+        Self {
+            id: value.id.to_owned(),
+            name: value.name.to_owned(),
+            lowest_key: value.lowest_key(),
+            highest_key: value.highest_key(),
+            notes: value.notes.iter().map(InstrumentNoteInfo::from).collect(),
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use anyhow::anyhow;
+use arduino_comm::instrument_profile::{InstrumentNote, InstrumentProfile};
 use arduino_comm::{midi_wrapper::MidiWrapper, note::Note};
 use serde::Serialize;
 use std::fmt::Display;
+use std::time::Duration;
 use thiserror::Error;
 use ts_rs::TS;
 
@@ -39,35 +41,56 @@ pub struct MidiPayload {
     note_name: String,
     velocity: u8,
     state: bool,
+    /// Latencia do dispositivo, a nota foi tocada esse tempo antes do evento ser enviado
+    latency_ms: u32,
+    /// Jogador que tocou a nota, ou que deve tocar no caso da partitura
+    player: u8,
 }
 
 impl MidiPayload {
-    pub fn from_midi_wrapper(midi_wrapper: MidiWrapper) -> Self {
-        let note = midi_wrapper.note.note;
-        let note_name: &str = note.into();
-        Self {
-            note_index: note.ordinal(),
-            is_bmol: note.is_bmol(),
-            note_name: note_name.to_string(),
-            velocity: midi_wrapper.air_strength,
-            state: midi_wrapper.state == Note::STATE_ON,
-        }
+    pub fn from_midi_wrapper(midi_wrapper: MidiWrapper, note: &InstrumentNote) -> Self {
+        Self::from_instrument_note(
+            note,
+            midi_wrapper.air_strength,
+            midi_wrapper.state == Note::STATE_ON,
+        )
     }
 
-    pub fn from_note(note: u8, velocity: u8, state: bool) -> Result<Self, MidiNoteError> {
+    /// Nota da partitura, retorna erro se o instrumento nao tiver essa nota
+    pub fn from_note(
+        instrument: &InstrumentProfile,
+        note: u8,
+        velocity: u8,
+        state: bool
+    ) -> Result<Self, MidiNoteError> {
         let s = if velocity == 0 { false } else { state };
-        let note = Note::from_byte(note).map_err(|e| MidiNoteError {
+        let note = instrument.note(note).map_err(|e| MidiNoteError {
             byte: note,
             source: anyhow!(e),
         })?;
-        let note_name: &str = note.into();
-        Ok(Self {
+        Ok(Self::from_instrument_note(note, velocity, s))
+    }
+
+    fn from_instrument_note(note: &InstrumentNote, velocity: u8, state: bool) -> Self {
+        Self {
             note_index: note.ordinal(),
             is_bmol: note.is_bmol(),
-            note_name: note_name.to_string(),
+            note_name: note.name.to_owned(),
             velocity,
-            state: s,
-        })
+            state,
+            latency_ms: 0,
+            player: 0,
+        }
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency_ms = latency.as_millis() as u32;
+        self
+    }
+
+    pub fn with_player(mut self, player: u8) -> Self {
+        self.player = player;
+        self
     }
 }
 
@@ -76,8 +99,8 @@ impl Display for MidiPayload {
         let state = if self.state { "on" } else { "off" };
         write!(
             f,
-            "Note index: {} | isBmol: {} | Note name: {} | velocity: {} | state: {}",
-            self.note_index, self.is_bmol, self.note_name, self.velocity, state
+            "Note index: {} | isBmol: {} | Note name: {} | velocity: {} | state: {} | player: {}",
+            self.note_index, self.is_bmol, self.note_name, self.velocity, state, self.player
         )
     }
 }
@@ -0,0 +1,71 @@
+use arduino_comm::midi_thru::{ThruConfig, VelocityCurve};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, TS)]
+#[ts(export, export_to = "../../src/app/core/model/ThruVelocityCurve.ts")]
+/// Curva aplicada na forca do sopro antes de repassar a nota
+pub enum ThruVelocityCurve {
+    Linear,
+    Soft,
+    Hard,
+    Fixed(u8),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/MidiThruSettings.ts")]
+/// Configuracao do midi thru, `port_name` é nulo quando o thru esta desligado
+pub struct MidiThruSettings {
+    #[serde(default)]
+    pub port_name: Option<String>,
+    pub transpose: i8,
+    pub velocity_curve: ThruVelocityCurve,
+    pub channel: u8,
+}
+
+impl From<VelocityCurve> for ThruVelocityCurve {
+    fn from(value: VelocityCurve) -> Self {
+        match value {
+            VelocityCurve::Linear => Self::Linear,
+            VelocityCurve::Soft => Self::Soft,
+            VelocityCurve::Hard => Self::Hard,
+            VelocityCurve::Fixed(v) => Self::Fixed(v),
+        }
+    }
+}
+
+impl From<ThruVelocityCurve> for VelocityCurve {
+    fn from(value: ThruVelocityCurve) -> Self {
+        match value {
+            ThruVelocityCurve::Linear => Self::Linear,
+            ThruVelocityCurve::Soft => Self::Soft,
+            ThruVelocityCurve::Hard => Self::Hard,
+            ThruVelocityCurve::Fixed(v) => Self::Fixed(v),
+        }
+    }
+}
+
+impl MidiThruSettings {
+    pub fn new(port_name: Option<String>, config: ThruConfig) -> Self {
+        Self {
+            port_name,
+            transpose: config.transpose,
+            velocity_curve: config.velocity_curve.into(),
+            channel: config.channel,
+        }
+    }
+
+    pub fn config(&self) -> ThruConfig {
+        ThruConfig {
+            transpose: self.transpose,
+            velocity_curve: self.velocity_curve.into(),
+            channel: self.channel,
+        }
+    }
+}
+
+impl Default for MidiThruSettings {
+    fn default() -> Self {
+        Self::new(None, ThruConfig::default())
+    }
+}
@@ -1,5 +1,18 @@
+pub mod backing_track;
+pub mod calibration;
+pub mod device_config;
+pub mod device_connection;
+pub mod device_profile;
+pub mod firmware;
+pub mod grade;
+pub mod hit_windows;
+pub mod input_filter;
+pub mod instrument;
 pub mod midi_payload;
+pub mod midi_thru;
 pub mod music;
 pub mod on_note_data;
 pub mod score;
+pub mod scoring_profile;
 pub mod service_error;
+pub mod song_editor;
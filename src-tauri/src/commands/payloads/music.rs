@@ -1,3 +1,4 @@
+use crate::commands::payloads::grade::Grade;
 use anyhow::anyhow;
 use entity::music::Model;
 use serde::{Deserialize, Serialize};
@@ -43,6 +44,9 @@ pub struct MidiMusic {
     pub id: i32,
     pub directory: String,
     pub duration: u64, // em segundos
+    /// Melhor nota das tentativas terminadas, `null` se a musica nunca foi terminada
+    #[serde(default)]
+    pub best_grade: Option<Grade>,
 }
 
 impl From<Model> for MidiMusic {
@@ -52,6 +56,7 @@ impl From<Model> for MidiMusic {
             id: value.id,
             directory: value.directory,
             duration: value.duration as u64,
+            best_grade: None,
         }
     }
 }
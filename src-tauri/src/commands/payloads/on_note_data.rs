@@ -2,7 +2,15 @@ use serde::{Deserialize, Serialize};
 use strum::EnumIter;
 use ts_rs::TS;
 
-#[derive(Deserialize, Eq, PartialEq, TS, Copy, Clone, EnumIter)]
+/// Precisao de uma nota tocada pelo usuario, julgada pelo backend.
+///
+/// Poderá ter 5 estados:
+/// `Middle` para quando tocar exatamente no momento certo
+/// `Left` para quando tocar um pouco depois
+/// `Right` para quando tocar um pouco antes
+/// `Miss` quando deixar a nota passar
+/// `EarlyMiss` quando errar a nota completamente antes de entrar na area de acerto
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, TS, Copy, Clone, EnumIter)]
 #[ts(export, export_to = "../../src/app/core/model/NotePressPrecision.ts")]
 pub enum OnNotePrecision {
     Middle,
@@ -12,18 +20,6 @@ pub enum OnNotePrecision {
     EarlyMiss,
 }
 
-impl From<OnNotePrecision> for f32 {
-    fn from(value: OnNotePrecision) -> Self {
-        match value {
-            OnNotePrecision::Middle => 2f32,
-            OnNotePrecision::Left => 1.3f32,
-            OnNotePrecision::Right => 1.5f32,
-            OnNotePrecision::Miss => -0.3f32,
-            OnNotePrecision::EarlyMiss => -0.8f32,
-        }
-    }
-}
-
 impl From<OnNotePrecision> for bool {
     fn from(value: OnNotePrecision) -> Self {
         match value {
@@ -33,24 +29,10 @@ impl From<OnNotePrecision> for bool {
     }
 }
 
-/// Struct para quando a nota for pressionada pelo usuario.
-///
-/// Poderá ter 4 estados:
-/// `Middle` para quando pressionar exatamente no momento certo
-/// `Left` para quando estiver um pouco para esquerda
-/// `Right` para quando estiver um pouco para direita
-/// `Miss` quando deixar a nota passar
-/// `EarlyMiss` quando errar a nota completamente antes de entrar na area de acerto
-#[derive(Deserialize, TS, Copy, Clone)]
-#[ts(export, export_to = "../../src/app/core/model/OnNoteMessage.ts")]
-pub struct OnNoteMessage {
-    pub precision: u8,
-}
-
 /// Payload que será emitido ao front sobre sempre que o usuario atualizar o seu score
 /// o `total_score` é o score total acumulado na sessao atual da musica e o
 /// `latest_message_score` é o score ganho/perdido depois do ultimo input.
-#[derive(Debug, Serialize, TS)]
+#[derive(Debug, Serialize, Clone, TS)]
 #[ts(export, export_to = "../../src/app/core/model/OnScoreUpdateMessage.ts", rename = "OnScoreUpdateMessage")]
 pub struct OnNotePayload {
     hit_streak: u32,
@@ -67,3 +49,57 @@ impl OnNotePayload {
         }
     }
 }
+
+/// Evento emitido sempre que uma nota da partitura é julgada, com o score do jogador depois dela
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/NoteJudgement.ts")]
+pub struct NoteJudgement {
+    pub player: u8,
+    /// Nota midi da partitura
+    pub key: u8,
+    pub precision: OnNotePrecision,
+    pub score: OnNotePayload,
+}
+
+/// Evento emitido quando uma nota da partitura termina, avaliando quanto dela o jogador segurou
+/// e quao estavel foi o sopro
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/SustainFeedback.ts")]
+pub struct SustainFeedback {
+    pub player: u8,
+    /// Nota midi da partitura
+    pub key: u8,
+    /// Porcentagem da duracao da nota que foi segurada
+    pub sustain_percent: u8,
+    /// Porcentagem de estabilidade da pressao, `null` se nao teve amostras suficientes
+    pub steadiness_percent: Option<u8>,
+    /// Porcentagem da duracao da nota com a pressao dentro da zona alvo
+    pub time_in_zone_percent: Option<u8>,
+    pub sustain_score: i32,
+    pub steadiness_score: i32,
+    pub score: OnNotePayload,
+}
+
+/// Onde a pressao do jogador esta em relacao a zona alvo da nota
+#[derive(Debug, Serialize, Eq, PartialEq, TS, Copy, Clone)]
+#[ts(export, export_to = "../../src/app/core/model/BreathZone.ts")]
+pub enum BreathZone {
+    TooWeak,
+    InZone,
+    TooStrong,
+}
+
+/// Evento emitido quando a pressao do jogador entra ou sai da zona alvo de uma nota da partitura
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(export, export_to = "../../src/app/core/model/BreathZoneFeedback.ts")]
+pub struct BreathZoneFeedback {
+    pub player: u8,
+    /// Nota midi da partitura
+    pub key: u8,
+    pub zone: BreathZone,
+    /// Pressao do jogador, de 0 a 127
+    pub pressure: u8,
+    /// Limites da zona alvo, vem da velocidade da nota
+    pub min: u8,
+    pub max: u8,
+}
@@ -1,3 +1,4 @@
+use crate::commands::payloads::grade::{Grade, GradeThresholds};
 use entity::score::Model;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
@@ -26,15 +27,142 @@ pub struct ScorePayload {
     pub date_achieved: String,
     pub highest_streak: i32,
     pub finished: bool,
+    /// Vezes que o jogo pausou porque o dispositivo desconectou
+    pub interruptions: i32,
+    /// Jogador que fez o score, 0 fora do modo dueto
+    pub player: i32,
+    /// Perfil de pontuacao usado, `null` nos scores feitos antes dos perfis existirem
+    pub scoring_profile: Option<String>,
+    /// Pontos que vieram das notas seguradas e da estabilidade do sopro
+    pub sustain_score: i32,
+    pub steadiness_score: i32,
+    /// Media em porcentagem da duracao das notas que foi segurada, `null` sem notas avaliadas
+    pub average_sustain: Option<i32>,
+    /// Media em porcentagem da estabilidade do sopro nas notas seguradas
+    pub average_steadiness: Option<i32>,
+    /// Media em porcentagem do tempo de cada nota com a pressao dentro da zona alvo
+    pub time_in_zone: Option<i32>,
+    /// Porcentagem de precisao das notas julgadas, `null` sem notas julgadas. Nos scores feitos
+    /// antes das notas existirem ela é calculada com os acertos salvos e os limites padrao
+    pub accuracy: Option<i32>,
+    /// Nota e estrelas (0 a 5) da tentativa, `null` se ela nao foi terminada
+    pub grade: Option<Grade>,
+    pub stars: Option<i32>,
+}
+
+/// Precisao, nota e estrelas da tentativa. Scores salvos antes dessas colunas existirem
+/// sao avaliados com os acertos salvos e o [GradeThresholds::default]
+pub fn grading(value: &Model) -> (Option<i32>, Option<Grade>, Option<i32>) {
+    if value.accuracy.is_some() {
+        return (value.accuracy, value.grade.as_deref().and_then(|g| g.parse().ok()), value.stars);
+    }
+    let count = |c: Option<i32>| c.unwrap_or_default().max(0) as u32;
+    let thresholds = GradeThresholds::default();
+    let accuracy = thresholds.accuracy(
+        count(value.total_great_hits),
+        count(value.total_ok_hits),
+        count(value.total_close_hits),
+        count(value.total_misses),
+        count(value.total_early_misses),
+    );
+    let graded = accuracy.filter(|_| value.completed);
+    (
+        accuracy.map(|a| a.round() as i32),
+        graded.map(|a| thresholds.grade(a)),
+        graded.map(|a| thresholds.stars(a) as i32),
+    )
 }
 
 impl From<Model> for ScorePayload {
     fn from(value: Model) -> Self {
+        let (accuracy, grade, stars) = grading(&value);
         Self {
             total: value.total,
             date_achieved: value.date.format("%H:%M:%S | %d/%m/%Y").to_string(),
             highest_streak: value.highest_streak,
             finished: value.completed,
+            interruptions: value.total_interruptions.unwrap_or_default(),
+            player: value.player.unwrap_or_default(),
+            scoring_profile: value.scoring_profile,
+            sustain_score: value.sustain_score.unwrap_or_default(),
+            steadiness_score: value.steadiness_score.unwrap_or_default(),
+            average_sustain: value.average_sustain,
+            average_steadiness: value.average_steadiness,
+            time_in_zone: value.time_in_zone,
+            accuracy,
+            grade,
+            stars,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sqlx::types::chrono::Utc;
+
+    fn old_score(completed: bool) -> Model {
+        Model {
+            id: 1,
+            total: 100,
+            date: Utc::now(),
+            completed,
+            highest_streak: 10,
+            music_id: 1,
+            breath_average_strength: None,
+            total_close_hits: Some(2),
+            total_great_hits: Some(16),
+            total_ok_hits: Some(2),
+            highest_breathing_duration: None,
+            total_breathing_duration: None,
+            total_early_misses: Some(0),
+            total_misses: Some(0),
+            total_interruptions: None,
+            interrupted_duration: None,
+            player: None,
+            scoring_profile: None,
+            sustain_score: None,
+            steadiness_score: None,
+            average_sustain: None,
+            average_steadiness: None,
+            time_in_zone: None,
+            accuracy: None,
+            grade: None,
+            stars: None,
+        }
+    }
+
+    #[test]
+    fn old_scores_are_graded_from_the_saved_hits() {
+        let payload = ScorePayload::from(old_score(true));
+        // (16 + 4 * 0.75) / 20
+        assert_eq!(payload.accuracy, Some(95));
+        assert_eq!(payload.grade, Some(Grade::S));
+        assert_eq!(payload.stars, Some(4));
+
+        let unfinished = ScorePayload::from(old_score(false));
+        assert_eq!(unfinished.accuracy, Some(95));
+        assert_eq!(unfinished.grade, None);
+        assert_eq!(unfinished.stars, None);
+    }
+
+    #[test]
+    fn saved_grades_are_kept() {
+        let score = Model {
+            accuracy: Some(71),
+            grade: Some("C".to_string()),
+            stars: Some(1),
+            ..old_score(true)
+        };
+        assert_eq!(grading(&score), (Some(71), Some(Grade::C), Some(1)));
+        let without_hits = Model {
+            total_close_hits: None,
+            total_great_hits: None,
+            total_ok_hits: None,
+            total_early_misses: None,
+            total_misses: None,
+            ..old_score(true)
+        };
+        assert_eq!(grading(&without_hits), (None, None, None));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::commands::payloads::grade::GradeThresholds;
+use crate::commands::payloads::on_note_data::OnNotePrecision;
+use crate::commands::ServiceResult;
+use crate::constants::errors::USER_INVALID_SCORING_PROFILE;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+pub const DEFAULT_SCORING_PROFILE_ID: &str = "default";
+const DEFAULT_SCORING_PROFILE_NAME: &str = "Padrao";
+const DEFAULT_BREATH_ZONE_TOLERANCE: u8 = 15;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, TS)]
+#[ts(export, export_to = "../../src/app/core/model/PrecisionPoints.ts")]
+/// Pontos de cada acerto antes do combo
+pub struct PrecisionPoints {
+    pub middle: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, TS)]
+#[ts(export, export_to = "../../src/app/core/model/ComboTier.ts")]
+/// A partir de `min_streak` acertos seguidos os pontos sao multiplicados por `multiplier`
+pub struct ComboTier {
+    pub min_streak: u32,
+    pub multiplier: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../../src/app/core/model/ScoringProfile.ts")]
+/// Regras de pontuacao, o id fica salvo em cada `Score` para comparar tentativas com regras diferentes
+pub struct ScoringProfile {
+    pub id: String,
+    pub name: String,
+    pub points: PrecisionPoints,
+    /// Vale a faixa com o maior `min_streak` alcançado, sem nenhuma o multiplicador é 1
+    #[serde(default)]
+    pub combo_tiers: Vec<ComboTier>,
+    /// Pontos perdidos quando a nota passa sem ser tocada
+    #[serde(default)]
+    pub miss_penalty: u32,
+    /// Pontos perdidos quando a nota é tocada cedo demais
+    #[serde(default)]
+    pub early_miss_penalty: u32,
+    /// Maximo de pontos de uma nota depois do combo, `null` sem limite
+    #[serde(default)]
+    pub max_note_score: Option<u32>,
+    /// Se o score total pode ficar abaixo de 0
+    #[serde(default)]
+    pub allow_negative_total: bool,
+    /// Pontos de uma nota segurada do inicio ao fim
+    #[serde(default)]
+    pub sustain_points: f32,
+    /// Pontos de uma nota segurada com a pressao constante
+    #[serde(default)]
+    pub steadiness_points: f32,
+    /// Precisoes que dao a nota e as estrelas de cada tentativa
+    #[serde(default)]
+    pub grades: GradeThresholds,
+    /// Distancia da pressao alvo (a velocidade da nota, de 0 a 127) que ainda fica dentro da zona
+    #[serde(default = "default_breath_zone_tolerance")]
+    pub breath_zone_tolerance: u8,
+}
+
+fn default_breath_zone_tolerance() -> u8 {
+    DEFAULT_BREATH_ZONE_TOLERANCE
+}
+
+impl Default for ScoringProfile {
+    fn default() -> Self {
+        Self {
+            id: DEFAULT_SCORING_PROFILE_ID.to_string(),
+            name: DEFAULT_SCORING_PROFILE_NAME.to_string(),
+            points: PrecisionPoints {
+                middle: 20f32,
+                left: 13f32,
+                right: 15f32,
+            },
+            combo_tiers: vec![
+                ComboTier { min_streak: 10, multiplier: 2f32 },
+                ComboTier { min_streak: 25, multiplier: 3f32 },
+                ComboTier { min_streak: 50, multiplier: 4f32 },
+            ],
+            miss_penalty: 3,
+            early_miss_penalty: 8,
+            max_note_score: None,
+            allow_negative_total: false,
+            sustain_points: 10f32,
+            steadiness_points: 10f32,
+            grades: GradeThresholds::default(),
+            breath_zone_tolerance: DEFAULT_BREATH_ZONE_TOLERANCE,
+        }
+    }
+}
+
+impl ScoringProfile {
+    /// Confere o perfil e ordena as faixas de combo
+    pub fn validated(mut self) -> ServiceResult<Self> {
+        let points = [
+            self.points.middle,
+            self.points.left,
+            self.points.right,
+            self.sustain_points,
+            self.steadiness_points,
+        ];
+        // as penalidades e o limite viram i32 no score
+        let limits = [self.miss_penalty, self.early_miss_penalty, self.max_note_score.unwrap_or_default()];
+        if self.id.is_empty()
+            || points.iter().any(|p| !p.is_finite() || *p < 0f32)
+            || limits.iter().any(|l| *l > i32::MAX as u32)
+            || self.combo_tiers.iter().any(|t| !t.multiplier.is_finite() || t.multiplier <= 0f32)
+            || !self.grades.is_valid()
+            || self.breath_zone_tolerance > 127
+        {
+            return Err(USER_INVALID_SCORING_PROFILE.into());
+        }
+        self.combo_tiers.sort_by_key(|t| t.min_streak);
+        Ok(self)
+    }
+
+    pub fn combo_multiplier(&self, hit_streak: u32) -> f32 {
+        self.combo_tiers
+            .iter()
+            .rev()
+            .find(|t| t.min_streak <= hit_streak)
+            .map(|t| t.multiplier)
+            .unwrap_or(1f32)
+    }
+
+    /// Pontos ganhos ou perdidos pela nota, o `hit_streak` ja conta com ela
+    pub fn note_score(&self, precision: OnNotePrecision, hit_streak: u32) -> i32 {
+        let points = match precision {
+            OnNotePrecision::Middle => self.points.middle,
+            OnNotePrecision::Left => self.points.left,
+            OnNotePrecision::Right => self.points.right,
+            OnNotePrecision::Miss => return -(self.miss_penalty as i32),
+            OnNotePrecision::EarlyMiss => return -(self.early_miss_penalty as i32),
+        };
+        let score = (points * self.combo_multiplier(hit_streak)).round() as i32;
+        match self.max_note_score {
+            Some(max) => score.min(max as i32),
+            None => score
+        }
+    }
+
+    /// Pontos pela parte da nota segurada e pela estabilidade da pressao, ambas de 0 a 1
+    pub fn sustain_score(&self, sustain: f32, steadiness: Option<f32>) -> (i32, i32) {
+        (
+            (self.sustain_points * sustain).round() as i32,
+            (self.steadiness_points * steadiness.unwrap_or_default()).round() as i32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combo_multiplier_uses_the_highest_tier_reached() {
+        let profile = ScoringProfile {
+            combo_tiers: vec![
+                ComboTier { min_streak: 25, multiplier: 3f32 },
+                ComboTier { min_streak: 10, multiplier: 2f32 },
+            ],
+            ..Default::default()
+        }
+        .validated()
+        .unwrap();
+        assert_eq!(profile.combo_multiplier(0), 1f32);
+        assert_eq!(profile.combo_multiplier(9), 1f32);
+        assert_eq!(profile.combo_multiplier(10), 2f32);
+        assert_eq!(profile.combo_multiplier(24), 2f32);
+        assert_eq!(profile.combo_multiplier(25), 3f32);
+        assert_eq!(profile.combo_multiplier(1000), 3f32);
+    }
+
+    #[test]
+    fn note_score_applies_combo_limit_and_penalties() {
+        let profile = ScoringProfile::default();
+        assert_eq!(profile.note_score(OnNotePrecision::Middle, 1), 20);
+        assert_eq!(profile.note_score(OnNotePrecision::Left, 1), 13);
+        assert_eq!(profile.note_score(OnNotePrecision::Right, 10), 30);
+        assert_eq!(profile.note_score(OnNotePrecision::Middle, 50), 80);
+        assert_eq!(profile.note_score(OnNotePrecision::Miss, 50), -3);
+        assert_eq!(profile.note_score(OnNotePrecision::EarlyMiss, 50), -8);
+
+        let limited = ScoringProfile { max_note_score: Some(50), ..Default::default() };
+        assert_eq!(limited.note_score(OnNotePrecision::Middle, 50), 50);
+        assert_eq!(limited.note_score(OnNotePrecision::Middle, 1), 20);
+    }
+
+    #[test]
+    fn validated_rejects_invalid_profiles() {
+        assert!(ScoringProfile::default().validated().is_ok());
+        let invalid = [
+            ScoringProfile { id: String::new(), ..Default::default() },
+            ScoringProfile { points: PrecisionPoints { middle: -1f32, left: 1f32, right: 1f32 }, ..Default::default() },
+            ScoringProfile { sustain_points: f32::NAN, ..Default::default() },
+            ScoringProfile { combo_tiers: vec![ComboTier { min_streak: 5, multiplier: 0f32 }], ..Default::default() },
+            ScoringProfile { miss_penalty: u32::MAX, ..Default::default() },
+            ScoringProfile { early_miss_penalty: i32::MAX as u32 + 1, ..Default::default() },
+            ScoringProfile { max_note_score: Some(u32::MAX), ..Default::default() },
+            ScoringProfile { breath_zone_tolerance: 128, ..Default::default() },
+        ];
+        for profile in invalid {
+            assert!(profile.validated().is_err());
+        }
+        let highest = ScoringProfile { miss_penalty: i32::MAX as u32, ..Default::default() };
+        assert_eq!(highest.validated().unwrap().note_score(OnNotePrecision::Miss, 0), -i32::MAX);
+    }
+
+    #[test]
+    fn breath_zone_tolerance_defaults_when_missing() {
+        let mut json = serde_json::to_value(ScoringProfile::default()).unwrap();
+        json.as_object_mut().unwrap().remove("breath_zone_tolerance");
+        let profile: ScoringProfile = serde_json::from_value(json).unwrap();
+        assert_eq!(profile.breath_zone_tolerance, DEFAULT_BREATH_ZONE_TOLERANCE);
+    }
+
+    #[test]
+    fn sustain_score_is_proportional_to_the_held_part_and_steadiness() {
+        let profile = ScoringProfile::default();
+        assert_eq!(profile.sustain_score(1f32, Some(1f32)), (10, 10));
+        assert_eq!(profile.sustain_score(0.75f32, Some(0.24f32)), (8, 2));
+        assert_eq!(profile.sustain_score(0.5f32, None), (5, 0));
+        assert_eq!(profile.sustain_score(0f32, Some(0f32)), (0, 0));
+    }
+}
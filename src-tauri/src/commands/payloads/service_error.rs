@@ -1,5 +1,5 @@
 use crate::app_states::database_state::DatabaseError;
-use crate::constants::errors::{CodedError, COULD_NOT_UPDATE_DATABASE, DATABASE_COULD_NOT_CREATE, DATABASE_COULD_NOT_LOAD, DATABASE_QUERY_ERROR, DEVICE_COULD_NOT_CONNECT, DEVICE_LISTEN_ERROR, DEVICE_NO_INPUT_CONNECTIONS_FOUND, DEVICE_PORT_NOT_FOUND, FILE_ALREADY_PLAYING, FILE_NOT_FOUND, MIDI_NOT_SUPPORTED, MIDI_NO_AVAILABLE_PORTS, MIDI_OUTPUT_CONNECTION_FAILED, MIDI_UNEXPECTED_PLAYBACK_ERROR, STATE_ACQUIRE_ERROR, STORAGE_COULD_NOT_BE_CREATED, STORAGE_COULD_NOT_READ, STORAGE_COULD_NOT_WRITE, STORAGE_HAS_NOT_BEEN_CREATED, STORAGE_KEY_DOES_NOT_EXIST, UNEXPECTED_ERROR};
+use crate::constants::errors::{CodedError, COULD_NOT_UPDATE_DATABASE, DATABASE_COULD_NOT_CREATE, DATABASE_COULD_NOT_LOAD, DATABASE_QUERY_ERROR, DEVICE_CONFIG_ERROR, DEVICE_CONFIG_TIMEOUT, DEVICE_COULD_NOT_CONNECT, DEVICE_FIRMWARE_ROLLED_BACK, DEVICE_FIRMWARE_UPLOAD_FAILED, DEVICE_INVALID_CONFIG, DEVICE_INVALID_FIRMWARE, DEVICE_INVALID_INPUT_FILTER, DEVICE_INVALID_LATENCY_CALIBRATION, DEVICE_INVALID_RECORDING, DEVICE_INVALID_REPLAY_SPEED, DEVICE_LISTEN_ERROR, DEVICE_NO_INPUT_CONNECTIONS_FOUND, DEVICE_OSC_ERROR, DEVICE_PORT_NOT_FOUND, DEVICE_RECORDING_ERROR, DEVICE_SERIAL_ERROR, DEVICE_THRU_COULD_NOT_CONNECT, DEVICE_THRU_INVALID_CONFIG, DEVICE_THRU_PORT_NOT_FOUND, EDITOR_COULD_NOT_SAVE, EDITOR_INVALID_EDIT, EDITOR_NOTE_NOT_FOUND, INSTRUMENT_INVALID_PROFILE, INSTRUMENT_NOTE_NOT_SUPPORTED, USER_INVALID_CALIBRATION, FILE_ALREADY_PLAYING, FILE_NOT_FOUND, FILE_RENDER_ERROR, MIDI_INVALID_TRACK, MIDI_NOT_SUPPORTED, MIDI_NO_AVAILABLE_PORTS, MIDI_OUTPUT_CONNECTION_FAILED, MIDI_OUTPUT_PORT_NOT_FOUND, MIDI_UNEXPECTED_PLAYBACK_ERROR, STATE_ACQUIRE_ERROR, STORAGE_COULD_NOT_BE_CREATED, STORAGE_COULD_NOT_READ, STORAGE_COULD_NOT_WRITE, STORAGE_HAS_NOT_BEEN_CREATED, STORAGE_KEY_DOES_NOT_EXIST, UNEXPECTED_ERROR};
 use anyhow::Error;
 use arduino_comm::errors::ArduinoCommunicationError;
 use midi_reader::errors::MidiReaderError;
@@ -77,7 +77,26 @@ impl From<ArduinoCommunicationError> for ServiceError {
             ArduinoCommunicationError::OcarinaNotFound => Self::from(DEVICE_PORT_NOT_FOUND),
             ArduinoCommunicationError::NoDevicesConnected => Self::from(DEVICE_NO_INPUT_CONNECTIONS_FOUND),
             ArduinoCommunicationError::PortListenError(_) => Self::from(DEVICE_LISTEN_ERROR),
-            _ => Self::from(value.to_string())
+            ArduinoCommunicationError::ThruPortNotFound(_) => Self::from(DEVICE_THRU_PORT_NOT_FOUND),
+            ArduinoCommunicationError::ThruConnectionError(_) => Self::from(DEVICE_THRU_COULD_NOT_CONNECT),
+            ArduinoCommunicationError::InvalidThruConfig(_) => Self::from(DEVICE_THRU_INVALID_CONFIG),
+            ArduinoCommunicationError::RecordingError(_) => Self::from(DEVICE_RECORDING_ERROR),
+            ArduinoCommunicationError::InvalidRecording(_) => Self::from(DEVICE_INVALID_RECORDING),
+            ArduinoCommunicationError::InvalidReplaySpeed(_) => Self::from(DEVICE_INVALID_REPLAY_SPEED),
+            ArduinoCommunicationError::SerialError(_) => Self::from(DEVICE_SERIAL_ERROR),
+            ArduinoCommunicationError::InvalidInstrumentProfile(_) => Self::from(INSTRUMENT_INVALID_PROFILE),
+            ArduinoCommunicationError::ByteNotSupported(_) => Self::from(INSTRUMENT_NOTE_NOT_SUPPORTED),
+            ArduinoCommunicationError::InvalidCalibration(_) => Self::from(USER_INVALID_CALIBRATION),
+            ArduinoCommunicationError::InvalidLatencyCalibration(_) => Self::from(DEVICE_INVALID_LATENCY_CALIBRATION),
+            ArduinoCommunicationError::InvalidInputFilter(_) => Self::from(DEVICE_INVALID_INPUT_FILTER),
+            ArduinoCommunicationError::DeviceConfigError(_) => Self::from(DEVICE_CONFIG_ERROR),
+            ArduinoCommunicationError::DeviceConfigTimeout => Self::from(DEVICE_CONFIG_TIMEOUT),
+            ArduinoCommunicationError::InvalidDeviceConfig(_) => Self::from(DEVICE_INVALID_CONFIG),
+            ArduinoCommunicationError::InvalidFirmware(_) => Self::from(DEVICE_INVALID_FIRMWARE),
+            ArduinoCommunicationError::FirmwareUploadError(_) => Self::from(DEVICE_FIRMWARE_UPLOAD_FAILED),
+            ArduinoCommunicationError::FirmwareRolledBack(_) => Self::from(DEVICE_FIRMWARE_ROLLED_BACK),
+            ArduinoCommunicationError::OscError(_) |
+            ArduinoCommunicationError::InvalidOscPacket(_) => Self::from(DEVICE_OSC_ERROR),
         }
     }
 }
@@ -90,7 +109,13 @@ impl From<MidiReaderError> for ServiceError {
             MidiReaderError::AlreadyPlaying => Self::from(FILE_ALREADY_PLAYING),
             MidiReaderError::MidiOutputError(_) => Self::from(MIDI_OUTPUT_CONNECTION_FAILED),
             MidiReaderError::NoPortsFound => Self::from(MIDI_NO_AVAILABLE_PORTS),
+            MidiReaderError::OutputPortNotFound(_) => Self::from(MIDI_OUTPUT_PORT_NOT_FOUND),
+            MidiReaderError::InvalidTrack(_) => Self::from(MIDI_INVALID_TRACK),
             MidiReaderError::FileDoesNotExist(_) => Self::from(FILE_NOT_FOUND),
+            MidiReaderError::NoteNotFound(_) => Self::from(EDITOR_NOTE_NOT_FOUND),
+            MidiReaderError::InvalidEdit(_) => Self::from(EDITOR_INVALID_EDIT),
+            MidiReaderError::WriteError(_) => Self::from(EDITOR_COULD_NOT_SAVE),
+            MidiReaderError::RenderError(_) => Self::from(FILE_RENDER_ERROR),
             _ => Self::from(value.to_string()),
         }
     }
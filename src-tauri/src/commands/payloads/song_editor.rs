@@ -0,0 +1,83 @@
+use midi_reader::song_editor::{EditableNote, EditableSong};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Uma nota da musica aberta no editor, todos os tempos sao em ticks.
+///
+/// O `index` é a posicao da nota na lista e é ele que deve ser usado para
+/// editar a nota, ele pode mudar depois de cada alteracao.
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(
+    export,
+    export_to = "../../src/app/core/model/EditableNote.ts",
+    rename = "EditableNote"
+)]
+pub struct EditableNotePayload {
+    #[serde(default)]
+    pub index: u32,
+    pub track: u32,
+    pub channel: u8,
+    pub key: u8,
+    pub velocity: u8,
+    pub start_tick: u32,
+    pub duration_ticks: u32,
+}
+
+/// Estado atual da musica aberta no editor
+#[derive(Debug, Serialize, Clone, TS)]
+#[ts(
+    export,
+    export_to = "../../src/app/core/model/EditableSong.ts",
+    rename = "EditableSong"
+)]
+pub struct EditableSongPayload {
+    music_id: i32,
+    ticks_per_beat: u16,
+    tempo_bpm: f64,
+    track_count: u32,
+    notes: Vec<EditableNotePayload>,
+}
+
+impl EditableNotePayload {
+    fn from_note(index: usize, note: &EditableNote) -> Self {
+        Self {
+            index: index as u32,
+            track: note.track as u32,
+            channel: note.channel,
+            key: note.key,
+            velocity: note.velocity,
+            start_tick: note.start,
+            duration_ticks: note.duration,
+        }
+    }
+}
+
+impl From<EditableNotePayload> for EditableNote {
+    fn from(value: EditableNotePayload) -> Self {
+        Self {
+            track: value.track as usize,
+            channel: value.channel,
+            key: value.key,
+            velocity: value.velocity,
+            start: value.start_tick,
+            duration: value.duration_ticks,
+        }
+    }
+}
+
+impl EditableSongPayload {
+    pub fn new(song: &EditableSong, music_id: i32) -> Self {
+        Self {
+            music_id,
+            ticks_per_beat: song.ticks_per_beat(),
+            tempo_bpm: song.tempo_bpm(),
+            track_count: song.track_count() as u32,
+            notes: song
+                .notes()
+                .iter()
+                .enumerate()
+                .map(|(i, n)| EditableNotePayload::from_note(i, n))
+                .collect(),
+        }
+    }
+}
@@ -1,42 +1,26 @@
-use crate::app_states::current_music_score_state::CurrentMusicScoreState;
+use crate::app_states::midi_device_state::MidiState;
 use crate::app_states::database_state::DatabaseState;
+use crate::app_states::hit_judge_state::HitJudgeState;
+use crate::app_states::scoring_state::ScoringState;
 use crate::app_states::monitoring_state::MonitoringState;
+use crate::app_states::store_state::StoreState;
+use crate::app_states::user_state::{UserState, HIT_WINDOWS_KEY, SCORING_PROFILE_KEY};
 use crate::commands::commands_utils::database_queries::get_music;
-use crate::commands::payloads::on_note_data::{OnNoteMessage, OnNotePayload};
+use crate::commands::commands_utils::midi_connection_utils::player_slot;
+use crate::commands::payloads::device_profile::BreathCurve;
+use crate::commands::payloads::hit_windows::HitWindows;
 use crate::commands::payloads::score::{OrderType, ScorePayload};
+use crate::commands::payloads::scoring_profile::ScoringProfile;
 use crate::commands::payloads::service_error::ServiceResult;
-use crate::commands::OnNotePrecision;
-use crate::constants::errors::{DATABASE_NO_VALUES_FOUND, INVALID_PARAMETER};
+use crate::constants::errors::DATABASE_NO_VALUES_FOUND;
 use entity::prelude::Score;
 use entity::score;
 use migration::Order;
-use paris::error;
+use paris::{error, info, success, warn};
+use persistence::storage::StorageError;
 use sea_orm::{ColumnTrait, EntityTrait, ModelTrait, QueryFilter, QueryOrder};
-use strum::IntoEnumIterator;
 use tauri::State;
 
-#[tauri::command]
-pub async fn on_note(
-    on_note_message: OnNoteMessage,
-    current_music_score: State<'_, CurrentMusicScoreState>,
-    monitoring_state: State<'_, MonitoringState>,
-) -> ServiceResult<OnNotePayload> {
-    if on_note_message.precision as usize >= OnNotePrecision::iter().len() {
-        return Err(INVALID_PARAMETER.into());
-    }
-    let precision = unsafe { std::mem::transmute(on_note_message.precision) };
-    let (new_total_score, gained_score, hit_streak) = current_music_score.add_to_total_score(
-        f32::from(precision),
-        !bool::from(precision),
-    );
-    monitoring_state.receive_score(precision)?;
-    Ok(OnNotePayload::new(
-        hit_streak,
-        new_total_score as i64,
-        gained_score as i32,
-    ))
-}
-
 #[tauri::command]
 pub async fn reset_music_score(
     music_id: i32,
@@ -85,3 +69,82 @@ pub async fn list_scores(
     }
     Ok(v)
 }
+
+#[tauri::command]
+pub async fn breath_curve(
+    player: Option<u8>,
+    monitoring_state: State<'_, MonitoringState>,
+    midi_state: State<'_, MidiState>,
+) -> ServiceResult<BreathCurve> {
+    let player = player_slot(player)?;
+    let profile = midi_state.device_profile(player)?;
+    Ok(BreathCurve {
+        sample_rate: profile.pressure_sample_rate,
+        samples: monitoring_state.breath_curve(player)?,
+    })
+}
+
+/// Janelas usadas para julgar as notas tocadas
+#[tauri::command]
+pub async fn hit_windows(judge_state: State<'_, HitJudgeState>) -> ServiceResult<HitWindows> {
+    judge_state.windows()
+}
+
+/// Troca as janelas usadas para julgar as notas e salva para o usuario atual
+#[tauri::command]
+pub async fn set_hit_windows(
+    windows: HitWindows,
+    judge_state: State<'_, HitJudgeState>,
+    user_state: State<'_, UserState>,
+    store_state: State<'_, StoreState>,
+) -> ServiceResult<()> {
+    let windows = windows.validated()?;
+    judge_state.set_windows(windows)?;
+    if let Some(user) = user_state.user()? {
+        store_state.save(&UserState::key(&user, HIT_WINDOWS_KEY), &windows)?;
+        store_state.commit()?;
+        success!("Saved hit windows {:?} for user {}", windows, user);
+    } else {
+        warn!("No user selected, hit windows will not be saved");
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_scoring_profiles(
+    scoring_state: State<'_, ScoringState>,
+) -> ServiceResult<Vec<ScoringProfile>> {
+    Ok(scoring_state.profiles().to_vec())
+}
+
+#[tauri::command]
+pub async fn active_scoring_profile(
+    scoring_state: State<'_, ScoringState>,
+) -> ServiceResult<ScoringProfile> {
+    scoring_state.active()
+}
+
+/// Escolhe o perfil de pontuacao do usuario atual, [None] volta para o padrao.
+/// Vale a partir da proxima musica
+#[tauri::command]
+pub async fn select_scoring_profile(
+    profile_id: Option<String>,
+    scoring_state: State<'_, ScoringState>,
+    user_state: State<'_, UserState>,
+    store_state: State<'_, StoreState>,
+) -> ServiceResult<ScoringProfile> {
+    scoring_state.set_user_profile(profile_id.clone())?;
+    if let Some(user) = user_state.user()? {
+        let key = UserState::key(&user, SCORING_PROFILE_KEY);
+        match profile_id.as_ref() {
+            Some(id) => store_state.save(&key, id)?,
+            None => match store_state.remove(&key) {
+                Ok(_) | Err(StorageError::KeyNotFound(_)) => {}
+                Err(e) => return Err(e.into()),
+            },
+        }
+        store_state.commit()?;
+        info!("Saved scoring profile {:?} for user {}", profile_id, user);
+    }
+    scoring_state.active()
+}
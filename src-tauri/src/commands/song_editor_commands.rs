@@ -0,0 +1,166 @@
+use crate::app_states::database_state::DatabaseState;
+use crate::app_states::song_editor_state::SongEditorState;
+use crate::commands::commands_utils::database_queries::get_music;
+use crate::commands::commands_utils::midi_file_utils::{check_midi_bytes, music_path, read_music_from_id};
+use crate::commands::payloads::music::MidiMusic;
+use crate::commands::payloads::service_error::ServiceResult;
+use crate::commands::payloads::song_editor::{EditableNotePayload, EditableSongPayload};
+use crate::constants::errors::FILE_NAME_ALREADY_EXIST;
+use convert_case::{Case, Casing};
+use entity::music;
+use entity::prelude::Music;
+use midi_reader::song_editor::EditableSong;
+use paris::{info, success, Logger};
+use sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, TransactionTrait};
+use std::fs;
+use tauri::{AppHandle, Runtime, State};
+
+#[inline]
+fn song_payload(editor_state: &SongEditorState) -> ServiceResult<EditableSongPayload> {
+    editor_state.read(EditableSongPayload::new)
+}
+
+#[tauri::command]
+pub async fn open_song_editor<R: Runtime>(
+    music_id: i32,
+    editor_state: State<'_, SongEditorState>,
+    db_state: State<'_, DatabaseState>,
+    handle: AppHandle<R>,
+) -> ServiceResult<EditableSongPayload> {
+    info!("Opening music with id {} in the editor...", music_id);
+    let (_, file) = read_music_from_id(&db_state, &handle, music_id).await?;
+    let song = EditableSong::from_bytes(&file)?;
+    editor_state.open(music_id, song)?;
+    success!("Music loaded into the editor");
+    song_payload(&editor_state)
+}
+
+#[tauri::command]
+pub async fn close_song_editor(editor_state: State<'_, SongEditorState>) -> ServiceResult<()> {
+    editor_state.close()
+}
+
+#[tauri::command]
+pub async fn editor_move_note(
+    index: u32,
+    start_tick: u32,
+    key: u8,
+    editor_state: State<'_, SongEditorState>,
+) -> ServiceResult<EditableSongPayload> {
+    editor_state.edit(move |song| song.move_note(index as usize, start_tick, key))?;
+    song_payload(&editor_state)
+}
+
+#[tauri::command]
+pub async fn editor_resize_note(
+    index: u32,
+    duration_ticks: u32,
+    editor_state: State<'_, SongEditorState>,
+) -> ServiceResult<EditableSongPayload> {
+    editor_state.edit(move |song| song.resize_note(index as usize, duration_ticks))?;
+    song_payload(&editor_state)
+}
+
+#[tauri::command]
+pub async fn editor_set_note_velocity(
+    index: u32,
+    velocity: u8,
+    editor_state: State<'_, SongEditorState>,
+) -> ServiceResult<EditableSongPayload> {
+    editor_state.edit(move |song| song.set_velocity(index as usize, velocity))?;
+    song_payload(&editor_state)
+}
+
+#[tauri::command]
+pub async fn editor_insert_note(
+    note: EditableNotePayload,
+    editor_state: State<'_, SongEditorState>,
+) -> ServiceResult<EditableSongPayload> {
+    editor_state.edit(move |song| song.insert_note(note.into()))?;
+    song_payload(&editor_state)
+}
+
+#[tauri::command]
+pub async fn editor_delete_note(
+    index: u32,
+    editor_state: State<'_, SongEditorState>,
+) -> ServiceResult<EditableSongPayload> {
+    editor_state.edit(move |song| song.delete_note(index as usize))?;
+    song_payload(&editor_state)
+}
+
+#[tauri::command]
+pub async fn editor_change_tempo(
+    bpm: f64,
+    editor_state: State<'_, SongEditorState>,
+) -> ServiceResult<EditableSongPayload> {
+    editor_state.edit(move |song| song.set_tempo_bpm(bpm))?;
+    song_payload(&editor_state)
+}
+
+#[tauri::command]
+pub async fn save_song_editor<R: Runtime>(
+    new_music_name: Option<String>,
+    editor_state: State<'_, SongEditorState>,
+    db_state: State<'_, DatabaseState>,
+    handle: AppHandle<R>,
+) -> ServiceResult<MidiMusic> {
+    let mut logger = Logger::new();
+    let (song, music_id) = editor_state.read(move |song, id| (song.clone(), id))?;
+    logger.info("Writing edited midi file...");
+    let bytes = song.to_bytes()?;
+    let duration = check_midi_bytes(bytes.clone())?;
+    // O arquivo so é escrito depois do banco aceitar a mudanca, e a transacao
+    // so é confirmada depois do arquivo ser escrito
+    let txn = db_state.db.begin().await?;
+    let saved = if let Some(name) = new_music_name {
+        if Music::find()
+            .filter(music::Column::Name.eq(&name))
+            .one(&db_state.db)
+            .await?
+            .is_some()
+        {
+            return Err(FILE_NAME_ALREADY_EXIST.into());
+        }
+        let directory = format!("{}.mid", name.to_case(Case::Snake));
+        let path = music_path(&handle, &directory)?;
+        if path.exists() {
+            return Err(FILE_NAME_ALREADY_EXIST.into());
+        }
+        let model = music::ActiveModel {
+            id: Default::default(),
+            name: ActiveValue::Set(name),
+            duration: ActiveValue::Set(duration as i32),
+            directory: ActiveValue::Set(directory),
+        };
+        let new = model.insert(&txn).await?;
+        fs::write(&path, bytes)?;
+        if let Err(e) = txn.commit().await {
+            let _ = fs::remove_file(&path);
+            return Err(e.into());
+        }
+        logger.success(format!("Edited music saved as a new music with id {}", new.id));
+        editor_state.open(new.id, song)?;
+        new
+    } else {
+        let old = get_music(music_id, &db_state).await?;
+        let path = music_path(&handle, &old.directory)?;
+        let mut model: music::ActiveModel = old.into();
+        model.duration = ActiveValue::Set(duration as i32);
+        let updated = model.update(&txn).await?;
+        // o original so é trocado depois da transacao, um erro antes disso deixa ele intacto
+        let temp_path = path.with_extension("mid.tmp");
+        if let Err(e) = fs::write(&temp_path, bytes) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        if let Err(e) = txn.commit().await {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        fs::rename(&temp_path, &path)?;
+        logger.success(format!("Music with id {} overwritten", music_id));
+        updated
+    };
+    Ok(saved.into())
+}
@@ -0,0 +1,84 @@
+use crate::app_states::calibration_state::CalibrationState;
+use crate::app_states::hit_judge_state::HitJudgeState;
+use crate::app_states::instrument_state::InstrumentState;
+use crate::app_states::midi_device_state::MAIN_PLAYER;
+use crate::app_states::scoring_state::ScoringState;
+use crate::app_states::store_state::StoreState;
+use crate::app_states::user_state::{UserState, CALIBRATION_KEY, HIT_WINDOWS_KEY, INSTRUMENT_KEY, SCORING_PROFILE_KEY};
+use crate::commands::payloads::calibration::BreathCalibrationInfo;
+use crate::commands::payloads::hit_windows::HitWindows;
+use crate::commands::payloads::service_error::ServiceResult;
+use arduino_comm::breath_calibration::BreathCalibration;
+use paris::{info, warn};
+use persistence::storage::{StorageError, StorageRetrievable};
+use tauri::State;
+
+/// Troca o usuario atual e carrega as preferencias salvas dele
+#[tauri::command]
+pub async fn set_current_user(
+    user_id: Option<String>,
+    user_state: State<'_, UserState>,
+    instrument_state: State<'_, InstrumentState>,
+    calibration_state: State<'_, CalibrationState>,
+    judge_state: State<'_, HitJudgeState>,
+    scoring_state: State<'_, ScoringState>,
+    store_state: State<'_, StoreState>,
+) -> ServiceResult<()> {
+    if let Some(id) = user_id.as_ref() {
+        info!("Loading preferences of user {}", id);
+    }
+    let instrument = preference::<String>(&store_state, user_id.as_deref(), INSTRUMENT_KEY)?;
+    if instrument_state.set_user_instrument(instrument.clone()).is_err() {
+        warn!("Saved instrument {:?} does not exist anymore, using the default", instrument);
+        instrument_state.set_user_instrument(None)?;
+    }
+    let calibration = preference::<BreathCalibrationInfo>(&store_state, user_id.as_deref(), CALIBRATION_KEY)?
+        .map(BreathCalibration::from)
+        .and_then(|c| match c.validated() {
+            Ok(c) => Some(c),
+            Err(e) => {
+                warn!("Saved breath calibration is invalid, using the default: {}", e);
+                None
+            }
+        })
+        .unwrap_or_default();
+    calibration_state.cancel_calibration();
+    calibration_state.set_calibration(MAIN_PLAYER, calibration)?;
+    let windows = preference::<HitWindows>(&store_state, user_id.as_deref(), HIT_WINDOWS_KEY)?
+        .and_then(|w| match w.validated() {
+            Ok(w) => Some(w),
+            Err(_) => {
+                warn!("Saved hit windows {:?} are invalid, using the default", w);
+                None
+            }
+        })
+        .unwrap_or_default();
+    judge_state.set_windows(windows)?;
+    let scoring_profile = preference::<String>(&store_state, user_id.as_deref(), SCORING_PROFILE_KEY)?;
+    if scoring_state.set_user_profile(scoring_profile.clone()).is_err() {
+        warn!("Saved scoring profile {:?} does not exist anymore, using the default", scoring_profile);
+        scoring_state.set_user_profile(None)?;
+    }
+    user_state.set_user(user_id)
+}
+
+#[tauri::command]
+pub async fn current_user(user_state: State<'_, UserState>) -> ServiceResult<Option<String>> {
+    user_state.user()
+}
+
+/// Preferencia salva do usuario, [None] se nao tiver usuario ou se ela nunca foi salva
+fn preference<V: StorageRetrievable>(
+    store_state: &StoreState,
+    user_id: Option<&str>,
+    key: &str,
+) -> ServiceResult<Option<V>> {
+    let Some(id) = user_id else {
+        return Ok(None);
+    };
+    match store_state.retrieve::<V>(&UserState::key(id, key)) {
+        Ok(v) => Ok(Some(v)),
+        Err(StorageError::KeyNotFound(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
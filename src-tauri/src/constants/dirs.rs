@@ -1,6 +1,8 @@
 pub(crate) const RESOURCES_FOLDER: &str = "resources/";
 pub(crate) const MUSICS_FOLDER: &str = "musics/";
 pub(crate) const DATA_FOLDER: &str = "data/";
+pub(crate) const INSTRUMENTS_FOLDER: &str = "instruments/";
+pub(crate) const SCORING_FOLDER: &str = "scoring/";
 pub(crate) const STORE_NAME: &str = "inspire_music_data.db";
 pub(crate) const DB_PROTOCOL: &str = "sqlite:";
 pub(crate) const DB_NAME: &str = "inspire.db";
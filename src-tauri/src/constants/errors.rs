@@ -9,6 +9,8 @@ const TAURI_ERRORS_PREFIX: char = 'T';
 const STORAGE_ERRORS_PREFIX: &str = "ST";
 const INPUT_DEVICE_ERRORS_PREFIX: char = 'I';
 const DATABASE_RELATED_ERRORS_PREFIX: char = 'D';
+const EDITOR_ERRORS_PREFIX: char = 'E';
+const USER_ERRORS_PREFIX: char = 'U';
 
 #[derive(Error, Debug)]
 pub struct CodedError {
@@ -84,6 +86,16 @@ pub const FILE_COULD_NOT_READ_PATH: CodedError = CodedError {
     message: "Could not read file location",
 };
 
+pub const FILE_RENDER_ERROR: CodedError = CodedError {
+    code: concatcp!(FILE_ERRORS_PREFIX, "10"),
+    message: "Could not render the audio file",
+};
+
+pub const FILE_NO_PERFORMANCE_RECORDED: CodedError = CodedError {
+    code: concatcp!(FILE_ERRORS_PREFIX, "11"),
+    message: "There is no recorded performance to render",
+};
+
 // STATE RELATED ERRORS
 pub const STATE_ACQUIRE_ERROR: CodedError = CodedError {
     code: concatcp!(STATE_ERRORS_PREFIX, "01"),
@@ -107,6 +119,14 @@ pub const MIDI_NOT_SUPPORTED: CodedError = CodedError {
     code: concatcp!(MIDI_ERRORS, "03"),
     message: "This midi file is not supported",
 };
+pub const MIDI_OUTPUT_PORT_NOT_FOUND: CodedError = CodedError {
+    code: concatcp!(MIDI_ERRORS, "04"),
+    message: "Midi output port could not be found",
+};
+pub const MIDI_INVALID_TRACK: CodedError = CodedError {
+    code: concatcp!(MIDI_ERRORS, "05"),
+    message: "Midi track does not exist",
+};
 
 // KEY VALUE STORAGE RELATED ERRORS
 pub const STORAGE_COULD_NOT_BE_CREATED: CodedError = CodedError {
@@ -158,6 +178,82 @@ pub const DEVICE_NOT_CONNECTED: CodedError = CodedError {
     code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "06"),
     message: "There is no device connected",
 };
+pub const DEVICE_THRU_PORT_NOT_FOUND: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "07"),
+    message: "Midi thru output port could not be found",
+};
+pub const DEVICE_THRU_COULD_NOT_CONNECT: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "08"),
+    message: "Could not connect to the midi thru output port",
+};
+pub const DEVICE_THRU_INVALID_CONFIG: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "09"),
+    message: "Invalid midi thru configuration",
+};
+pub const DEVICE_THRU_NOT_ENABLED: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "10"),
+    message: "Midi thru is not enabled",
+};
+pub const DEVICE_RECORDING_ERROR: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "11"),
+    message: "Error while recording device input",
+};
+pub const DEVICE_INVALID_RECORDING: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "12"),
+    message: "File is not a valid input recording",
+};
+pub const DEVICE_INVALID_REPLAY_SPEED: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "13"),
+    message: "Replay speed must be at least 1.0",
+};
+pub const DEVICE_SERIAL_ERROR: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "14"),
+    message: "Could not communicate with the serial device",
+};
+pub const DEVICE_INVALID_LATENCY_CALIBRATION: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "15"),
+    message: "Latency calibration failed, play along with the clicks and try again",
+};
+pub const DEVICE_LATENCY_NOT_CALIBRATING: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "16"),
+    message: "There is no latency calibration in progress",
+};
+pub const DEVICE_INVALID_INPUT_FILTER: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "17"),
+    message: "Invalid input filter, the off velocity must not be higher than the on velocity",
+};
+pub const DEVICE_CONFIG_ERROR: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "18"),
+    message: "Could not read or write the device configuration, the device may not support it",
+};
+pub const DEVICE_CONFIG_TIMEOUT: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "19"),
+    message: "The device did not answer, check if its firmware is up to date",
+};
+pub const DEVICE_INVALID_CONFIG: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "20"),
+    message: "Invalid device configuration",
+};
+pub const DEVICE_INVALID_FIRMWARE: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "21"),
+    message: "The firmware file is not a valid firmware for the device",
+};
+pub const DEVICE_FIRMWARE_UPLOAD_FAILED: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "22"),
+    message: "Firmware upload failed and the device may not work until it is updated again",
+};
+pub const DEVICE_FIRMWARE_ROLLED_BACK: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "23"),
+    message: "Firmware upload failed, the previous firmware was restored",
+};
+pub const DEVICE_INVALID_PLAYER: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "24"),
+    message: "Invalid player slot for the device",
+};
+pub const DEVICE_OSC_ERROR: CodedError = CodedError {
+    code: concatcp!(INPUT_DEVICE_ERRORS_PREFIX, "25"),
+    message: "Could not send or receive OSC messages",
+};
 
 // DATABASE RELATED ERRORS
 pub const DATABASE_COULD_NOT_LOAD: CodedError = CodedError {
@@ -181,5 +277,54 @@ pub const DATABASE_NO_VALUES_FOUND: CodedError = CodedError {
     message: "No values could be found for the requested operation",
 };
 
+// SONG EDITOR RELATED ERRORS
+pub const EDITOR_NO_MUSIC_OPEN: CodedError = CodedError {
+    code: concatcp!(EDITOR_ERRORS_PREFIX, "01"),
+    message: "There is no music open in the editor",
+};
+pub const EDITOR_NOTE_NOT_FOUND: CodedError = CodedError {
+    code: concatcp!(EDITOR_ERRORS_PREFIX, "02"),
+    message: "Note does not exist in the music being edited",
+};
+pub const EDITOR_INVALID_EDIT: CodedError = CodedError {
+    code: concatcp!(EDITOR_ERRORS_PREFIX, "03"),
+    message: "This edit cannot be applied to the music",
+};
+pub const EDITOR_COULD_NOT_SAVE: CodedError = CodedError {
+    code: concatcp!(EDITOR_ERRORS_PREFIX, "04"),
+    message: "Could not write the edited midi file",
+};
 
-
+// USER AND INSTRUMENT RELATED ERRORS
+pub const INSTRUMENT_NOT_FOUND: CodedError = CodedError {
+    code: concatcp!(USER_ERRORS_PREFIX, "01"),
+    message: "Instrument profile does not exist",
+};
+pub const INSTRUMENT_INVALID_PROFILE: CodedError = CodedError {
+    code: concatcp!(USER_ERRORS_PREFIX, "02"),
+    message: "Instrument profile is invalid",
+};
+pub const INSTRUMENT_NOTE_NOT_SUPPORTED: CodedError = CodedError {
+    code: concatcp!(USER_ERRORS_PREFIX, "03"),
+    message: "The selected instrument cannot play this note",
+};
+pub const USER_INVALID_CALIBRATION: CodedError = CodedError {
+    code: concatcp!(USER_ERRORS_PREFIX, "04"),
+    message: "Breath calibration is invalid, record all steps again",
+};
+pub const USER_CALIBRATION_NOT_STARTED: CodedError = CodedError {
+    code: concatcp!(USER_ERRORS_PREFIX, "05"),
+    message: "There is no breath calibration in progress",
+};
+pub const USER_INVALID_HIT_WINDOWS: CodedError = CodedError {
+    code: concatcp!(USER_ERRORS_PREFIX, "06"),
+    message: "Hit windows must grow from middle to right and left, and right up to early miss",
+};
+pub const USER_SCORING_PROFILE_NOT_FOUND: CodedError = CodedError {
+    code: concatcp!(USER_ERRORS_PREFIX, "07"),
+    message: "Scoring profile does not exist",
+};
+pub const USER_INVALID_SCORING_PROFILE: CodedError = CodedError {
+    code: concatcp!(USER_ERRORS_PREFIX, "08"),
+    message: "Scoring profile needs an id, non negative points, positive combo multipliers and valid grade thresholds",
+};
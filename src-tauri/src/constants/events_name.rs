@@ -2,3 +2,10 @@ pub const MIDI_NOTE: &str = "MIDI_INPUT_NOTE";
 pub const MIDI_READ_NOTE: &str = "MIDI_READ_NOTE";
 pub const MIDI_READ_STATE: &str = "MIDI_READ_STATE";
 pub const MIDI_DEVICE_CONNECTION_STATE: &str = "MIDI_DEVICE_CONNECTION";
+pub const BREATH_PRESSURE: &str = "BREATH_PRESSURE";
+pub const LATENCY_CLICK: &str = "LATENCY_CLICK";
+pub const FIRMWARE_UPLOAD_PROGRESS: &str = "FIRMWARE_UPLOAD_PROGRESS";
+pub const GAME_INTERRUPTION: &str = "GAME_INTERRUPTION";
+pub const NOTE_JUDGEMENT: &str = "NOTE_JUDGEMENT";
+pub const NOTE_SUSTAIN: &str = "NOTE_SUSTAIN";
+pub const BREATH_ZONE: &str = "BREATH_ZONE";
@@ -1,2 +1,4 @@
 pub const MIDI_LENGTH_SECONDS_LIMIT: u64 = 600;
-pub const MIDI_DEVICE_CONNECTION_CHECKER_TIMEOUT: u64 = 1000; // ms
\ No newline at end of file
+pub const MIDI_DEVICE_CONNECTION_CHECKER_TIMEOUT: u64 = 1000; // ms
+pub const MAX_PLAYERS: u8 = 4;
+pub const HIT_JUDGE_TICK: u64 = 10; // ms
@@ -1,14 +1,31 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use crate::app_states::backing_track_state::BackingTrackState;
+use crate::app_states::calibration_state::CalibrationState;
 use crate::app_states::current_music_score_state::CurrentMusicScoreState;
 use crate::app_states::database_state::{DatabaseResult, DatabaseState};
+use crate::app_states::duet_state::DuetState;
+use crate::app_states::game_interruption_state::GameInterruptionState;
+use crate::app_states::hit_judge_state::HitJudgeState;
+use crate::app_states::instrument_state::InstrumentState;
+use crate::app_states::latency_state::LatencyState;
 use crate::app_states::monitoring_state::MonitoringState;
+use crate::app_states::performance_state::PerformanceState;
+use crate::app_states::scoring_state::ScoringState;
+use crate::app_states::song_editor_state::SongEditorState;
 use crate::app_states::store_state::StoreState;
-use crate::constants::dirs::{DATA_FOLDER, DB_NAME, RESOURCES_FOLDER, STORE_NAME};
+use crate::app_states::sustain_state::SustainState;
+use crate::app_states::user_state::UserState;
+use crate::constants::dirs::{DATA_FOLDER, DB_NAME, INSTRUMENTS_FOLDER, RESOURCES_FOLDER, SCORING_FOLDER, STORE_NAME};
 use crate::constants::errors::{CodedError, COULDNT_GET_PATH};
 use app_states::midi_device_state::MidiState;
-use commands::{midi_connection_commands::*, midi_reader_commands::*, score_commands::*};
+use commands::{
+    backing_track_commands::*, calibration_commands::*, firmware_commands::*, instrument_commands::*, latency_commands::*,
+    midi_connection_commands::*, midi_reader_commands::*, score_commands::*, song_editor_commands::*, user_commands::*,
+};
+use commands::start_device_watcher;
+use paris::error;
 use persistence::storage::StorageResult;
 use std::path::PathBuf;
 use tauri::async_runtime::block_on;
@@ -39,33 +56,118 @@ fn main() {
         .manage(MidiState::new())
         .manage(CurrentMusicScoreState::default())
         .manage(MonitoringState::default())
+        .manage(SongEditorState::default())
+        .manage(PerformanceState::default())
+        .manage(BackingTrackState::default())
+        .manage(UserState::default())
+        .manage(GameInterruptionState::default())
+        .manage(CalibrationState::default())
+        .manage(LatencyState::default())
+        .manage(DuetState::default())
+        .manage(HitJudgeState::default())
+        .manage(SustainState::default())
         .invoke_handler(tauri::generate_handler![
             start_listening_midi,
             connect_to_midi,
+            connect_virtual_midi,
+            list_serial_devices,
+            connect_to_serial,
+            virtual_midi_input,
             disconnect_midi,
+            connected_players,
             list_midi_devices,
             start_game,
             end_game,
             pause_game,
             stop_game,
             resume_game,
+            set_game_auto_resume,
+            set_duet_mode,
+            duet_mode,
             list_musics,
             music_length,
             remaining_time,
-            on_note,
+            hit_windows,
+            set_hit_windows,
+            list_scoring_profiles,
+            active_scoring_profile,
+            select_scoring_profile,
             reset_music_score,
             list_scores,
             add_new_music,
-            remove_music
+            remove_music,
+            open_song_editor,
+            close_song_editor,
+            editor_move_note,
+            editor_resize_note,
+            editor_set_note_velocity,
+            editor_insert_note,
+            editor_delete_note,
+            editor_change_tempo,
+            save_song_editor,
+            render_music_preview,
+            render_performance,
+            list_midi_output_ports,
+            select_midi_output_port,
+            backing_track_config,
+            set_player_tracks,
+            set_track_mute,
+            set_track_solo,
+            list_music_tracks,
+            enable_midi_thru,
+            update_midi_thru,
+            disable_midi_thru,
+            midi_thru_settings,
+            connect_osc_input,
+            enable_osc_output,
+            disable_osc_output,
+            osc_output_target,
+            connected_device_profile,
+            connected_device_info,
+            device_settings,
+            write_device_settings,
+            upload_device_firmware,
+            input_filter_settings,
+            update_input_filter,
+            input_filter_stats,
+            reset_input_filter_stats,
+            start_input_recording,
+            stop_input_recording,
+            replay_input_session,
+            breath_curve,
+            set_current_user,
+            current_user,
+            list_instruments,
+            active_instrument,
+            select_instrument,
+            check_music_instrument,
+            start_breath_calibration_step,
+            finish_breath_calibration_step,
+            save_breath_calibration,
+            cancel_breath_calibration,
+            breath_calibration,
+            reset_breath_calibration,
+            start_latency_calibration,
+            finish_latency_calibration,
+            cancel_latency_calibration,
+            input_latency,
+            set_input_latency
         ])
         .setup(move |app| {
             let mut context_resources_path = get_context_path(app)?;
+            let instruments = InstrumentState::load(&context_resources_path.join(INSTRUMENTS_FOLDER));
+            app.manage(instruments);
+            let scoring = ScoringState::load(&context_resources_path.join(SCORING_FOLDER));
+            app.manage(scoring);
             context_resources_path.push(DATA_FOLDER);
             let store = create_storage(&context_resources_path)?;
             let db = create_db(&context_resources_path)?;
             drop(context_resources_path);
             app.manage(store);
             app.manage(db);
+            if let Err(e) = start_device_watcher(app.handle()) {
+                error!("Could not start the device watcher: {}", e.message);
+            }
             Ok(())
         })
         .run(tauri::generate_context!())
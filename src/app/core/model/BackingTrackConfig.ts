@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Configuracao atual do acompanhamento
+ */
+export type BackingTrackConfig = { output_port: string | null, 
+/**
+ * Trilhas que vao para a partitura do paciente, vazio significa todas
+ */
+player_tracks: Array<number>, muted_tracks: Array<number>, solo_tracks: Array<number>, };
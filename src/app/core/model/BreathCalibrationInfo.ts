@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Curva de resposta do sopro do paciente, valores crus do dispositivo (0 a 127)
+ */
+export type BreathCalibrationInfo = { noise_floor: number, comfortable: number, maximum: number, };
\ No newline at end of file
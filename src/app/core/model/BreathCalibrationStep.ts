@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Etapa da calibracao do sopro
+ */
+export type BreathCalibrationStep = "Resting" | "Comfortable" | "Maximum";
\ No newline at end of file
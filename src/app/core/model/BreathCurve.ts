@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Amostras de pressao do sopro desde o inicio da musica, em taxa fixa
+ */
+export type BreathCurve = { sample_rate: number, samples: Array<number>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Amostra de pressao do sopro de um jogador, de 0 a 127
+ */
+export type BreathPressure = { player: number, pressure: number, };
\ No newline at end of file
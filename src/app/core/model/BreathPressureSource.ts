@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * De onde vem a pressao do sopro do dispositivo
+ */
+export type BreathPressureSource = "NoteVelocity" | { "ControlChange": number } | "ChannelPressure";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Onde a pressao do jogador esta em relacao a zona alvo da nota
+ */
+export type BreathZone = "TooWeak" | "InZone" | "TooStrong";
\ No newline at end of file
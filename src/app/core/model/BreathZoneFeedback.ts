@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BreathZone } from "./BreathZone";
+
+/**
+ * Evento emitido quando a pressao do jogador entra ou sai da zona alvo de uma nota da partitura
+ */
+export type BreathZoneFeedback = { player: number, 
+/**
+ * Nota midi da partitura
+ */
+key: number, zone: BreathZone, 
+/**
+ * Pressao do jogador, de 0 a 127
+ */
+pressure: number, 
+/**
+ * Limites da zona alvo, vem da velocidade da nota
+ */
+min: number, max: number, };
\ No newline at end of file
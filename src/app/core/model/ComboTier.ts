@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A partir de `min_streak` acertos seguidos os pontos sao multiplicados por `multiplier`
+ */
+export type ComboTier = { min_streak: number, multiplier: number, };
\ No newline at end of file
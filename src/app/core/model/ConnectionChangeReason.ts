@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Motivo da mudanca na conexao com o dispositivo
+ */
+export type ConnectionChangeReason = "Connected" | "Disconnected" | "DeviceDetached" | "Reconnected" | "ReconnectFailed";
\ No newline at end of file
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConnectionChangeReason } from "./ConnectionChangeReason";
+
+/**
+ * Enviado no evento de conexao sempre que o dispositivo conecta ou desconecta
+ */
+export type DeviceConnectionState = { connected: boolean, port_name: string | null, reason: ConnectionChangeReason, 
+/**
+ * Jogador dono do dispositivo
+ */
+player: number, };
\ No newline at end of file
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Identificacao da placa conectada, lida do firmware
+ */
+export type DeviceFirmwareInfo = { port_name: string, device_id: number, 
+/**
+ * `major.minor.patch`
+ */
+firmware_version: string, };
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Posicao dos dedos (um bit por furo, 1 é tapado) e a nota tocada com ela
+ */
+export type DeviceNoteMapping = { fingering: number, note: number, };
\ No newline at end of file
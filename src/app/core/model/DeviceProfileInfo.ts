@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BreathPressureSource } from "./BreathPressureSource";
+
+/**
+ * Perfil usado para ler o dispositivo conectado
+ */
+export type DeviceProfileInfo = { name: string, port_pattern: string, pressure_source: BreathPressureSource, 
+/**
+ * Amostras de pressao por segundo
+ */
+pressure_sample_rate: number, };
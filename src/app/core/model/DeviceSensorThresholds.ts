@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Sensibilidade dos sensores da placa, de 0 a 127
+ */
+export type DeviceSensorThresholds = { breath_on: number, 
+/**
+ * Nao pode ser maior que `breath_on`
+ */
+breath_off: number, key_touch: number, };
\ No newline at end of file
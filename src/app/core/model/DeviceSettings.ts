@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DeviceNoteMapping } from "./DeviceNoteMapping";
+import type { DeviceSensorThresholds } from "./DeviceSensorThresholds";
+
+/**
+ * Configuracao gravada no firmware da placa
+ */
+export type DeviceSettings = { thresholds: DeviceSensorThresholds, note_map: Array<DeviceNoteMapping>, };
\ No newline at end of file
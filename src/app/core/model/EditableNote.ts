@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Uma nota da musica aberta no editor, todos os tempos sao em ticks.
+ *
+ * O `index` é a posicao da nota na lista e é ele que deve ser usado para
+ * editar a nota, ele pode mudar depois de cada alteracao.
+ */
+export type EditableNote = { index: number, track: number, channel: number, key: number, velocity: number, start_tick: number, duration_ticks: number, };
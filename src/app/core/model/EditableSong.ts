@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EditableNote } from "./EditableNote";
+
+/**
+ * Estado atual da musica aberta no editor
+ */
+export type EditableSong = { music_id: number, ticks_per_beat: number, tempo_bpm: number, track_count: number, notes: Array<EditableNote>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FirmwareUploadStage } from "./FirmwareUploadStage";
+
+/**
+ * Paginas da flash ja processadas na etapa atual
+ */
+export type FirmwareUploadProgress = { stage: FirmwareUploadStage, done: number, total: number, };
\ No newline at end of file
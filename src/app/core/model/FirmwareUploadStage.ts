@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Etapa da gravacao do firmware, `Rollback` quando a gravacao falhou e o firmware antigo
+ * esta sendo regravado
+ */
+export type FirmwareUploadStage = "Backup" | "Write" | "Verify" | "Rollback";
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Jogo pausado porque o dispositivo desconectou no meio da musica
+ */
+export type GameInterruption = "DeviceLost" | "WaitingResume" | { "ResumeCountdown": number } | "Resumed";
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Nota de uma tentativa, a ordem é do melhor para o pior
+ */
+export type Grade = "S" | "A" | "B" | "C" | "D" | "F";
\ No newline at end of file
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Como a precisao de uma tentativa vira nota e estrelas, todas as precisoes sao de 0 a 100
+ */
+export type GradeThresholds = { 
+/**
+ * Quanto um acerto `Left` ou `Right` vale na precisao, o `Middle` vale 1
+ */
+partial_hit_weight: number, 
+/**
+ * Precisao minima de S, A, B, C e D, abaixo de todas é F
+ */
+grades: [number, number, number, number, number], 
+/**
+ * Precisao minima de cada estrela
+ */
+stars: [number, number, number, number, number], };
\ No newline at end of file
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Janelas em ms usadas para julgar as notas tocadas, contadas a partir do momento da nota
+ * na partitura. `Right` é antes da nota e `Left` depois, como a nota vindo da direita.
+ */
+export type HitWindows = { 
+/**
+ * Antes ou depois, ate esse tempo é `Middle`
+ */
+middle_ms: number, 
+/**
+ * Antes da nota, ate esse tempo é `Right`
+ */
+right_ms: number, 
+/**
+ * Depois da nota, ate esse tempo é `Left`. Passou disso a nota é `Miss`
+ */
+left_ms: number, 
+/**
+ * Antes da nota e fora da `right_ms`, ate esse tempo é `EarlyMiss`
+ */
+early_miss_ms: number, };
\ No newline at end of file
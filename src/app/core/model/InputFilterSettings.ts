@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VelocityHysteresis } from "./VelocityHysteresis";
+
+/**
+ * Filtro das notas do dispositivo, `null` desliga a parte correspondente
+ */
+export type InputFilterSettings = { 
+/**
+ * Duracao minima de uma nota em ms
+ */
+min_note_duration_ms: number | null, hysteresis: VelocityHysteresis | null, suppress_duplicate_note_ons: boolean, };
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Quantas mensagens o filtro segurou desde que o dispositivo conectou
+ */
+export type InputFilterStats = { short_notes: number, below_threshold: number, duplicate_note_ons: number, total: number, };
\ No newline at end of file
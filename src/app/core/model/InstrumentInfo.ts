@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InstrumentNoteInfo } from "./InstrumentNoteInfo";
+
+/**
+ * Instrumento que pode ser escolhido pelo usuario
+ */
+export type InstrumentInfo = { id: string, name: string, lowest_key: number, highest_key: number, notes: Array<InstrumentNoteInfo>, };
\ No newline at end of file
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Nota que o instrumento consegue tocar
+ */
+export type InstrumentNoteInfo = { key: number, name: string, 
+/**
+ * Linha na partitura, o mesmo `note_index` do `MidiSignal`
+ */
+ordinal: number, is_bmol: boolean, };
\ No newline at end of file
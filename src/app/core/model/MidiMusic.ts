@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Grade } from "./Grade";
 
 /**
  * Struct para uma musica, vai possuir o nome, id, e o diretorio dela para localizala rapidamente.
@@ -7,4 +8,8 @@
  * Se esse for o caso, chama o comando `music_length`.
  *
  */
-export type MidiMusic = { name: string, id: number, directory: string, duration: bigint, };
+export type MidiMusic = { name: string, id: number, directory: string, duration: bigint, 
+/**
+ * Melhor nota das tentativas terminadas, `null` se a musica nunca foi terminada
+ */
+best_grade: Grade | null, };
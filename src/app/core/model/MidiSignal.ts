@@ -3,4 +3,12 @@
 /**
  * O payload para enviar dados da nota midi para o front
  */
-export type MidiSignal = { note_index: number, is_bmol: boolean, note_name: string, velocity: number, state: boolean, };
+export type MidiSignal = { note_index: number, is_bmol: boolean, note_name: string, velocity: number, state: boolean, 
+/**
+ * Latencia do dispositivo, a nota foi tocada esse tempo antes do evento ser enviado
+ */
+latency_ms: number, 
+/**
+ * Jogador que tocou a nota, ou que deve tocar no caso da partitura
+ */
+player: number, };
\ No newline at end of file
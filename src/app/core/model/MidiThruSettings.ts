@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ThruVelocityCurve } from "./ThruVelocityCurve";
+
+/**
+ * Configuracao do midi thru, `port_name` é nulo quando o thru esta desligado
+ */
+export type MidiThruSettings = { port_name: string | null, transpose: number, velocity_curve: ThruVelocityCurve, channel: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Uma trilha da musica com notas, identificada pelo index dela no arquivo
+ */
+export type MusicTrack = { track: number, 
+/**
+ * Canais midi usados pelas notas da trilha
+ */
+channels: Array<number>, note_count: number, lowest_key: number, highest_key: number, };
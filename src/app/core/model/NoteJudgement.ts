@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OnNotePrecision } from "./NotePressPrecision";
+import type { OnScoreUpdateMessage } from "./OnScoreUpdateMessage";
+
+/**
+ * Evento emitido sempre que uma nota da partitura é julgada, com o score do jogador depois dela
+ */
+export type NoteJudgement = { player: number, 
+/**
+ * Nota midi da partitura
+ */
+key: number, precision: OnNotePrecision, score: OnScoreUpdateMessage, };
\ No newline at end of file
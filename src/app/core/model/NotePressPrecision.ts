@@ -1,3 +1,13 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type OnNotePrecision = "Middle" | "Left" | "Right" | "Miss" | "EarlyMiss";
+/**
+ * Precisao de uma nota tocada pelo usuario, julgada pelo backend.
+ *
+ * Poderá ter 5 estados:
+ * `Middle` para quando tocar exatamente no momento certo
+ * `Left` para quando tocar um pouco depois
+ * `Right` para quando tocar um pouco antes
+ * `Miss` quando deixar a nota passar
+ * `EarlyMiss` quando errar a nota completamente antes de entrar na area de acerto
+ */
+export type OnNotePrecision = "Middle" | "Left" | "Right" | "Miss" | "EarlyMiss";
\ No newline at end of file
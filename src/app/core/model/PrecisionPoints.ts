@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Pontos de cada acerto antes do combo
+ */
+export type PrecisionPoints = { middle: number, left: number, right: number, };
\ No newline at end of file
@@ -1,3 +1,41 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Grade } from "./Grade";
 
-export type Score = { total: number, date_achieved: string, highest_streak: number, finished: boolean, };
+export type Score = { total: number, date_achieved: string, highest_streak: number, finished: boolean, 
+/**
+ * Vezes que o jogo pausou porque o dispositivo desconectou
+ */
+interruptions: number, 
+/**
+ * Jogador que fez o score, 0 fora do modo dueto
+ */
+player: number, 
+/**
+ * Perfil de pontuacao usado, `null` nos scores feitos antes dos perfis existirem
+ */
+scoring_profile: string | null, 
+/**
+ * Pontos que vieram das notas seguradas e da estabilidade do sopro
+ */
+sustain_score: number, steadiness_score: number, 
+/**
+ * Media em porcentagem da duracao das notas que foi segurada, `null` sem notas avaliadas
+ */
+average_sustain: number | null, 
+/**
+ * Media em porcentagem da estabilidade do sopro nas notas seguradas
+ */
+average_steadiness: number | null, 
+/**
+ * Media em porcentagem do tempo de cada nota com a pressao dentro da zona alvo
+ */
+time_in_zone: number | null, 
+/**
+ * Porcentagem de precisao das notas julgadas, `null` sem notas julgadas. Nos scores feitos
+ * antes das notas existirem ela é calculada com os acertos salvos e os limites padrao
+ */
+accuracy: number | null, 
+/**
+ * Nota e estrelas (0 a 5) da tentativa, `null` se ela nao foi terminada
+ */
+grade: Grade | null, stars: number | null, };
\ No newline at end of file
@@ -0,0 +1,45 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ComboTier } from "./ComboTier";
+import type { GradeThresholds } from "./GradeThresholds";
+import type { PrecisionPoints } from "./PrecisionPoints";
+
+/**
+ * Regras de pontuacao, o id fica salvo em cada `Score` para comparar tentativas com regras diferentes
+ */
+export type ScoringProfile = { id: string, name: string, points: PrecisionPoints, 
+/**
+ * Vale a faixa com o maior `min_streak` alcançado, sem nenhuma o multiplicador é 1
+ */
+combo_tiers: Array<ComboTier>, 
+/**
+ * Pontos perdidos quando a nota passa sem ser tocada
+ */
+miss_penalty: number, 
+/**
+ * Pontos perdidos quando a nota é tocada cedo demais
+ */
+early_miss_penalty: number, 
+/**
+ * Maximo de pontos de uma nota depois do combo, `null` sem limite
+ */
+max_note_score: number | null, 
+/**
+ * Se o score total pode ficar abaixo de 0
+ */
+allow_negative_total: boolean, 
+/**
+ * Pontos de uma nota segurada do inicio ao fim
+ */
+sustain_points: number, 
+/**
+ * Pontos de uma nota segurada com a pressao constante
+ */
+steadiness_points: number, 
+/**
+ * Precisoes que dao a nota e as estrelas de cada tentativa
+ */
+grades: GradeThresholds, 
+/**
+ * Distancia da pressao alvo (a velocidade da nota, de 0 a 127) que ainda fica dentro da zona
+ */
+breath_zone_tolerance: number, };
\ No newline at end of file
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OnScoreUpdateMessage } from "./OnScoreUpdateMessage";
+
+/**
+ * Evento emitido quando uma nota da partitura termina, avaliando quanto dela o jogador segurou
+ * e quao estavel foi o sopro
+ */
+export type SustainFeedback = { player: number, 
+/**
+ * Nota midi da partitura
+ */
+key: number, 
+/**
+ * Porcentagem da duracao da nota que foi segurada
+ */
+sustain_percent: number, 
+/**
+ * Porcentagem de estabilidade da pressao, `null` se nao teve amostras suficientes
+ */
+steadiness_percent: number | null, 
+/**
+ * Porcentagem da duracao da nota com a pressao dentro da zona alvo
+ */
+time_in_zone_percent: number | null, sustain_score: number, steadiness_score: number, score: OnScoreUpdateMessage, };
\ No newline at end of file
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Curva aplicada na forca do sopro antes de repassar a nota
+ */
+export type ThruVelocityCurve = "Linear" | "Soft" | "Hard" | { "Fixed": number };
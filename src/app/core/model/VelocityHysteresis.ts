@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Velocity para ligar a nota e velocity abaixo da qual ela desliga
+ */
+export type VelocityHysteresis = { on_velocity: number, off_velocity: number, };
\ No newline at end of file
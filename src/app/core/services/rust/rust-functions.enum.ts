@@ -8,20 +8,74 @@
 export enum RustFunctionName {
     // dataSource service
     /**
-     * Conecta ao dispositivo midi com o nome esperado da ocarina
+     * (player?: number)
+     *
+     * Conecta ao dispositivo midi com o nome esperado da ocarina.
+     *
+     * Todas as funcoes de dispositivo recebem `player` opcional, o jogador dono do dispositivo (0 a 3).
+     * Cada jogador pode ter um dispositivo conectado ao mesmo tempo, sem `player` usa o jogador 0
      */
     connectMidi = "start_listening_midi",
     /**
-     * (port_name: String)
+     * (port_name: String, player?: number)
      *
      * Conecta a porta com o nome especificado. Idealmente ser um dos retornos de `listMidiDevices`
      */
     connectToMidiWithName = "connect_to_midi",
     /**
-     * Desconecta do dispositivo midi previamente conectado.
+     * (player?: number)
+     *
+     * Conecta um dispositivo midi virtual, para jogar pelo teclado sem o instrumento.
+     * As notas sao enviadas com `virtualMidiInput`
+     */
+    connectVirtualMidi = "connect_virtual_midi",
+    /**
+     * Devolve o nome de todas as portas seriais disponiveis
+     */
+    listSerialDevices = "list_serial_devices",
+    /**
+     * (port_name: string, baud_rate?: number, player?: number)
+     *
+     * Conecta a placa direto pela porta serial, sem precisar do adaptador midi.
+     * O baud rate padrao é 115200
+     */
+    connectToSerial = "connect_to_serial",
+    /**
+     * (key: number, velocity: number, on: boolean, player?: number)
+     *
+     * Envia uma nota para o dispositivo virtual, ela chega como se fosse do instrumento.
+     * Retorna erro se o dispositivo virtual nao estiver conectado
+     */
+    virtualMidiInput = "virtual_midi_input",
+    /**
+     * (output_path: string)
+     *
+     * Comeca a gravar os bytes crus recebidos do dispositivo do jogador 0 no arquivo.
+     * Pode ser chamada antes ou depois de conectar
+     */
+    startInputRecording = "start_input_recording",
+    /**
+     * Termina a gravacao e retorna quantas entradas foram gravadas
+     */
+    stopInputRecording = "stop_input_recording",
+    /**
+     * (input_path: string, speed: number, player?: number)
+     *
+     * Conecta uma sessao gravada com `startInputRecording` no lugar do dispositivo.
+     * `speed` 1 reproduz na velocidade original, valores maiores reproduzem mais rapido
+     */
+    replayInputSession = "replay_input_session",
+    /**
+     * (player?: number)
+     *
+     * Desconecta do dispositivo midi previamente conectado pelo jogador.
      * Faz nada se nao tiver conectado
      */
     stopMidi = "disconnect_midi",
+    /**
+     * Retorna a lista dos jogadores (number[]) que tem um dispositivo conectado
+     */
+    connectedPlayers = "connected_players",
     /**
      * Devolve uma lista de string com o nome da porta de todos
      * os dispositivos conectados
@@ -56,10 +110,34 @@ export enum RustFunctionName {
     /**
      * Notifica o lado do rust para voltar a emitir eventos de notas, só funciona se a música estiver
      * sido pausada.
+     * 
+     * Se o jogo foi pausado porque o dispositivo desconectou, retorna erro enquanto ele não voltar.
      */
     resumeGame = "resume_game",
     /**
-     * Devolve a lista de todas as musicas disponiveis (`MidiMusicList`)
+     * (countdown_seconds: number | null)
+     * 
+     * Quando o dispositivo reconecta depois de pausar o jogo, o jogo volta sozinho depois
+     * dessa contagem em segundos. `null` desliga e espera o `resumeGame`.
+     */
+    setGameAutoResume = "set_game_auto_resume",
+    /**
+     * (notes_per_turn: number | null)
+     *
+     * Liga o modo dueto, as notas da partitura sao divididas entre os jogadores 0 e 1, que se revezam
+     * a cada `notes_per_turn` notas. `MidiSignal.player` do `midiReadNote` diz quem deve tocar cada nota
+     * e cada jogador tem o seu `Score` salvo no fim da musica. `null` desliga.
+     *
+     * Vale na hora, mesmo com uma musica tocando, e a vez volta para o jogador 0.
+     */
+    setDuetMode = "set_duet_mode",
+    /**
+     * Retorna as notas de cada vez do modo dueto ou null se ele estiver desligado
+     */
+    duetMode = "duet_mode",
+    /**
+     * Devolve a lista de todas as musicas disponiveis (`MidiMusicList`), cada uma com a
+     * melhor nota das tentativas terminadas nela
      */
     listMusics = "list_musics",
     /**
@@ -74,11 +152,32 @@ export enum RustFunctionName {
      */
     remainingTime = "remaining_time",
     /**
-     * (on_note_message: OnNoteMessage)
+     * Retorna as `HitWindows` usadas para julgar as notas tocadas
+     */
+    hitWindows = "hit_windows",
+    /**
+     * (windows: HitWindows)
      *
-     * Adiciona ao acumulador de score, retorna `OnScoreUpdateMessage`
+     * Troca as janelas de acerto, salvas para o usuario atual. O score é calculado pelo backend
+     * e chega pelo evento `RustEventsName.noteJudgement`. Cada nota vale o melhor toque e so é
+     * julgada quando a janela `left_ms` fecha, a nao ser que o toque seja `Middle`.
+     */
+    setHitWindows = "set_hit_windows",
+    /**
+     * Retorna uma lista de `ScoringProfile` com todos os perfis de pontuacao disponiveis
      */
-    onNote = "on_note",
+    listScoringProfiles = "list_scoring_profiles",
+    /**
+     * Retorna o `ScoringProfile` que sera usado na proxima musica
+     */
+    activeScoringProfile = "active_scoring_profile",
+    /**
+     * (profile_id?: string)
+     *
+     * Escolhe o perfil de pontuacao do usuario atual e salva a escolha para ele.
+     * Sem `profile_id` volta para o perfil padrao. Retorna o `ScoringProfile` ativo
+     */
+    selectScoringProfile = "select_scoring_profile",
     /**
      * (music_id: number)
      *
@@ -96,6 +195,9 @@ export enum RustFunctionName {
      * Se `completed` for nulo, retorna scores com a musica finalizada e sem ter finalizado
      *
      * Se for vazia, uma lista vazia é retornada.
+     *
+     * Cada `Score` tem a precisao, a nota e as estrelas da tentativa, calculadas com as
+     * `GradeThresholds` do perfil de pontuacao usado nela.
      */
     listScores = "list_scores",
     /**
@@ -119,7 +221,331 @@ export enum RustFunctionName {
      * Remove a musica e todos os seus scores da base.
      */
     removeMusic = "remove_music",
-    
+    /**
+     * (music_id: number)
+     *
+     * Abre a musica no editor, retorna `EditableSong`.
+     *
+     * Todas as funcoes de edicao retornam o `EditableSong` atualizado, o `index` das notas
+     * pode mudar depois de cada edicao.
+     */
+    openSongEditor = "open_song_editor",
+    /**
+     * Fecha o editor descartando as alteracoes que nao foram salvas
+     */
+    closeSongEditor = "close_song_editor",
+    /**
+     * (index: number, start_tick: number, key: number)
+     *
+     * Move a nota para outro tick e/ou outra altura
+     */
+    editorMoveNote = "editor_move_note",
+    /**
+     * (index: number, duration_ticks: number)
+     */
+    editorResizeNote = "editor_resize_note",
+    /**
+     * (index: number, velocity: number)
+     *
+     * A velocidade da nota é o centro da zona alvo de pressao do sopro durante ela
+     */
+    editorSetNoteVelocity = "editor_set_note_velocity",
+    /**
+     * (note: EditableNote)
+     *
+     * O `index` da nota é ignorado
+     */
+    editorInsertNote = "editor_insert_note",
+    /**
+     * (index: number)
+     */
+    editorDeleteNote = "editor_delete_note",
+    /**
+     * (bpm: number)
+     *
+     * Troca o tempo da musica inteira
+     */
+    editorChangeTempo = "editor_change_tempo",
+    /**
+     * (new_music_name: string | null)
+     *
+     * Salva a musica editada, recalculando a duracao dela. Se `new_music_name` for nulo a musica
+     * original é sobrescrita, se não uma nova musica é criada com esse nome e o editor passa a
+     * editar a nova musica.
+     *
+     * Retorna o `MidiMusic` salvo
+     */
+    saveSongEditor = "save_song_editor",
+    /**
+     * (music_id: number, output_path: string)
+     *
+     * Gera um arquivo wav com a musica tocada por um sintetizador de ocarina, não precisa de
+     * nenhum dispositivo de audio ou saida midi.
+     */
+    renderMusicPreview = "render_music_preview",
+    /**
+     * (output_path: string)
+     *
+     * Gera um arquivo wav com tudo que o paciente tocou durante a ultima musica.
+     * A gravacao é feita automaticamente entre `startGame` e `endGame`/`stopGame`.
+     */
+    renderPerformance = "render_performance",
+    /**
+     * ()
+     *
+     * Lista as portas de saida midi (sintetizadores) disponiveis, usadas pelo acompanhamento e pelo midi thru
+     */
+    listMidiOutputPorts = "list_midi_output_ports",
+    /**
+     * (port_name: string | null)
+     *
+     * Escolhe a porta que vai receber o acompanhamento da musica, `null` desliga o acompanhamento.
+     * Retorna a `BackingTrackConfig` atualizada.
+     */
+    selectMidiOutputPort = "select_midi_output_port",
+    /**
+     * ()
+     *
+     * Retorna a `BackingTrackConfig` atual
+     */
+    backingTrackConfig = "backing_track_config",
+    /**
+     * (tracks: number[])
+     *
+     * Define quais trilhas da musica sao tocadas pelo paciente, o resto vira acompanhamento.
+     * Uma lista vazia coloca todas as trilhas na partitura.
+     */
+    setPlayerTracks = "set_player_tracks",
+    /**
+     * (track: number, muted: boolean)
+     *
+     * Muta ou desmuta uma trilha do acompanhamento, pode ser chamado durante a musica
+     */
+    setTrackMute = "set_track_mute",
+    /**
+     * (track: number, solo: boolean)
+     *
+     * Coloca ou tira uma trilha do acompanhamento do solo, pode ser chamado durante a musica
+     */
+    setTrackSolo = "set_track_solo",
+    /**
+     * (music_id: number)
+     *
+     * Lista as trilhas com notas de uma musica, retorna `MusicTrack[]`
+     */
+    listMusicTracks = "list_music_tracks",
+    /**
+     * (settings: MidiThruSettings)
+     *
+     * Liga o midi thru, repassando o que o paciente toca para `settings.port_name`
+     * com a transposicao e curva de velocity escolhidas. Se `port_name` for nulo o thru é desligado.
+     */
+    enableMidiThru = "enable_midi_thru",
+    /**
+     * (settings: MidiThruSettings)
+     *
+     * Atualiza a transposicao, curva de velocity e canal do thru ja ligado, `port_name` é ignorado
+     */
+    updateMidiThru = "update_midi_thru",
+    /**
+     * ()
+     *
+     * Desliga o midi thru
+     */
+    disableMidiThru = "disable_midi_thru",
+    /**
+     * ()
+     *
+     * Retorna a `MidiThruSettings` atual
+     */
+    midiThruSettings = "midi_thru_settings",
+    /**
+     * (port?: number, bind_address?: string, player?: number)
+     *
+     * Conecta outro programa (app de celular, sensor do laboratorio) como dispositivo do jogador,
+     * recebendo OSC por UDP na porta, padrao 9000. Sem `bind_address` so recebe mensagens do
+     * proprio computador (127.0.0.1), use `"0.0.0.0"` para aceitar sensores da rede. As mensagens aceitas sao `/inspirasom/note`
+     * com (nota, velocity), velocity 0 desliga a nota, e `/inspirasom/pressure` com (pressao).
+     * Valores inteiros vao de 0 a 127 e floats de 0.0 a 1.0
+     */
+    connectOscInput = "connect_osc_input",
+    /**
+     * (target: string)
+     *
+     * Envia as notas e a pressao de todos os jogadores por OSC para `target` (`ip:porta`, pode ser
+     * um endereco de broadcast). Os enderecos sao `/inspirasom/player/{jogador}/note` com
+     * (nota, velocity) e `/inspirasom/player/{jogador}/pressure` com (pressao). Retorna o endereco usado
+     */
+    enableOscOutput = "enable_osc_output",
+    disableOscOutput = "disable_osc_output",
+    /**
+     * Retorna o endereco da saida OSC ou null se ela estiver desligada
+     */
+    oscOutputTarget = "osc_output_target",
+    /**
+     * (player?: number)
+     *
+     * Retorna o `DeviceProfileInfo` do dispositivo conectado, ele define de onde vem a pressao do sopro
+     */
+    connectedDeviceProfile = "connected_device_profile",
+    /**
+     * (player?: number)
+     *
+     * Pergunta para o firmware da placa conectada a identificacao dela, retorna `DeviceFirmwareInfo`.
+     * So funciona com a ocarina conectada pela porta midi, outras placas respondem com erro.
+     */
+    connectedDeviceInfo = "connected_device_info",
+    /**
+     * (player?: number)
+     *
+     * Le a configuracao gravada no firmware da placa, retorna `DeviceSettings`.
+     */
+    deviceSettings = "device_settings",
+    /**
+     * (settings: DeviceSettings, player?: number)
+     *
+     * Grava a sensibilidade dos sensores e a tabela de notas no firmware, sem precisar regravar
+     * a placa. Retorna o `DeviceSettings` que a placa passou a usar.
+     */
+    writeDeviceSettings = "write_device_settings",
+    /**
+     * (port_name: string, firmware_path: string)
+     *
+     * Grava o firmware (`.hex` gerado pela IDE do Arduino ou `.bin`) na placa da porta serial,
+     * `port_name` vem do `listSerialDevices`. Se a placa estiver conectada ela é desconectada antes.
+     *
+     * O progresso chega em `RustEventsName.firmwareUploadProgress`. O firmware gravado é conferido e,
+     * se algo der errado, o firmware antigo é regravado e o erro indica isso.
+     */
+    uploadDeviceFirmware = "upload_device_firmware",
+    /**
+     * (player?: number)
+     *
+     * Retorna o `InputFilterSettings` do dispositivo conectado. O filtro segura os picos curtos
+     * de note on e note off que o sensor gera perto dos limites, o padrao vem do perfil do dispositivo.
+     */
+    inputFilterSettings = "input_filter_settings",
+    /**
+     * (settings: InputFilterSettings, player?: number)
+     *
+     * Troca o filtro do dispositivo conectado ate ele desconectar, retorna o `InputFilterSettings` usado.
+     */
+    updateInputFilter = "update_input_filter",
+    /**
+     * (player?: number)
+     *
+     * Retorna `InputFilterStats` com quantas mensagens cada parte do filtro segurou.
+     */
+    inputFilterStats = "input_filter_stats",
+    resetInputFilterStats = "reset_input_filter_stats",
+    /**
+     * (player?: number)
+     *
+     * Retorna a `BreathCurve` com todas as amostras de pressao do sopro desde o inicio da musica
+     */
+    breathCurve = "breath_curve",
+    /**
+     * (user_id?: string)
+     *
+     * Troca o usuario atual e carrega as preferencias salvas dele, como o instrumento, a calibracao do sopro,
+     * as janelas de acerto e o perfil de pontuacao.
+     * Sem `user_id` as preferencias nao sao salvas
+     */
+    setCurrentUser = "set_current_user",
+    /**
+     * Retorna o id do usuario atual ou null
+     */
+    currentUser = "current_user",
+    /**
+     * Retorna uma lista de `InstrumentInfo` com todos os instrumentos disponiveis
+     */
+    listInstruments = "list_instruments",
+    /**
+     * Retorna o `InstrumentInfo` usado para nomear as notas recebidas e da partitura.
+     * O instrumento do usuario tem prioridade sobre o do dispositivo conectado
+     */
+    activeInstrument = "active_instrument",
+    /**
+     * (instrument_id?: string)
+     *
+     * Escolhe o instrumento do usuario atual e salva a escolha para ele.
+     * Sem `instrument_id` volta a usar o instrumento do dispositivo. Retorna o `InstrumentInfo` ativo
+     */
+    selectInstrument = "select_instrument",
+    /**
+     * (music_id: number)
+     *
+     * Retorna as notas da partitura do paciente que o instrumento ativo nao consegue tocar,
+     * uma lista vazia significa que a musica pode ser tocada
+     */
+    checkMusicInstrument = "check_music_instrument",
+    /**
+     * (step: BreathCalibrationStep, player?: number)
+     *
+     * Começa a gravar uma etapa da calibracao do sopro do jogador, o dispositivo dele precisa estar conectado.
+     * As etapas sao `Resting` (sem soprar), `Comfortable` e `Maximum`, cada uma termina
+     * com `finishBreathCalibrationStep`.
+     */
+    startBreathCalibrationStep = "start_breath_calibration_step",
+    /**
+     * Termina a etapa que esta sendo gravada e retorna o valor medido nela (0 a 127).
+     */
+    finishBreathCalibrationStep = "finish_breath_calibration_step",
+    /**
+     * Termina a calibracao depois das 3 etapas, a curva passa a ser aplicada no sopro
+     * (`MidiSignal.velocity` e `BREATH_PRESSURE`) do jogador calibrado. So a curva do
+     * jogador principal é salva para o usuario atual.
+     *
+     * Retorna `BreathCalibrationInfo`.
+     */
+    saveBreathCalibration = "save_breath_calibration",
+    /**
+     * Descarta a calibracao em andamento, a curva atual continua sendo usada.
+     */
+    cancelBreathCalibration = "cancel_breath_calibration",
+    /**
+     * (player?: number)
+     *
+     * Retorna a `BreathCalibrationInfo` usada agora pelo jogador.
+     */
+    breathCalibration = "breath_calibration",
+    /**
+     * (player?: number)
+     *
+     * Volta para a curva padrao do jogador, a calibracao salva do usuario atual so é
+     * apagada para o jogador principal.
+     */
+    resetBreathCalibration = "reset_breath_calibration",
+    /**
+     * (interval_ms: number | undefined, clicks: number | undefined, player?: number)
+     *
+     * Começa a calibracao da latencia do dispositivo do jogador, o paciente toca junto com
+     * os cliques enviados em `RustEventsName.latencyClick`. Os 4 primeiros cliques sao so para
+     * pegar o ritmo. Padrao de 16 cliques a cada 750ms.
+     *
+     * Retorna a duracao da calibracao em ms, depois dela chamar `finishLatencyCalibration`.
+     */
+    startLatencyCalibration = "start_latency_calibration",
+    /**
+     * Termina a calibracao e retorna a latencia medida em ms, ela passa a ser descontada das
+     * notas recebidas do jogador calibrado e é salva para o dispositivo dele.
+     *
+     * Da erro se o paciente nao acompanhou pelo menos metade dos cliques.
+     */
+    finishLatencyCalibration = "finish_latency_calibration",
+    cancelLatencyCalibration = "cancel_latency_calibration",
+    /**
+     * (player?: number)
+     *
+     * Retorna a latencia do jogador usada agora em ms, tambem enviada em `MidiSignal.latency_ms`.
+     */
+    inputLatency = "input_latency",
+    /**
+     * (latency_ms: number, player?: number)
+     *
+     * Troca a latencia do dispositivo do jogador sem calibrar e salva para ele.
+     */
+    setInputLatency = "set_input_latency",
 }
 
 /**
@@ -129,7 +555,8 @@ export enum RustFunctionName {
  */
 export enum RustEventsName {
     /**
-     * Evento que periodicamente envia `MidiSignal` que vem do arduino.
+     * Evento que periodicamente envia `MidiSignal` que vem do arduino, `player` diz de qual
+     * jogador veio a nota.
      *
      * Chamar `RustFunctionName.connectMidi` para começar a emitir.
      */
@@ -141,6 +568,28 @@ export enum RustEventsName {
      * Chamar `RustFunctionName.startGame` para começar a emitir.
      */
     midiReadNote = "MIDI_READ_NOTE",
+    /**
+     * Evento com o `NoteJudgement` de cada nota da partitura, comparando com as notas que
+     * chegaram do dispositivo do jogador dentro das `HitWindows`. Substitui o antigo `on_note`.
+     *
+     * Chamar `RustFunctionName.startGame` para começar a emitir.
+     */
+    noteJudgement = "NOTE_JUDGEMENT",
+    /**
+     * Evento com o `SustainFeedback` de cada nota da partitura quando ela termina, com quanto dela
+     * o jogador segurou e quao estavel foi a pressao. Notas muito curtas nao sao avaliadas.
+     *
+     * Chamar `RustFunctionName.startGame` para começar a emitir.
+     */
+    noteSustain = "NOTE_SUSTAIN",
+    /**
+     * Evento com o `BreathZoneFeedback` sempre que a pressao do jogador entra ou sai da zona alvo
+     * de uma nota da partitura. A zona é a velocidade da nota com a margem `breath_zone_tolerance`
+     * do `ScoringProfile` ativo para cima e para baixo.
+     *
+     * Chamar `RustFunctionName.startGame` para começar a emitir.
+     */
+    breathZone = "BREATH_ZONE",
     /**
      * Evento para receber atualizacoes de estado da musica sendo tocada
      *
@@ -152,11 +601,40 @@ export enum RustEventsName {
     /**
      * Evento para receber sinais de conexao com o dispositivo MIDI.
      * 
-     * Envia apenas um boolean, false quando for desconectado, true quando estiver conectado
+     * Envia um `DeviceConnectionState`, `connected` diz se o jogador `player` tem um dispositivo
+     * conectado e `reason` o motivo da mudança.
+     * 
+     * Você vai receber esse evento quando conectar e desconectar, mas tambem pode receber
+     * `DeviceDetached` em qualquer momento enquanto tiver um dispositivo conectado,
+     * a verificação é feita a cada 1 segundo.
+     * 
+     * Quando a ultima porta usada volta ela é reconectada sozinha e o evento chega com `Reconnected`,
+     * ou `ReconnectFailed` se não foi possivel. Depois de `stopMidi` a porta não é reconectada.
+     */
+    midiDeviceState = "MIDI_DEVICE_CONNECTION",
+    /**
+     * Evento que envia a `BreathPressure` de cada jogador em uma taxa fixa enquanto
+     * ele tiver um dispositivo conectado, a taxa vem do `DeviceProfileInfo`.
+     */
+    breathPressure = "BREATH_PRESSURE",
+    /**
+     * Evento enviado em cada clique da calibracao de latencia, com o indice do clique.
+     *
+     * Chamar `RustFunctionName.startLatencyCalibration` para começar a emitir.
+     */
+    latencyClick = "LATENCY_CLICK",
+    /**
+     * Evento com o `FirmwareUploadProgress` enquanto `RustFunctionName.uploadDeviceFirmware` grava a placa.
+     */
+    firmwareUploadProgress = "FIRMWARE_UPLOAD_PROGRESS",
+    /**
+     * Evento enviado quando o dispositivo desconecta no meio da musica, o jogo é pausado sozinho.
+     * 
+     * O tipo retornado é `GameInterruption`, `DeviceLost` quando pausa, `WaitingResume` quando o
+     * dispositivo volta e o jogo espera o `resumeGame`, `ResumeCountdown` a cada segundo da
+     * contagem para voltar sozinho e `Resumed` quando a musica volta a tocar.
      * 
-     * Você vai receber esse evento quando conectar e desconectar, mas tambem pode receber `false` em 
-     * qualquer momento enquanto tiver um dispositivo conectado, 
-     * a verificação é feita a cada 3 segundos.
+     * As interrupcoes ficam salvas no `Score` da sessao.
      */
-    midiDeviceState = "MIDI_DEVICE_CONNECTION"
+    gameInterruption = "GAME_INTERRUPTION"
 }
\ No newline at end of file